@@ -1,4 +1,4 @@
-use clap::{ArgAction, Parser, ValueEnum, ValueHint};
+use clap::{ArgAction, Parser, Subcommand, ValueEnum, ValueHint};
 use org_exporter::{ConfigOptions, Exporter};
 use serde::Deserialize;
 
@@ -7,7 +7,12 @@ use serde::Deserialize;
 #[command(author = "Laith Bahodi <laithbahodi@gmail.com>")]
 #[command(about = "Exporter for Org Mode Content")]
 #[command(author, version, about, long_about=None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Cli {
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+
     /// Default is html
     #[arg(short, long, value_enum)]
     pub backend: Option<Backend>,
@@ -16,6 +21,8 @@ pub struct Cli {
     ///
     /// If the input is a directory, `org-rust` will walk and export every file
     /// to the output directory maintaining the directory structure.
+    // the empty default only takes effect for subcommands, which lift the requirement
+    #[arg(required = true, default_value = "", hide_default_value = true)]
     #[arg(value_hint = ValueHint::FilePath)]
     pub input: String,
 
@@ -24,6 +31,7 @@ pub struct Cli {
     /// The output type corresponds to the type of the input. I.e. if the input path is a file
     /// then the output path will be a file, same for a directory.
     #[arg(short, long, value_hint = ValueHint::AnyPath)]
+    #[arg(required = true, default_value = "", hide_default_value = true)]
     pub output: String,
 
     /// Path to config file
@@ -37,6 +45,32 @@ pub struct Cli {
     /// Prints files that are being processed / copied
     #[arg(short, long, action = ArgAction::SetTrue)]
     pub verbose: bool,
+
+    /// Run `sh`, `bash` and `python` source blocks to refresh their results
+    ///
+    /// The org backend writes the new `#+RESULTS:` after every block, keeping the
+    /// rest of the document as written. The html backend shows them for blocks
+    /// exporting their results.
+    #[arg(short, long, action = ArgAction::SetTrue)]
+    #[serde(default)]
+    pub evaluate: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Write source blocks to the files named by their `:tangle` header argument
+    ///
+    /// Relative paths are resolved from the directory of the org file. Blocks
+    /// tangled to the same file are written in document order.
+    Tangle {
+        /// Org files to tangle
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        input: Vec<String>,
+
+        /// Print the files that are written
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        verbose: bool,
+    },
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize, Default)]
@@ -1,11 +1,11 @@
 use anyhow::bail;
-use org_exporter::ConfigOptions;
+use org_exporter::{ConfigOptions, resolve_path};
 use std::fs::{self, OpenOptions, read_to_string};
 use std::io::{BufWriter, Read, Write, stdout};
 use std::path::{Path, PathBuf};
 use template::Template;
 use types::{CliError, InpType, OutType};
-use utils::{mkdir_recursively, relative_path_from};
+use utils::mkdir_recursively;
 
 use clap::Parser;
 
@@ -24,6 +24,10 @@ fn main() {
 /// Function that works through the entire pipeline
 fn run() -> anyhow::Result<()> {
     let cli_params = cli::Cli::parse();
+    if let Some(cli::Command::Tangle { input, verbose }) = &cli_params.command {
+        return tangle(input, *verbose);
+    }
+
     let config_params: cli::Cli;
 
     if let Some(config_path) = cli_params.config {
@@ -61,7 +65,12 @@ fn run() -> anyhow::Result<()> {
         config_params.verbose
     };
 
+    let evaluate = cli_params.evaluate || config_params.evaluate;
+
     let backend = backend.unwrap_or_default();
+    // evaluating into org writes the results back into the document, which keeps
+    // everything else as written
+    let write_back = evaluate && backend == cli::Backend::Org;
 
     let f = Path::new(&input_path);
     if !f.exists() {
@@ -156,7 +165,9 @@ fn run() -> anyhow::Result<()> {
                 }
             }
 
-            let conf = ConfigOptions::new(Some(file_path.to_path_buf()));
+            let conf = ConfigOptions::new(Some(file_path.to_path_buf()))
+                .with_block_evaluation(evaluate)
+                .with_write_back(write_back);
             if let Err(err_vec) = backend.export(&parser_output, &mut exported_content, conf) {
                 let mut build_str = String::new();
                 for e in err_vec {
@@ -169,7 +180,7 @@ fn run() -> anyhow::Result<()> {
             // handle a template (if needed)
             if let Some(template_path) = parser_output.keywords.get("template_path") {
                 // evaluate relative paths if needed
-                let template_path = relative_path_from(file_path, Path::new(template_path))?;
+                let template_path = resolve_path(Some(file_path), Path::new(template_path));
                 let template_contents = std::fs::read_to_string(&template_path).map_err(|e| {
                     CliError::from(e)
                         .with_path(&template_path)
@@ -278,3 +289,34 @@ fn run() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Writes the source blocks of every input file to the files they are tangled to
+fn tangle(inputs: &[String], verbose: bool) -> anyhow::Result<()> {
+    let mut stdout = BufWriter::new(stdout());
+
+    for input in inputs {
+        let file_path = Path::new(input);
+        let file_contents = read_to_string(file_path).map_err(|e| {
+            CliError::from(e)
+                .with_path(file_path)
+                .with_cause("failed to read input file")
+        })?;
+
+        let parser_output = org_parser::parse_org(&file_contents);
+        let files = org_exporter::tangle_files(&parser_output, Some(file_path))?;
+
+        if verbose {
+            writeln!(stdout, "input: {}", file_path.display())?;
+            for file in files {
+                writeln!(
+                    stdout,
+                    " -- tangled {} block(s): {}",
+                    file.blocks,
+                    file.path.display()
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use crate::{types::CliError, utils::relative_path_from};
+use crate::types::CliError;
+use org_exporter::resolve_path;
 use org_parser::Parser;
 use std::{fs::read_to_string, path::Path};
 
@@ -194,7 +195,7 @@ impl<'a, 'template> Template<'a, 'template> {
                         }
                     }
                     Command::Include(file) => {
-                        let include_path = relative_path_from(self.template_path, Path::new(file))?;
+                        let include_path = resolve_path(Some(self.template_path), Path::new(file));
                         let included_template = read_to_string(&include_path).map_err(|e| {
                             CliError::from(e)
                                 .with_path(&include_path)
@@ -0,0 +1,72 @@
+//! Resolve babel calls against named source blocks
+//!
+//! Calls are not evaluated, they render the `#+RESULTS:` stored
+//! alongside the called block.
+
+use org_parser::element::{Block, Exports};
+use org_parser::{Expr, NodeID, Parser};
+use thiserror::Error;
+
+use crate::ConfigOptions;
+use crate::runner::runs_at_export;
+
+/// Finds the stored results of the source block named `name`.
+pub(crate) fn call_results(parser: &Parser, name: &str) -> Result<NodeID, BabelError> {
+    let block_id = *parser
+        .names
+        .get(name)
+        .ok_or_else(|| BabelError::UndefinedBlock { name: name.into() })?;
+
+    if !matches!(parser.pool[block_id].obj, Expr::Block(Block::Src { .. })) {
+        return Err(BabelError::NotSrcBlock { name: name.into() });
+    }
+
+    parser
+        .results(block_id)
+        .ok_or_else(|| BabelError::NoResults { name: name.into() })
+}
+
+/// What is exported of a source block, inline source block or babel call: its
+/// code, its results, both or neither.
+pub(crate) fn exports(parser: &Parser, node_id: NodeID, conf: &ConfigOptions) -> Exports {
+    if conf.write_back() {
+        Exports::Both
+    } else {
+        parser.header_args(node_id).exports
+    }
+}
+
+/// Whether the code of the source block at `node_id` is left out of the export,
+/// along with its affiliated keywords.
+pub(crate) fn hides_code(parser: &Parser, node_id: NodeID, conf: &ConfigOptions) -> bool {
+    matches!(parser.pool[node_id].obj, Expr::Block(Block::Src { .. }))
+        && matches!(
+            exports(parser, node_id, conf),
+            Exports::Results | Exports::None
+        )
+}
+
+/// Whether the `#+RESULTS:` keyword or `{{{results(...)}}}` macro at `results_id` is
+/// left out of the export: hidden by the `:exports` of the node it belongs to, or
+/// replaced by running that node.
+pub(crate) fn skips_results(parser: &Parser, results_id: NodeID, conf: &ConfigOptions) -> bool {
+    let Some(owner_id) = parser.results_owner(results_id) else {
+        return false;
+    };
+    match exports(parser, owner_id, conf) {
+        Exports::Code | Exports::None => true,
+        Exports::Results | Exports::Both => {
+            conf.evaluate_blocks() && runs_at_export(parser, owner_id)
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BabelError {
+    #[error("call to undefined source block `{name}`")]
+    UndefinedBlock { name: String },
+    #[error("`{name}` does not name a source block")]
+    NotSrcBlock { name: String },
+    #[error("source block `{name}` has no #+RESULTS:")]
+    NoResults { name: String },
+}
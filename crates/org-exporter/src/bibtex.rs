@@ -0,0 +1,324 @@
+//! Reads BibTeX databases into a [`Bibliography`]
+//!
+//! Only the parts needed to format citations are handled:
+//!
+//! - `@type{key, field = value, ...}` entries, with either braces or parentheses
+//! - values delimited by braces, quotes, bare numbers or `@string` macros,
+//!   concatenated with `#`
+//! - `@string` definitions, and the predefined month macros
+//!
+//! `@comment` and `@preamble` are skipped, as is any text between entries.
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::citation::{BibEntry, Bibliography};
+
+const MONTHS: [(&str, &str); 12] = [
+    ("jan", "January"),
+    ("feb", "February"),
+    ("mar", "March"),
+    ("apr", "April"),
+    ("may", "May"),
+    ("jun", "June"),
+    ("jul", "July"),
+    ("aug", "August"),
+    ("sep", "September"),
+    ("oct", "October"),
+    ("nov", "November"),
+    ("dec", "December"),
+];
+
+#[derive(Debug, Error)]
+#[error("line {line}: {msg}")]
+pub struct BibtexError {
+    pub line: usize,
+    pub msg: &'static str,
+}
+
+struct BibCursor<'a> {
+    src: &'a str,
+    index: usize,
+    strings: HashMap<String, String>,
+}
+
+pub(crate) fn parse_bibtex(src: &str) -> Result<Bibliography, BibtexError> {
+    let mut cursor = BibCursor {
+        src,
+        index: 0,
+        strings: MONTHS
+            .iter()
+            .map(|(abbr, month)| (abbr.to_string(), month.to_string()))
+            .collect(),
+    };
+    let mut bibliography = Bibliography::new();
+
+    while let Some(at) = cursor.rest().find('@') {
+        cursor.index += at + 1;
+        let kind = cursor.ident().to_ascii_lowercase();
+        cursor.skip_ws();
+
+        let close = match cursor.curr() {
+            Some(b'{') => b'}',
+            Some(b'(') => b')',
+            // stray `@` in a comment
+            _ => continue,
+        };
+        cursor.index += 1;
+
+        match kind.as_str() {
+            "comment" | "preamble" => {
+                cursor.index -= 1;
+                cursor.skip_delimited()?;
+            }
+            "string" => {
+                cursor.skip_ws();
+                let (name, val) = cursor.field()?;
+                cursor.strings.insert(name, val);
+                cursor.skip_ws();
+                cursor.expect(close, "unterminated @string")?;
+            }
+            _ => {
+                cursor.skip_ws();
+                let key = cursor
+                    .fn_while(|chr| {
+                        !matches!(chr, b',' | b'}' | b')') && !chr.is_ascii_whitespace()
+                    })
+                    .to_owned();
+                if key.is_empty() {
+                    return Err(cursor.error("entry without a key"));
+                }
+
+                let mut fields = HashMap::new();
+                loop {
+                    cursor.skip_ws();
+                    match cursor.curr() {
+                        Some(b',') => {
+                            cursor.index += 1;
+                            cursor.skip_ws();
+                            // trailing comma
+                            if cursor.curr() == Some(close) {
+                                continue;
+                            }
+                            let (name, val) = cursor.field()?;
+                            fields.insert(name, val);
+                        }
+                        Some(chr) if chr == close => {
+                            cursor.index += 1;
+                            break;
+                        }
+                        _ => return Err(cursor.error("expected `,` or end of entry")),
+                    }
+                }
+
+                bibliography.insert(key, BibEntry { kind, fields });
+            }
+        }
+    }
+
+    Ok(bibliography)
+}
+
+impl<'a> BibCursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.index..]
+    }
+
+    fn curr(&self) -> Option<u8> {
+        self.src.as_bytes().get(self.index).copied()
+    }
+
+    fn error(&self, msg: &'static str) -> BibtexError {
+        BibtexError {
+            line: self.src[..self.index.min(self.src.len())]
+                .lines()
+                .count()
+                .max(1),
+            msg,
+        }
+    }
+
+    fn expect(&mut self, chr: u8, msg: &'static str) -> Result<(), BibtexError> {
+        if self.curr() == Some(chr) {
+            self.index += 1;
+            Ok(())
+        } else {
+            Err(self.error(msg))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        self.fn_while(|chr| chr.is_ascii_whitespace());
+    }
+
+    fn fn_while(&mut self, func: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.index;
+        while self.curr().is_some_and(&func) {
+            self.index += 1;
+        }
+        &self.src[start..self.index]
+    }
+
+    fn ident(&mut self) -> &'a str {
+        self.fn_while(|chr| chr.is_ascii_alphanumeric() || b"_-:.+/'".contains(&chr))
+    }
+
+    // name = value # value
+    fn field(&mut self) -> Result<(String, String), BibtexError> {
+        let name = self.ident().to_ascii_lowercase();
+        if name.is_empty() {
+            return Err(self.error("expected a field name"));
+        }
+        self.skip_ws();
+        self.expect(b'=', "expected `=` after field name")?;
+
+        let mut val = String::new();
+        loop {
+            self.skip_ws();
+            match self.curr() {
+                Some(b'{') => {
+                    let start = self.index;
+                    self.skip_delimited()?;
+                    val.push_str(&self.src[start + 1..self.index - 1]);
+                }
+                Some(b'"') => {
+                    self.index += 1;
+                    let start = self.index;
+                    let mut depth = 0;
+                    loop {
+                        match self.curr() {
+                            Some(b'{') => depth += 1,
+                            Some(b'}') => depth -= 1,
+                            Some(b'"') if depth == 0 => break,
+                            None => return Err(self.error("unterminated string")),
+                            _ => {}
+                        }
+                        self.index += 1;
+                    }
+                    val.push_str(&self.src[start..self.index]);
+                    self.index += 1;
+                }
+                Some(chr) if chr.is_ascii_digit() => {
+                    val.push_str(self.fn_while(|chr| chr.is_ascii_digit()));
+                }
+                _ => {
+                    let name = self.ident().to_ascii_lowercase();
+                    if name.is_empty() {
+                        return Err(self.error("expected a field value"));
+                    }
+                    // BibTeX treats undefined macros as empty
+                    if let Some(expansion) = self.strings.get(&name) {
+                        val.push_str(expansion);
+                    }
+                }
+            }
+
+            self.skip_ws();
+            if self.curr() == Some(b'#') {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+
+        Ok((name, clean_value(&val)))
+    }
+
+    /// Skips a balanced `{...}` or `(...)` group, the cursor must be on the opening delimiter.
+    fn skip_delimited(&mut self) -> Result<(), BibtexError> {
+        let (open, close) = match self.curr() {
+            Some(b'(') => (b'(', b')'),
+            _ => (b'{', b'}'),
+        };
+        let mut depth = 0;
+        loop {
+            match self.curr() {
+                Some(chr) if chr == open => depth += 1,
+                Some(chr) if chr == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.index += 1;
+                        return Ok(());
+                    }
+                }
+                None => return Err(self.error("unbalanced delimiters")),
+                _ => {}
+            }
+            self.index += 1;
+        }
+    }
+}
+
+/// Drops case-protecting braces and collapses whitespace.
+fn clean_value(val: &str) -> String {
+    val.split_ascii_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(['{', '}'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn basic_entries() {
+        let input = r#"
+Anything outside of entries is a comment.
+
+@Book{doe2020,
+  author    = {Doe, Jane and Roe, Richard},
+  title     = "On {T}hings",
+  year      = 2020,
+  publisher = {Some
+               Press},
+}
+
+@article(smith, title = {A {{Nested}} Title}, journal = {J})
+"#;
+        let bib = parse_bibtex(input).unwrap();
+
+        assert_eq!(bib.len(), 2);
+        let doe = &bib["doe2020"];
+        assert_eq!(doe.kind, "book");
+        assert_eq!(doe.fields["author"], "Doe, Jane and Roe, Richard");
+        assert_eq!(doe.fields["title"], "On Things");
+        assert_eq!(doe.fields["year"], "2020");
+        assert_eq!(doe.fields["publisher"], "Some Press");
+
+        let smith = &bib["smith"];
+        assert_eq!(smith.kind, "article");
+        assert_eq!(smith.fields["title"], "A Nested Title");
+    }
+
+    #[test]
+    fn string_macros() {
+        let input = r#"
+@string{ acm = "ACM Press" }
+@comment{ @book{ignored, title = {no}} }
+@inproceedings{key,
+  publisher = acm # { Ltd.},
+  month = jan,
+  note = undefined,
+}
+"#;
+        let bib = parse_bibtex(input).unwrap();
+
+        assert_eq!(bib.len(), 1);
+        assert_eq!(bib["key"].fields["publisher"], "ACM Press Ltd.");
+        assert_eq!(bib["key"].fields["month"], "January");
+        assert_eq!(bib["key"].fields["note"], "");
+    }
+
+    #[test]
+    fn invalid_bibtex() {
+        let err = parse_bibtex("@book{key,\n  title = {oops\n").unwrap_err();
+        assert_eq!(err.line, 2);
+
+        assert!(parse_bibtex("@book{, title = {x}}").is_err());
+        assert!(parse_bibtex("@book{key title = {x}}").is_err());
+    }
+}
@@ -0,0 +1,450 @@
+//! Citation processing
+//!
+//! Exporters don't format citations themselves, they hand them to a
+//! [`CitationProcessor`], which decides how each citation is rendered and
+//! what ends up in the bibliography.
+//!
+//! [`AuthorYear`] is used unless [`ConfigOptions`](crate::ConfigOptions)
+//! specifies otherwise.
+//!
+//! Entries are read from the BibTeX files named in `#+bibliography:` keywords,
+//! and listed wherever `#+print_bibliography:` appears.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use org_parser::object::{Citation, CitationReference};
+use org_parser::{Expr, NodeID, Parser};
+use thiserror::Error;
+
+use crate::bibtex::{BibtexError, parse_bibtex};
+use crate::types::{ConfigOptions, ExportError, FileError, LogicErrorKind};
+use crate::utils::resolve_path;
+
+/// A work that can be cited.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BibEntry {
+    /// The type of the entry, e.g.: `article`, `book`.
+    pub kind: String,
+    /// Lowercased field names mapped to their values, e.g.: `author`, `year`, `title`.
+    pub fields: HashMap<String, String>,
+}
+
+/// Citation keys mapped to the entries they refer to.
+pub type Bibliography = HashMap<String, BibEntry>;
+
+/// Formats citations and bibliographies.
+///
+/// A processor is created fresh for every export, and sees citations in
+/// document order.
+pub trait CitationProcessor: Debug {
+    /// Renders a single citation as plain text.
+    fn cite(
+        &mut self,
+        citation: &Citation,
+        references: &[&CitationReference],
+        bibliography: &Bibliography,
+    ) -> String;
+
+    /// Renders the entries that should be listed in the bibliography,
+    /// in the order they should appear.
+    fn bibliography(&self, bibliography: &Bibliography) -> Vec<String>;
+}
+
+/// Constructor for a [`CitationProcessor`], used by [`ConfigOptions`](crate::ConfigOptions).
+pub type ProcessorFactory = fn() -> Box<dyn CitationProcessor>;
+
+/// Author-year citations, e.g.: `(Doe, 2020)`.
+///
+/// Supports the following styles:
+///
+/// | style         | output        |
+/// |---------------+---------------|
+/// | (none)        | (Doe, 2020)   |
+/// | `text`, `t`   | Doe (2020)    |
+/// | `author`, `a` | Doe           |
+/// | `noauthor`, `na` | (2020)     |
+/// | `nocite`, `n` |               |
+///
+/// The bibliography lists every cited work, sorted by author.
+#[derive(Debug, Default)]
+pub struct AuthorYear {
+    cited: Vec<String>,
+}
+
+impl AuthorYear {
+    pub fn boxed() -> Box<dyn CitationProcessor> {
+        Box::new(Self::default())
+    }
+}
+
+impl CitationProcessor for AuthorYear {
+    fn cite(
+        &mut self,
+        citation: &Citation,
+        references: &[&CitationReference],
+        bibliography: &Bibliography,
+    ) -> String {
+        for reference in references {
+            if !self.cited.iter().any(|key| key == reference.key) {
+                self.cited.push(reference.key.to_owned());
+            }
+        }
+
+        // style/variant: we only care about the style
+        let style = citation
+            .style
+            .map(|style| style.split('/').next().unwrap_or_default());
+
+        let (open, close) = match style {
+            Some("nocite" | "n") => return String::new(),
+            Some("text" | "t" | "author" | "a") => ("", ""),
+            _ => ("(", ")"),
+        };
+
+        let refs: Vec<String> = references
+            .iter()
+            .map(|reference| {
+                let entry = bibliography.get(reference.key);
+                let author = entry
+                    .and_then(short_author)
+                    .unwrap_or_else(|| reference.key.to_owned());
+                let year = entry.and_then(year).unwrap_or("n.d.");
+
+                let body = match style {
+                    Some("text" | "t") => format!("{author} ({year})"),
+                    Some("author" | "a") => author,
+                    Some("noauthor" | "na") => year.to_owned(),
+                    _ => format!("{author}, {year}"),
+                };
+
+                let prefix = reference.prefix.map(str::trim).unwrap_or_default();
+                let suffix = reference.suffix.map(str::trim).unwrap_or_default();
+                [prefix, &body, suffix]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+
+        let prefix = citation.prefix.map(str::trim).unwrap_or_default();
+        let suffix = citation.suffix.map(str::trim).unwrap_or_default();
+        let inner = [prefix, &refs.join("; "), suffix]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!("{open}{inner}{close}")
+    }
+
+    fn bibliography(&self, bibliography: &Bibliography) -> Vec<String> {
+        let mut entries: Vec<(String, String)> = self
+            .cited
+            .iter()
+            .map(|key| {
+                let Some(entry) = bibliography.get(key) else {
+                    return (key.clone(), key.clone());
+                };
+
+                let author = entry
+                    .fields
+                    .get("author")
+                    .or(entry.fields.get("editor"))
+                    .map(|authors| authors.split(" and ").collect::<Vec<_>>().join(", "));
+                let sort_key = short_author(entry).unwrap_or_else(|| key.clone());
+
+                let mut parts = Vec::new();
+                if let Some(author) = author {
+                    parts.push(author);
+                }
+                parts.push(year(entry).unwrap_or("n.d.").to_owned());
+                if let Some(title) = entry.fields.get("title") {
+                    parts.push(title.clone());
+                }
+                for container in ["journal", "booktitle", "publisher"] {
+                    if let Some(val) = entry.fields.get(container) {
+                        parts.push(val.clone());
+                    }
+                }
+                (sort_key, format!("{}.", parts.join(". ")))
+            })
+            .collect();
+
+        entries.sort_by_key(|(sort_key, _)| sort_key.to_lowercase());
+        entries.into_iter().map(|(_, text)| text).collect()
+    }
+}
+
+/// The output of a [`CitationProcessor`] for a single document.
+#[derive(Debug, Default)]
+pub(crate) struct Citations {
+    /// Rendered text of each [`Citation`] node.
+    pub(crate) rendered: HashMap<NodeID, String>,
+    pub(crate) bibliography: Vec<String>,
+}
+
+impl Citations {
+    /// Loads the document's bibliography files, then runs every citation
+    /// through the configured processor.
+    ///
+    /// This happens before exporting so that `#+print_bibliography:` can
+    /// appear before the citations it lists.
+    ///
+    /// Citation keys are only checked when the document names at least
+    /// one bibliography.
+    pub(crate) fn process(
+        parser: &Parser,
+        conf: &ConfigOptions,
+        errors: &mut Vec<ExportError>,
+    ) -> Self {
+        let mut citation_ids = Vec::new();
+        let mut bib_ids = Vec::new();
+        collect_citations(
+            parser,
+            parser.pool.root_id(),
+            &mut citation_ids,
+            &mut bib_ids,
+        );
+        if citation_ids.is_empty() {
+            return Self::default();
+        }
+
+        let mut bibliography = Bibliography::new();
+        for id in &bib_ids {
+            let node = &parser.pool[*id];
+            let Expr::Keyword(keyword) = &node.obj else {
+                unreachable!()
+            };
+            match load_bibliography(keyword.val, conf) {
+                Ok(entries) => bibliography.extend(entries),
+                Err(e) => errors.push(ExportError::LogicError {
+                    span: node.start..node.end,
+                    source: LogicErrorKind::Citation(e),
+                }),
+            }
+        }
+
+        let mut processor = conf.citation_processor()();
+        let mut rendered = HashMap::new();
+        for id in citation_ids {
+            let Expr::Citation(citation) = &parser.pool[id].obj else {
+                unreachable!()
+            };
+            let mut references = Vec::new();
+            for ref_id in &citation.references {
+                let node = &parser.pool[*ref_id];
+                let Expr::CitationReference(reference) = &node.obj else {
+                    continue;
+                };
+                if !bib_ids.is_empty() && !bibliography.contains_key(reference.key) {
+                    errors.push(ExportError::LogicError {
+                        span: node.start..node.end,
+                        source: LogicErrorKind::Citation(CitationError::UnknownKey {
+                            key: reference.key.into(),
+                        }),
+                    });
+                }
+                references.push(reference);
+            }
+
+            rendered.insert(id, processor.cite(citation, &references, &bibliography));
+        }
+
+        Self {
+            rendered,
+            bibliography: processor.bibliography(&bibliography),
+        }
+    }
+}
+
+/// Collects citations and `#+bibliography:` keywords in document order.
+fn collect_citations(
+    parser: &Parser,
+    id: NodeID,
+    citations: &mut Vec<NodeID>,
+    bibliographies: &mut Vec<NodeID>,
+) {
+    let obj = &parser.pool[id].obj;
+    match obj {
+        Expr::Citation(_) => {
+            citations.push(id);
+            return;
+        }
+        Expr::Keyword(keyword) if keyword.key.eq_ignore_ascii_case("bibliography") => {
+            bibliographies.push(id);
+        }
+        Expr::Heading(heading) => {
+            if let Some(title) = &heading.title {
+                for child in &title.1 {
+                    collect_citations(parser, *child, citations, bibliographies);
+                }
+            }
+        }
+        _ => {}
+    }
+    if let Some(children) = obj.children() {
+        for child in children {
+            collect_citations(parser, *child, citations, bibliographies);
+        }
+    }
+}
+
+/// Reads the BibTeX file named by a `#+bibliography:` keyword.
+fn load_bibliography(value: &str, conf: &ConfigOptions) -> Result<Bibliography, CitationError> {
+    let file = Path::new(value.trim_matches('"'));
+    let path = resolve_path(conf.file_path().as_deref(), file);
+    let contents = read_to_string(&path).map_err(|e| FileError {
+        context: "failed to read bibliography ".into(),
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+
+    parse_bibtex(&contents).map_err(|source| CitationError::Bibtex {
+        path: path.into(),
+        source,
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum CitationError {
+    #[error("{0}")]
+    IoError(#[from] FileError),
+    #[error("invalid bibliography `{path}`: {source}")]
+    Bibtex { path: PathBuf, source: BibtexError },
+    #[error("no bibliography entry for citation key `{key}`")]
+    UnknownKey { key: String },
+}
+
+/// `Doe`, `Doe and Roe`, or `Doe et al.`
+fn short_author(entry: &BibEntry) -> Option<String> {
+    let authors = entry.fields.get("author").or(entry.fields.get("editor"))?;
+    let names: Vec<&str> = authors.split(" and ").map(last_name).collect();
+
+    Some(match names.as_slice() {
+        [] => return None,
+        [one] => (*one).to_owned(),
+        [one, two] => format!("{one} and {two}"),
+        [one, ..] => format!("{one} et al."),
+    })
+}
+
+/// Handles both `Last, First` and `First Last`.
+fn last_name(name: &str) -> &str {
+    let name = name.trim();
+    if let Some((last, _)) = name.split_once(',') {
+        last.trim()
+    } else {
+        name.rsplit(' ').next().unwrap_or(name)
+    }
+}
+
+fn year(entry: &BibEntry) -> Option<&str> {
+    if let Some(year) = entry.fields.get("year") {
+        Some(year)
+    } else {
+        // biblatex: YYYY-MM-DD
+        entry.fields.get("date").and_then(|date| date.get(..4))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use org_parser::object::{Citation, CitationReference};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn bib() -> Bibliography {
+        let entry = |author: &str, year: &str, title: &str| BibEntry {
+            kind: "book".into(),
+            fields: HashMap::from([
+                ("author".into(), author.into()),
+                ("year".into(), year.into()),
+                ("title".into(), title.into()),
+            ]),
+        };
+        HashMap::from([
+            ("doe".into(), entry("Doe, Jane", "2020", "On Things")),
+            (
+                "many".into(),
+                entry("Ann Smith and Bob Jones and Cat Lee", "1999", "Stuff"),
+            ),
+        ])
+    }
+
+    fn cite(style: Option<&str>, refs: &[&CitationReference]) -> String {
+        let citation = Citation {
+            style,
+            prefix: None,
+            suffix: None,
+            references: Vec::new(),
+        };
+        AuthorYear::default().cite(&citation, refs, &bib())
+    }
+
+    #[test]
+    fn author_year_styles() {
+        let doe = CitationReference {
+            key: "doe",
+            prefix: None,
+            suffix: None,
+        };
+        let many = CitationReference {
+            key: "many",
+            prefix: Some("see "),
+            suffix: Some(" p. 4"),
+        };
+
+        assert_eq!(cite(None, &[&doe]), "(Doe, 2020)");
+        assert_eq!(
+            cite(None, &[&doe, &many]),
+            "(Doe, 2020; see Smith et al., 1999 p. 4)"
+        );
+        assert_eq!(cite(Some("t"), &[&doe]), "Doe (2020)");
+        assert_eq!(cite(Some("text/b"), &[&doe]), "Doe (2020)");
+        assert_eq!(cite(Some("na"), &[&doe]), "(2020)");
+        assert_eq!(cite(Some("author"), &[&doe]), "Doe");
+        assert_eq!(cite(Some("nocite"), &[&doe]), "");
+    }
+
+    #[test]
+    fn unknown_key() {
+        let missing = CitationReference {
+            key: "missing",
+            prefix: None,
+            suffix: None,
+        };
+        assert_eq!(cite(None, &[&missing]), "(missing, n.d.)");
+    }
+
+    #[test]
+    fn bibliography_sorted() {
+        let citation = Citation {
+            style: None,
+            prefix: None,
+            suffix: None,
+            references: Vec::new(),
+        };
+        let mut processor = AuthorYear::default();
+        for key in ["many", "doe", "many"] {
+            let reference = CitationReference {
+                key,
+                prefix: None,
+                suffix: None,
+            };
+            processor.cite(&citation, &[&reference], &bib());
+        }
+
+        assert_eq!(
+            processor.bibliography(&bib()),
+            [
+                "Doe, Jane. 2020. On Things.",
+                "Ann Smith, Bob Jones, Cat Lee. 1999. Stuff."
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Regenerates the contents of dynamic blocks
+//!
+//! ```org
+//! #+BEGIN: word-count :scope file
+//! stale contents
+//! #+END:
+//! ```
+//!
+//! When a generator is registered for a block's name, its output replaces
+//! the contents of the block in every exporter, so the [`Org`](crate::Org)
+//! exporter writes the refreshed block back. Blocks without a generator keep
+//! their contents.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use org_parser::element::DynamicBlock;
+use org_parser::{NodeID, Parser};
+
+/// Produces the new contents of a dynamic block as Org source.
+///
+/// Receives the block, its id and the document it belongs to. Generators may
+/// capture state, such as a database handle or a clock.
+pub type BlockGenerator = Arc<dyn Fn(&DynamicBlock, NodeID, &Parser) -> String + Send + Sync>;
+
+/// Generators for dynamic blocks, keyed by case-insensitive block name.
+#[derive(Clone, Default)]
+pub struct GeneratorRegistry {
+    generators: HashMap<String, BlockGenerator>,
+}
+
+impl fmt::Debug for GeneratorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.generators.keys()).finish()
+    }
+}
+
+impl GeneratorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `generator` for blocks named `name`, replacing any previous one.
+    pub fn register(
+        &mut self,
+        name: &str,
+        generator: impl Fn(&DynamicBlock, NodeID, &Parser) -> String + Send + Sync + 'static,
+    ) {
+        self.generators
+            .insert(name.to_ascii_lowercase(), Arc::new(generator));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BlockGenerator> {
+        self.generators.get(&name.to_ascii_lowercase())
+    }
+
+    /// Runs the generator registered for the block, if any.
+    pub(crate) fn generate(
+        &self,
+        block: &DynamicBlock,
+        node_id: NodeID,
+        parser: &Parser,
+    ) -> Option<String> {
+        self.get(block.name)
+            .map(|generator| generator(block, node_id, parser))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty(_: &DynamicBlock, _: NodeID, _: &Parser) -> String {
+        String::new()
+    }
+
+    #[test]
+    fn case_insensitive_names() {
+        let mut registry = GeneratorRegistry::new();
+        registry.register("ClockTable", empty);
+
+        assert!(registry.get("clocktable").is_some());
+        assert!(registry.get("CLOCKTABLE").is_some());
+        assert!(registry.get("index").is_none());
+    }
+}
@@ -0,0 +1,124 @@
+//! Decides which headings make it into an export
+//!
+//! Follows Org's rules for trimming a document before exporting it:
+//!
+//! - headings marked `COMMENT` are left out along with their subtree
+//! - headings tagged with one of `#+EXCLUDE_TAGS` (`noexport` by default) are left out
+//! - headings tagged `ARCHIVE` only keep their headline
+//! - once any heading is tagged with one of `#+SELECT_TAGS` (`export` by default),
+//!   only the selected subtrees and the headings leading to them are exported
+//!
+//! Tags inherited from parent headings and `#+FILETAGS:` count as well, and
+//! group tags from `#+TAGS:` match any of their members.
+
+use std::collections::HashSet;
+
+use org_parser::{Expr, NodeID, Parser};
+
+const ARCHIVE_TAG: &str = "ARCHIVE";
+
+/// How much of a heading should be exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Visibility {
+    /// The headline and its contents.
+    Full,
+    /// Only the headline.
+    Headline,
+    /// Nothing at all.
+    Hidden,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExportFilter {
+    exclude_tags: Vec<String>,
+    /// Headings kept by the select tags, `None` when nothing is selected.
+    selected: Option<HashSet<NodeID>>,
+}
+
+impl ExportFilter {
+    pub(crate) fn new(parser: &Parser) -> Self {
+        let exclude_tags =
+            tag_keyword(parser, "exclude_tags").unwrap_or_else(|| vec!["noexport".into()]);
+        let select_tags =
+            tag_keyword(parser, "select_tags").unwrap_or_else(|| vec!["export".into()]);
+
+        let mut selected = HashSet::new();
+        select(parser, parser.pool.root_id(), &select_tags, &mut selected);
+
+        Self {
+            exclude_tags,
+            selected: if selected.is_empty() {
+                None
+            } else {
+                Some(selected)
+            },
+        }
+    }
+
+    /// Determines the visibility of a heading or inline task.
+    ///
+    /// Any other node is always exported in full.
+    pub(crate) fn visibility(&self, parser: &Parser, node_id: NodeID) -> Visibility {
+        let comment = match &parser.pool[node_id].obj {
+            Expr::Heading(inner) => inner.comment,
+            Expr::InlineTask(inner) => inner.comment,
+            _ => return Visibility::Full,
+        };
+        if comment {
+            return Visibility::Hidden;
+        }
+
+        if self
+            .exclude_tags
+            .iter()
+            .any(|excluded| parser.has_tag(node_id, excluded))
+        {
+            return Visibility::Hidden;
+        }
+        if let Some(selected) = &self.selected
+            && !selected.contains(&node_id)
+        {
+            return Visibility::Hidden;
+        }
+
+        if parser.has_tag(node_id, ARCHIVE_TAG) {
+            Visibility::Headline
+        } else {
+            Visibility::Full
+        }
+    }
+}
+
+fn tag_keyword(parser: &Parser, name: &str) -> Option<Vec<String>> {
+    parser
+        .keywords
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, val)| val.split_ascii_whitespace().map(String::from).collect())
+}
+
+/// Collects the selected headings under `node_id` along with their ancestors.
+///
+/// Returns whether anything in the subtree was selected.
+fn select(
+    parser: &Parser,
+    node_id: NodeID,
+    select_tags: &[String],
+    selected: &mut HashSet<NodeID>,
+) -> bool {
+    let obj = &parser.pool[node_id].obj;
+    let headline = matches!(obj, Expr::Heading(_) | Expr::InlineTask(_));
+
+    let mut keep = headline
+        && select_tags
+            .iter()
+            .any(|select| parser.has_tag(node_id, select));
+    for id in obj.children().into_iter().flatten() {
+        keep |= select(parser, *id, select_tags, selected);
+    }
+
+    if keep && headline {
+        selected.insert(node_id);
+    }
+    keep
+}
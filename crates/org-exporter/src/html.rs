@@ -4,19 +4,27 @@
 
 use core::fmt;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 
 use latex2mathml::{DisplayStyle, latex_to_mathml};
 use memchr::memchr3_iter;
-use org_parser::element::{Affiliated, Block, CheckBox, ListKind, TableRow};
-use org_parser::object::{LatexFragment, PathReg, PlainOrRec};
+use org_parser::element::{
+    Affiliated, Alignment, Block, CheckBox, ColumnFormat, ColumnGroup, Exports, ListKind, Switches,
+    TableRow,
+};
+use org_parser::object::{LatexFragment, PathReg, PlainOrRec, Timestamp, TimestampEnd};
 use org_parser::{Expr, Node, NodeID, Parser, parse_macro_call, parse_org};
 
 use crate::ExportError;
+use crate::babel::{call_results, exports, skips_results};
+use crate::citation::Citations;
+use crate::filter::{ExportFilter, Visibility};
 use crate::include::include_handle;
+use crate::noweb::{NowebContext, expand_noweb};
 use crate::org_macros::macro_handle;
-use crate::types::{ConfigOptions, Exporter, ExporterInner, LogicErrorKind};
+use crate::runner::{run_block, runs_at_export, stored_results};
+use crate::types::{ConfigOptions, DeepHeadings, Exporter, ExporterInner, LogicErrorKind};
 use crate::utils::{Options, TocItem, process_toc};
 use phf::phf_set;
 
@@ -63,7 +71,13 @@ pub struct Html<'buf> {
     footnotes: Vec<NodeID>,
     footnote_ids: HashMap<NodeID, usize>,
     conf: ConfigOptions,
+    opts: Options,
+    citations: Citations,
+    filter: ExportFilter,
     errors: Vec<ExportError>,
+    /// Stored results left out by `:exports` or replaced by running their blocks,
+    /// see [`runner`](crate::runner).
+    skipped: HashSet<NodeID>,
 }
 
 /// Wrapper around strings that need to be properly HTML escaped.
@@ -128,17 +142,22 @@ impl<'buf> Exporter<'buf> for Html<'buf> {
         buf: &'buf mut T,
         conf: ConfigOptions,
     ) -> core::result::Result<(), Vec<ExportError>> {
+        let opts = Options::handle_opts(parsed).unwrap_or_default();
+        let mut errors = Vec::new();
+        let citations = Citations::process(parsed, &conf, &mut errors);
         let mut obj = Html {
             buf,
             footnotes: Vec::new(),
             footnote_ids: HashMap::new(),
             conf,
-            errors: Vec::new(),
+            opts,
+            citations,
+            filter: ExportFilter::new(parsed),
+            errors,
+            skipped: HashSet::new(),
         };
 
-        if let Ok(opts) = Options::handle_opts(parsed)
-            && let Ok(tocs) = process_toc(parsed, &opts)
-        {
+        if let Ok(tocs) = process_toc(parsed, &opts, &obj.filter) {
             handle_toc(parsed, &mut obj, &tocs);
         }
 
@@ -213,7 +232,11 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
             footnotes: Vec::new(),
             footnote_ids: HashMap::new(),
             conf,
+            opts: Options::default(),
+            citations: Citations::default(),
+            filter: ExportFilter::default(),
             errors: Vec::new(),
+            skipped: HashSet::new(),
         };
 
         obj.export_rec(&parsed.pool.root_id(), &parsed);
@@ -225,6 +248,9 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
     }
 
     fn export_rec(&mut self, node_id: &NodeID, parser: &Parser) {
+        if self.skipped.contains(node_id) {
+            return;
+        }
         let node = &parser.pool[*node_id];
         match &node.obj {
             Expr::Root(inner) => {
@@ -233,25 +259,72 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                 }
             }
             Expr::Heading(inner) => {
-                let heading_number: u8 = inner.heading_level.into();
+                let visibility = self.filter.visibility(parser, *node_id);
+                if visibility == Visibility::Hidden {
+                    return;
+                }
+                let level: u8 = inner.heading_level.into();
+                let heading_number = level.min(6);
+                let as_list = level > 6 && self.conf.deep_headings() == DeepHeadings::List;
 
-                w!(self, "<h{heading_number}");
+                if as_list {
+                    w!(self, "<ul>\n<li");
+                } else {
+                    w!(self, "<h{heading_number}");
+                    if level > 6 {
+                        self.class(&format!("level-{level}"));
+                    }
+                }
                 self.prop(node);
                 w!(self, ">");
 
+                self.todo_keyword(inner.keyword, inner.done);
+
                 if let Some(title) = &inner.title {
                     for id in &title.1 {
                         self.export_rec(id, parser);
                     }
                 }
 
-                w!(self, "</h{heading_number}>\n");
+                if as_list {
+                    w!(self, "\n");
+                } else {
+                    w!(self, "</h{heading_number}>\n");
+                }
+
+                if visibility == Visibility::Full
+                    && self.opts.planning
+                    && let Some(planning) = &inner.planning
+                {
+                    w!(self, "<p");
+                    self.class("planning");
+                    w!(self, ">");
+                    let mut sep = "";
+                    for (name, ts) in [
+                        ("SCHEDULED", &planning.scheduled),
+                        ("DEADLINE", &planning.deadline),
+                        ("CLOSED", &planning.closed),
+                    ] {
+                        if let Some(ts) = ts {
+                            w!(self, r#"{sep}<span class="timestamp-kwd">{name}:</span> "#);
+                            self.timestamp(ts);
+                            sep = " ";
+                        }
+                    }
+                    w!(self, "</p>\n");
+                }
 
-                if let Some(children) = &inner.children {
+                if visibility == Visibility::Full
+                    && let Some(children) = &inner.children
+                {
                     for id in children {
                         self.export_rec(id, parser);
                     }
                 }
+
+                if as_list {
+                    w!(self, "</li>\n</ul>\n");
+                }
             }
             Expr::Block(inner) => {
                 match inner {
@@ -328,6 +401,7 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                     }
                     Block::Example {
                         parameters,
+                        switches,
                         contents,
                     } => {
                         if parameters.get("exports").is_some_and(|&x| x == "none") {
@@ -336,7 +410,9 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                         w!(self, "<pre");
                         self.class("example");
                         self.prop(node);
-                        w!(self, ">\n{}</pre>\n", HtmlEscape(contents));
+                        w!(self, ">\n");
+                        self.code(contents, switches);
+                        w!(self, "</pre>\n");
                     }
                     Block::Export {
                         backend,
@@ -351,21 +427,37 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                         }
                     }
                     Block::Src {
-                        language,
-                        parameters,
-                        contents,
+                        language, switches, ..
                     } => {
-                        if parameters.get("exports").is_some_and(|&x| x == "none") {
-                            return;
+                        let exports = exports(parser, *node_id, &self.conf);
+                        if matches!(exports, Exports::Code | Exports::Both) {
+                            let contents =
+                                match expand_noweb(parser, *node_id, NowebContext::Export) {
+                                    Ok(contents) => contents,
+                                    Err(e) => {
+                                        self.errors().push(ExportError::LogicError {
+                                            span: e.span(),
+                                            source: LogicErrorKind::Noweb(e),
+                                        });
+                                        return;
+                                    }
+                                };
+                            w!(self, "<pre>");
+                            w!(self, "<code");
+                            self.class("src");
+                            if let Some(lang) = language {
+                                self.class(&format!("src-{}", lang));
+                            }
+                            self.prop(node);
+                            w!(self, ">\n");
+                            self.code(&contents, switches);
+                            w!(self, "</pre></code>\n");
                         }
-                        w!(self, "<pre>");
-                        w!(self, "<code");
-                        self.class("src");
-                        if let Some(lang) = language {
-                            self.class(&format!("src-{}", lang));
+                        if matches!(exports, Exports::Results | Exports::Both) {
+                            self.block_results(*node_id, parser);
+                        } else {
+                            self.skipped.extend(stored_results(parser, *node_id));
                         }
-                        self.prop(node);
-                        w!(self, ">\n{}</pre></code>\n", HtmlEscape(contents));
                     }
                     Block::Verse {
                         parameters,
@@ -387,7 +479,7 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                     PathReg::PlainLink(a) => a.into(),
                     PathReg::Id(a) => format!("#{a}"),
                     PathReg::CustomId(a) => format!("#{a}"),
-                    PathReg::Coderef(_) => todo!(),
+                    PathReg::Coderef(label) => format!("#coderef-{label}"),
                     PathReg::Unspecified(a) => {
                         let mut rita = String::new();
                         // see if the link is present in someone's target
@@ -432,6 +524,21 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                         for id in children {
                             self.export_rec(id, parser);
                         }
+                    } else if let PathReg::Coderef(label) = &inner.path.obj {
+                        // links show the line number when labels are removed from the code
+                        let line = parser.coderefs.get(label).and_then(|coderef| {
+                            match &parser.pool[coderef.block].obj {
+                                Expr::Block(
+                                    Block::Src { switches, .. } | Block::Example { switches, .. },
+                                ) if switches.remove_labels => Some(coderef.line),
+                                _ => None,
+                            }
+                        });
+                        if let Some(line) = line {
+                            w!(self, "{line}");
+                        } else {
+                            w!(self, "{}", HtmlEscape(label));
+                        }
                     } else {
                         w!(self, "{}", HtmlEscape(inner.path.to_str(parser.source)));
                     }
@@ -523,12 +630,17 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                 w!(self, "<!--{}-->", inner.0);
             }
             Expr::InlineSrc(inner) => {
-                w!(
-                    self,
-                    "<code class={}>{}</code>",
-                    inner.lang,
-                    HtmlEscape(inner.body)
-                );
+                if matches!(
+                    exports(parser, *node_id, &self.conf),
+                    Exports::Code | Exports::Both
+                ) {
+                    w!(
+                        self,
+                        "<code class={}>{}</code>",
+                        inner.lang,
+                        HtmlEscape(inner.body)
+                    );
+                }
                 // if let Some(args) = inner.headers {
                 //     w!(self, "[{args}]")?;
                 // }
@@ -553,6 +665,16 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                     //     source: LogicErrorKind::Include(e),
                     // })?;
                     w!(self, "</div>");
+                } else if inner.key.eq_ignore_ascii_case("print_bibliography")
+                    && !self.citations.bibliography.is_empty()
+                {
+                    w!(self, r#"<ul class="bibliography""#);
+                    self.prop(node);
+                    w!(self, ">\n");
+                    for entry in self.citations.bibliography.clone() {
+                        w!(self, "<li>{}</li>\n", HtmlEscape(entry));
+                    }
+                    w!(self, "</ul>\n");
                 }
             }
             Expr::LatexEnv(inner) => {
@@ -678,8 +800,52 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                     self.export_rec(&affiliate, parser);
                     w!(self, "</caption>\n");
                 }
+                if !inner.column_groups.is_empty() {
+                    for (i, group) in inner.column_groups.iter().enumerate() {
+                        let starts = i == 0
+                            || matches!(group, Some(ColumnGroup::Start | ColumnGroup::Single))
+                            || matches!(
+                                inner.column_groups[i - 1],
+                                Some(ColumnGroup::End | ColumnGroup::Single)
+                            );
+                        if starts {
+                            if i != 0 {
+                                w!(self, "</colgroup>\n");
+                            }
+                            w!(self, "<colgroup>\n");
+                        }
+                        w!(self, "<col />\n");
+                    }
+                    w!(self, "</colgroup>\n");
+                }
+
+                // rows above the first hrule go in the header, every other
+                // group of rows between hrules gets its own body
+                let header_len = inner.header_rows(&parser.pool).len();
+                let mut row_count = 0;
+                let mut section = None;
                 for id in &inner.children {
-                    self.export_rec(id, parser);
+                    match &parser.pool[*id].obj {
+                        Expr::TableRow(TableRow::Standard(cells)) => {
+                            let header = row_count < header_len;
+                            if section.is_none() {
+                                let tag = if header { "thead" } else { "tbody" };
+                                w!(self, "<{tag}>\n");
+                                section = Some(tag);
+                            }
+                            self.table_row(parser, cells, &inner.columns, header);
+                            row_count += 1;
+                        }
+                        Expr::TableRow(TableRow::Rule) => {
+                            if let Some(tag) = section.take() {
+                                w!(self, "</{tag}>\n");
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(tag) = section {
+                    w!(self, "</{tag}>\n");
                 }
 
                 w!(self, "</table>\n");
@@ -687,13 +853,18 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
 
             Expr::TableRow(inner) => {
                 match inner {
-                    TableRow::Rule => { /*skip*/ }
+                    TableRow::Rule | TableRow::Cookies(_) | TableRow::ColumnGroups(_) => {
+                        /*skip*/
+                    }
                     TableRow::Standard(stands) => {
-                        w!(self, "<tr>\n");
-                        for id in stands.iter() {
-                            self.export_rec(id, parser);
-                        }
-                        w!(self, "</tr>\n");
+                        let columns = node
+                            .parent
+                            .and_then(|parent| match &parser.pool[parent].obj {
+                                Expr::Table(table) => Some(table.columns.as_slice()),
+                                _ => None,
+                            })
+                            .unwrap_or_default();
+                        self.table_row(parser, stands, columns, false);
                     }
                 }
             }
@@ -747,6 +918,9 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                 );
             }
             Expr::Macro(macro_call) => {
+                if skips_results(parser, *node_id, &self.conf) {
+                    return;
+                }
                 let macro_contents = match macro_handle(parser, macro_call, self.config_opts()) {
                     Ok(contents) => contents,
                     Err(e) => {
@@ -777,6 +951,45 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                     self.export_rec(id, parser);
                 }
             }
+            Expr::InlineTask(inner) => {
+                let visibility = self.filter.visibility(parser, *node_id);
+                if visibility == Visibility::Hidden {
+                    return;
+                }
+                w!(self, "<div");
+                self.class("inlinetask");
+                self.prop(node);
+                w!(self, ">\n<b>");
+                self.todo_keyword(inner.keyword, inner.done);
+                if let Some(title) = &inner.title {
+                    for id in &title.1 {
+                        self.export_rec(id, parser);
+                    }
+                }
+                w!(self, "</b><br />\n");
+                if visibility == Visibility::Full
+                    && let Some(children) = &inner.children
+                {
+                    for id in children {
+                        self.export_rec(id, parser);
+                    }
+                }
+                w!(self, "</div>\n");
+            }
+            Expr::DynamicBlock(inner) => {
+                if let Some(contents) = self
+                    .conf
+                    .block_generators()
+                    .generate(inner, *node_id, parser)
+                {
+                    let parsed = parse_org(&contents);
+                    self.export_rec(&parsed.pool.root_id(), &parsed);
+                } else {
+                    for id in &inner.children {
+                        self.export_rec(id, parser);
+                    }
+                }
+            }
             Expr::ExportSnippet(inner) => {
                 if inner.backend == Html::backend_name() {
                     w!(self, "{}", inner.contents);
@@ -791,6 +1004,12 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                     self.export_rec(contents, parser);
                 }
                 Affiliated::Attr { .. } => {}
+                Affiliated::Results { child_id, .. } => {
+                    if skips_results(parser, *node_id, &self.conf) {
+                        self.skipped.extend(*child_id);
+                    }
+                }
+                Affiliated::Header { .. } => {}
             },
             Expr::MacroDef(_) => {}
             Expr::FootnoteDef(_) => {
@@ -836,6 +1055,110 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
                     index,
                 );
             }
+            Expr::Timestamp(inner) => {
+                self.timestamp(inner);
+            }
+            Expr::Citation(_) => {
+                if let Some(text) = self.citations.rendered.get(node_id).cloned()
+                    && !text.is_empty()
+                {
+                    w!(
+                        self,
+                        r#"<span class="citation">{}</span>"#,
+                        HtmlEscape(text)
+                    );
+                }
+            }
+            // handled by the parent citation
+            Expr::CitationReference(_) => {}
+            Expr::BabelCall(inner) => match call_results(parser, inner.name) {
+                Ok(results_id) => {
+                    // shown at the call even when left out after the called block
+                    self.skipped.remove(&results_id);
+                    self.export_rec(&results_id, parser)
+                }
+                Err(e) => self.errors().push(ExportError::LogicError {
+                    span: node.start..node.end,
+                    source: LogicErrorKind::Babel(e),
+                }),
+            },
+            Expr::InlineBabelCall(inner) => match call_results(parser, inner.name) {
+                // keep paragraph and fixed width results inline
+                Ok(results_id) => match &parser.pool[results_id].obj {
+                    Expr::Paragraph(par) => {
+                        for id in &par.0 {
+                            self.export_rec(id, parser);
+                        }
+                    }
+                    Expr::FixedWidth(fixed) => {
+                        w!(self, "<code>{}</code>", HtmlEscape(&fixed.contents));
+                    }
+                    _ => {
+                        self.skipped.remove(&results_id);
+                        self.export_rec(&results_id, parser)
+                    }
+                },
+                Err(e) => self.errors().push(ExportError::LogicError {
+                    span: node.start..node.end,
+                    source: LogicErrorKind::Babel(e),
+                }),
+            },
+            Expr::StatisticsCookie(inner) => {
+                w!(self, "<code>{inner}</code>");
+            }
+            Expr::FixedWidth(inner) => {
+                w!(self, "<pre");
+                self.class("example");
+                self.prop(node);
+                w!(self, ">\n{}\n</pre>\n", HtmlEscape(&inner.contents));
+            }
+            Expr::RadioTarget(inner) => {
+                w!(self, "<span");
+                self.prop(node);
+                w!(self, ">{}</span>", HtmlEscape(inner.0));
+            }
+            Expr::RadioLink(inner) => {
+                if let Some(target) = parser.targets.get(inner.target) {
+                    w!(
+                        self,
+                        r##"<a href="#{}">{}</a>"##,
+                        target,
+                        HtmlEscape(inner.contents)
+                    );
+                } else {
+                    w!(self, "{}", HtmlEscape(inner.contents));
+                }
+            }
+            Expr::Clock(inner) => {
+                if !self.opts.clocks {
+                    return;
+                }
+                w!(self, "<p");
+                self.class("clock");
+                self.prop(node);
+                w!(self, r#"><span class="timestamp-kwd">CLOCK:</span> "#);
+                self.timestamp(&Timestamp {
+                    active: false,
+                    start: inner.start,
+                    repeater: None,
+                    warning: None,
+                    end: inner.end.map(|end| TimestampEnd::Date {
+                        end,
+                        repeater: None,
+                        warning: None,
+                    }),
+                });
+                if let Some(duration) = inner.duration {
+                    let minutes = duration.as_secs() / 60;
+                    w!(
+                        self,
+                        r#" <span class="clock-duration">({}:{:02})</span>"#,
+                        minutes / 60,
+                        minutes % 60
+                    );
+                }
+                w!(self, "</p>\n");
+            }
         }
     }
 
@@ -849,10 +1172,39 @@ impl<'buf> ExporterInner<'buf> for Html<'buf> {
     fn errors(&mut self) -> &mut Vec<ExportError> {
         &mut self.errors
     }
+
+    fn citations(&mut self) -> &mut Citations {
+        &mut self.citations
+    }
 }
 
 // Writers for generic attributes
 impl<'buf> Html<'buf> {
+    /// Runs a source block when evaluation is enabled, writing its results in place
+    /// of the stored ones.
+    fn block_results(&mut self, block_id: NodeID, parser: &Parser) {
+        if !self.conf.evaluate_blocks() || !runs_at_export(parser, block_id) {
+            return;
+        }
+        self.skipped.extend(stored_results(parser, block_id));
+
+        let node = &parser.pool[block_id];
+        match run_block(parser, block_id, self.conf.file_path().as_deref()) {
+            Ok(Some(results)) => {
+                // results are exported as is, without running anything they contain
+                let conf = self.conf.clone().with_block_evaluation(false);
+                if let Err(mut err_vec) = Html::export_tree(&parse_org(&results), self, conf) {
+                    self.errors().append(&mut err_vec);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => self.errors().push(ExportError::LogicError {
+                span: node.start..node.end,
+                source: LogicErrorKind::Run(e),
+            }),
+        }
+    }
+
     /// Adds a property
     fn prop(&mut self, node: &Node) {
         // if the target needs an id
@@ -868,6 +1220,70 @@ impl<'buf> Html<'buf> {
         }
     }
 
+    /// Writes the contents of a src or example block, marking the lines with coderefs.
+    fn code(&mut self, contents: &str, switches: &Switches) {
+        for line in contents.split_inclusive('\n') {
+            if let Some((start, label)) = switches.coderef(line) {
+                let (code, newline) = if switches.strip_labels() {
+                    (line[..start].trim_end(), "\n")
+                } else {
+                    line.split_at(line.trim_end_matches('\n').len())
+                };
+                w!(
+                    self,
+                    r#"<span id="coderef-{}">{}</span>{newline}"#,
+                    HtmlEscape(label),
+                    HtmlEscape(code)
+                );
+            } else {
+                w!(self, "{}", HtmlEscape(line));
+            }
+        }
+    }
+
+    fn table_row(
+        &mut self,
+        parser: &Parser,
+        cells: &[NodeID],
+        columns: &[ColumnFormat],
+        header: bool,
+    ) {
+        let tag = if header { "th" } else { "td" };
+        w!(self, "<tr>\n");
+        for (i, id) in cells.iter().enumerate() {
+            w!(self, "<{tag}");
+            if header {
+                w!(self, r#" scope="col""#);
+            }
+            if let Some(align) = columns.get(i).and_then(|column| column.align) {
+                let align = match align {
+                    Alignment::Left => "left",
+                    Alignment::Right => "right",
+                    Alignment::Center => "center",
+                };
+                w!(self, r#" style="text-align: {align}""#);
+            }
+            w!(self, ">");
+            if let Expr::TableCell(cell) = &parser.pool[*id].obj {
+                for id in &cell.0 {
+                    self.export_rec(id, parser);
+                }
+            }
+            w!(self, "</{tag}>\n");
+        }
+        w!(self, "</tr>\n");
+    }
+
+    fn todo_keyword(&mut self, keyword: Option<&str>, done: bool) {
+        if self.opts.todo
+            && let Some(keyword) = keyword
+        {
+            let state = if done { "done" } else { "todo" };
+            let keyword = HtmlEscape(keyword);
+            w!(self, r#"<span class="{state} {keyword}">{keyword}</span> "#);
+        }
+    }
+
     fn class(&mut self, name: &str) {
         w!(self, r#" class="{name}""#);
     }
@@ -876,6 +1292,47 @@ impl<'buf> Html<'buf> {
         w!(self, r#" {}="{}""#, key, HtmlEscape(val));
     }
 
+    fn timestamp(&mut self, ts: &Timestamp) {
+        if let Some(TimestampEnd::Date {
+            end,
+            repeater,
+            warning,
+        }) = &ts.end
+        {
+            // give each half of the range its own <time>
+            self.time(&Timestamp {
+                end: None,
+                ..ts.clone()
+            });
+            w!(self, "&#x2013;");
+            self.time(&Timestamp {
+                active: ts.active,
+                start: *end,
+                repeater: *repeater,
+                warning: *warning,
+                end: None,
+            });
+        } else {
+            self.time(ts);
+        }
+    }
+
+    /// Writes a single, non-ranged timestamp as a `<time>` element
+    fn time(&mut self, ts: &Timestamp) {
+        let date = &ts.start.date;
+        w!(
+            self,
+            r#"<time datetime="{:04}-{:02}-{:02}"#,
+            date.year,
+            date.month,
+            date.day
+        );
+        if let Some(time) = &ts.start.time {
+            w!(self, "T{:02}:{:02}", time.hour, time.minute);
+        }
+        w!(self, r#"">{}</time>"#, HtmlEscape(ts.to_string()));
+    }
+
     fn exp_footnotes(&mut self, parser: &Parser) {
         if self.footnotes.is_empty() {
             return;
@@ -967,6 +1424,9 @@ impl fmt::Write for Html<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::citation::CitationError;
+    use crate::noweb::NowebError;
+    use crate::runner::RunError;
     use pretty_assertions::assert_eq;
 
     fn html_export(input: &str) -> String {
@@ -1441,6 +1901,446 @@ here
         )
     }
 
+    #[test]
+    fn timestamp() {
+        assert_eq!(
+            html_export("<2024-03-01 Fri 10:00 +1w>"),
+            r#"<p><time datetime="2024-03-01T10:00">&lt;2024-03-01 Fri 10:00 +1w&gt;</time></p>
+"#
+        );
+
+        assert_eq!(
+            html_export("[2024-03-01 Fri]--[2024-03-04 Mon]"),
+            r#"<p><time datetime="2024-03-01">[2024-03-01 Fri]</time>&#x2013;<time datetime="2024-03-04">[2024-03-04 Mon]</time></p>
+"#
+        );
+    }
+
+    #[test]
+    fn planning() {
+        let input = r"* TODO task
+SCHEDULED: <2024-03-01 Fri>
+";
+        assert_eq!(
+            html_export(input),
+            r#"<h1 id="task"><span class="todo TODO">TODO</span> task</h1>
+"#
+        );
+
+        let input = r"#+options: p:t
+* TODO task
+SCHEDULED: <2024-03-01 Fri> DEADLINE: <2024-03-08 Fri 12:00>
+";
+        assert_eq!(
+            html_export(input),
+            r#"<h1 id="task"><span class="todo TODO">TODO</span> task</h1>
+<p class="planning"><span class="timestamp-kwd">SCHEDULED:</span> <time datetime="2024-03-01">&lt;2024-03-01 Fri&gt;</time> <span class="timestamp-kwd">DEADLINE:</span> <time datetime="2024-03-08T12:00">&lt;2024-03-08 Fri 12:00&gt;</time></p>
+"#
+        );
+    }
+
+    #[test]
+    fn todo_keywords() {
+        let input = r"#+todo: TODO WAITING(w@) <NEXT> | DONE CANCELLED
+* WAITING reply
+* CANCELLED meeting
+* <NEXT> call
+* plain
+";
+        assert_eq!(
+            html_export(input),
+            r#"<h1 id="reply"><span class="todo WAITING">WAITING</span> reply</h1>
+<h1 id="meeting"><span class="done CANCELLED">CANCELLED</span> meeting</h1>
+<h1 id="call"><span class="todo &lt;NEXT&gt;">&lt;NEXT&gt;</span> call</h1>
+<h1 id="plain">plain</h1>
+"#
+        );
+
+        let input = r"#+options: todo:nil
+* TODO task
+";
+        assert_eq!(
+            html_export(input),
+            r#"<h1 id="task">task</h1>
+"#
+        );
+    }
+
+    #[test]
+    fn deep_headings() {
+        let input = r"****** six
+******* seven
+text
+******** eight
+";
+        assert_eq!(
+            html_export(input),
+            r#"<h6 id="six">six</h6>
+<h6 class="level-7" id="seven">seven</h6>
+<p>text</p>
+<h6 class="level-8" id="eight">eight</h6>
+"#
+        );
+
+        let conf = ConfigOptions::default().with_deep_headings(DeepHeadings::List);
+        assert_eq!(
+            Html::export(input, conf).unwrap(),
+            r#"<h6 id="six">six</h6>
+<ul>
+<li id="seven">seven
+<p>text</p>
+<ul>
+<li id="eight">eight
+</li>
+</ul>
+</li>
+</ul>
+"#
+        );
+    }
+
+    #[test]
+    fn export_filtering() {
+        let input = r"#+exclude_tags: private
+#+options: toc:t
+* Public
+text
+** Secret :private:
+*** Nested
+also hidden
+* COMMENT Drafts
+draft
+* Old :ARCHIVE:
+archived body
+** Old child
+* Notes :noexport:
+kept
+";
+        assert_eq!(
+            html_export(input),
+            r#"<nav id="table-of-contents" role="doc-toc">
+<h2>Table Of Contents</h2>
+<div id="text-table-of-contents" role="doc-toc">
+<ul><li><a href=#public>Public</a></li><li><a href=#old>Old</a></li><li><a href=#notes>Notes</a></li></ul></div></nav><h1 id="public">Public</h1>
+<p>text</p>
+<h1 id="old">Old</h1>
+<h1 id="notes">Notes</h1>
+<p>kept</p>
+"#
+        );
+    }
+
+    #[test]
+    fn select_tags() {
+        let input = r"intro
+* Skipped
+** Chosen :export:
+chosen text
+*** Child
+*************** inline task
+* Parent
+parent text
+** Unrelated
+* Private :export:noexport:
+";
+        assert_eq!(
+            html_export(input),
+            r#"<p>intro</p>
+<h1 id="skipped">Skipped</h1>
+<h2 id="chosen">Chosen</h2>
+<p>chosen text</p>
+<h3 id="child">Child</h3>
+<div class="inlinetask" id="inline-task">
+<b>inline task</b><br />
+</div>
+"#
+        );
+    }
+
+    #[test]
+    fn inline_task() {
+        let input = r"#+options: todo:t
+* Heading
+*************** TODO call <back>
+body
+*************** END
+after
+";
+        assert_eq!(
+            html_export(input),
+            r#"<h1 id="heading">Heading</h1>
+<div class="inlinetask" id="call-back">
+<b><span class="todo TODO">TODO</span> call &lt;back&gt;</b><br />
+<p>body</p>
+</div>
+<p>after</p>
+"#
+        );
+    }
+
+    #[test]
+    fn clock() {
+        let input = "CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 10:30] =>  1:30\n";
+        assert_eq!(html_export(input), "");
+        assert_eq!(html_export(&format!("#+options: c:nil\n{input}")), "");
+        assert_eq!(
+            html_export(&format!("#+options: c:t\n{input}")),
+            r#"<p class="clock"><span class="timestamp-kwd">CLOCK:</span> <time datetime="2024-03-01T09:00">[2024-03-01 Fri 09:00]</time>&#x2013;<time datetime="2024-03-01T10:30">[2024-03-01 Fri 10:30]</time> <span class="clock-duration">(1:30)</span></p>
+"#
+        );
+    }
+
+    #[test]
+    fn radio_target() {
+        let input = r"<<<Radio>>> targets link
+
+every radio mention.
+";
+        assert_eq!(
+            html_export(input),
+            r##"<p><span id="radio">Radio</span> targets link</p>
+<p>every <a href="#radio">radio</a> mention.</p>
+"##
+        );
+    }
+
+    #[test]
+    fn citation() {
+        let input = r"As shown [cite:see @doe p. 4; @roe], and [cite/t:@doe].
+
+#+print_bibliography:
+";
+        assert_eq!(
+            html_export(input),
+            r#"<p>As shown <span class="citation">(see doe, n.d. p. 4; roe, n.d.)</span>, and <span class="citation">doe (n.d.)</span>.</p>
+<ul class="bibliography">
+<li>doe</li>
+<li>roe</li>
+</ul>
+"#
+        );
+    }
+
+    #[test]
+    fn bibliography_file() {
+        let dir = std::env::temp_dir().join("org-rust-bibliography-file");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("refs.bib"),
+            r"@book{doe, author = {Doe, Jane}, year = 2020, title = {On Things}, publisher = {Press}}",
+        )
+        .unwrap();
+
+        let input = r"#+bibliography: refs.bib
+Cited [cite:@doe].
+
+#+print_bibliography:
+";
+        let conf = ConfigOptions::new(Some(dir.join("doc.org")));
+        assert_eq!(
+            Html::export(input, conf).unwrap(),
+            r#"<p>Cited <span class="citation">(Doe, 2020)</span>.</p>
+<ul class="bibliography">
+<li>Doe, Jane. 2020. On Things. Press.</li>
+</ul>
+"#
+        );
+
+        let input = r"#+bibliography: refs.bib
+Cited [cite:@doe; @nobody].
+";
+        let conf = ConfigOptions::new(Some(dir.join("doc.org")));
+        let errors = Html::export(input, conf).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let ExportError::LogicError { span, source } = &errors[0];
+        assert_eq!(&input[span.clone()], " @nobody");
+        assert!(matches!(
+            source,
+            LogicErrorKind::Citation(CitationError::UnknownKey { key }) if key == "nobody"
+        ));
+
+        let conf = ConfigOptions::new(Some(dir.join("doc.org")));
+        let errors = Html::export("#+bibliography: missing.bib\n[cite:@doe]\n", conf).unwrap_err();
+        assert!(matches!(
+            errors[0],
+            ExportError::LogicError {
+                source: LogicErrorKind::Citation(CitationError::IoError(_)),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn included_citation() {
+        let dir = std::env::temp_dir().join("org-rust-included-citation");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("part.org"), "Included [cite:@roe].\n").unwrap();
+
+        let input = r"Cited [cite:@doe].
+
+#+include: part.org
+";
+        let conf = ConfigOptions::new(Some(dir.join("doc.org")));
+        assert_eq!(
+            Html::export(input, conf).unwrap(),
+            r#"<p>Cited <span class="citation">(doe, n.d.)</span>.</p>
+<div class="org-include"><p>Included <span class="citation">(roe, n.d.)</span>.</p>
+</div>"#
+        );
+    }
+
+    #[test]
+    fn dynamic_block() {
+        use org_parser::element::DynamicBlock;
+
+        fn headings(_: &DynamicBlock, _: NodeID, parser: &Parser) -> String {
+            let mut ret = String::new();
+            for node in parser.pool.iter() {
+                if let Expr::Heading(heading) = &node.obj
+                    && let Some(title) = &heading.title
+                {
+                    ret.push_str(&format!("- {}\n", title.0));
+                }
+            }
+            ret
+        }
+
+        let input = r"#+BEGIN: index
+- stale
+#+END:
+#+BEGIN: unknown :param x
+kept
+#+END:
+* One
+* Two
+";
+        let conf = ConfigOptions::default().with_block_generator("index", headings);
+        assert_eq!(
+            Html::export(input, conf).unwrap(),
+            r#"<ul>
+<li><p>One</p>
+</li>
+<li><p>Two</p>
+</li>
+</ul>
+<p>kept</p>
+<h1 id="one">One</h1>
+<h1 id="two">Two</h1>
+"#
+        );
+    }
+
+    #[test]
+    fn custom_citation_processor() {
+        use crate::citation::{Bibliography, CitationProcessor};
+        use org_parser::object::{Citation, CitationReference};
+
+        #[derive(Debug)]
+        struct Keys;
+        impl CitationProcessor for Keys {
+            fn cite(
+                &mut self,
+                _: &Citation,
+                references: &[&CitationReference],
+                _: &Bibliography,
+            ) -> String {
+                references
+                    .iter()
+                    .map(|r| r.key)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }
+            fn bibliography(&self, _: &Bibliography) -> Vec<String> {
+                vec!["<entry>".into()]
+            }
+        }
+
+        let conf = ConfigOptions::default().with_citation_processor(|| Box::new(Keys));
+        assert_eq!(
+            Html::export("[cite:@a;@b]\n#+print_bibliography:\n", conf).unwrap(),
+            r#"<p><span class="citation">a,b</span></p>
+<ul class="bibliography">
+<li>&lt;entry&gt;</li>
+</ul>
+"#
+        );
+    }
+
+    #[test]
+    fn statistics_cookie() {
+        assert_eq!(
+            html_export("progress [40%]\n"),
+            "<p>progress <code>[40%]</code></p>\n"
+        );
+    }
+
+    #[test]
+    fn babel_call() {
+        let input = r"#+name: answer
+#+begin_src python
+return 42
+#+end_src
+
+#+RESULTS: answer
+The answer is 42.
+
+#+call: answer()
+
+Inline: call_answer().
+";
+        assert_eq!(
+            html_export(input),
+            r#"<pre><code class="src" class="src-python" id="answer">
+return 42
+</pre></code>
+<p>The answer is 42.</p>
+<p>Inline: The answer is 42..</p>
+"#
+        );
+
+        let input = r"#+name: empty
+#+begin_src python
+#+end_src
+
+#+call: empty()
+#+call: missing()
+";
+        let errors = Html::export(input, ConfigOptions::default()).unwrap_err();
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "44-60: source block `empty` has no #+RESULTS:",
+                "60-78: call to undefined source block `missing`"
+            ]
+        );
+    }
+
+    #[test]
+    fn fixed_width() {
+        let input = r"#+name: answer
+#+begin_src python :exports both
+return 42
+#+end_src
+
+#+RESULTS:
+: <42>
+
+The answer is call_answer().
+";
+        assert_eq!(
+            html_export(input),
+            r#"<pre><code class="src" class="src-python" id="answer">
+return 42
+</pre></code>
+<pre class="example">
+&lt;42&gt;
+</pre>
+<p>The answer is <code>&lt;42&gt;</code>.</p>
+"#
+        );
+    }
+
     #[test]
     fn tabale_caption() {
         let a = r#"
@@ -1454,13 +2354,285 @@ here
 <caption>
 <p> i am a table</p>
 </caption>
+<tbody>
 <tr>
 <td>a</td>
 <td>b</td>
 <td>c</td>
 </tr>
+</tbody>
 </table>
 "#
         )
     }
+
+    #[test]
+    fn table_cookies() {
+        let input = r"|<r>||<c>|
+|/|<|>|
+|1|a|b|
+|22|c|d|
+";
+
+        assert_eq!(
+            html_export(input),
+            r#"<table>
+<colgroup>
+<col />
+</colgroup>
+<colgroup>
+<col />
+<col />
+</colgroup>
+<tbody>
+<tr>
+<td style="text-align: right">1</td>
+<td>a</td>
+<td style="text-align: center">b</td>
+</tr>
+<tr>
+<td style="text-align: right">22</td>
+<td>c</td>
+<td style="text-align: center">d</td>
+</tr>
+</tbody>
+</table>
+"#
+        );
+    }
+
+    #[test]
+    fn coderefs() {
+        let input = r#"#+begin_src rust -r
+let x = 1; (ref:init)
+x < 2 (ref:cmp)
+#+end_src
+
+#+begin_example -l "<%s>"
+one <first>
+#+end_example
+
+See [[(init)]], [[(first)]] and [[(cmp)][the check]].
+"#;
+
+        assert_eq!(
+            html_export(input),
+            r##"<pre><code class="src" class="src-rust">
+<span id="coderef-init">let x = 1;</span>
+<span id="coderef-cmp">x &lt; 2</span>
+</pre></code>
+<pre class="example">
+<span id="coderef-first">one &lt;first&gt;</span>
+</pre>
+<p>See <a href="#coderef-init">1</a>, <a href="#coderef-first">first</a> and <a href="#coderef-cmp">the check</a>.</p>
+"##
+        );
+    }
+
+    #[test]
+    fn table_header() {
+        let input = r"|-
+|name|age|
+|<r>||
+|-
+|bob|30|
+|-
+|-
+|alice|*25*|
+";
+
+        assert_eq!(
+            html_export(input),
+            r#"<table>
+<thead>
+<tr>
+<th scope="col" style="text-align: right">name</th>
+<th scope="col">age</th>
+</tr>
+</thead>
+<tbody>
+<tr>
+<td style="text-align: right">bob</td>
+<td>30</td>
+</tr>
+</tbody>
+<tbody>
+<tr>
+<td style="text-align: right">alice</td>
+<td><b>25</b></td>
+</tr>
+</tbody>
+</table>
+"#
+        );
+    }
+
+    #[test]
+    fn src_exports_none() {
+        let input = r"#+PROPERTY: header-args :exports none
+* Hidden
+#+begin_src python
+print(1)
+#+end_src
+* Shown
+:PROPERTIES:
+:header-args: :exports code
+:END:
+#+header: :exports both
+#+begin_src python
+print(2)
+#+end_src
+";
+
+        assert_eq!(
+            html_export(input),
+            r#"<h1 id="hidden">Hidden</h1>
+<h1 id="shown">Shown</h1>
+<pre><code class="src" class="src-python">
+print(2)
+</pre></code>
+"#
+        );
+    }
+
+    #[test]
+    fn src_exports() {
+        let input = r"#+RESULTS: late
+: early results
+
+#+begin_src sh
+echo code
+#+end_src
+
+#+RESULTS:
+: code results
+
+#+begin_src sh :exports results
+echo results
+#+end_src
+
+#+RESULTS:
+: results results
+
+#+begin_src sh :exports both
+echo both
+#+end_src
+
+#+RESULTS:
+: both results
+
+#+name: late
+#+begin_src sh :exports none
+echo none
+#+end_src
+
+src_sh{echo 1} {{{results(=1=)}}}, src_sh[:exports code]{echo 2} {{{results(=2=)}}}, src_sh[:exports both]{echo 3} {{{results(=3=)}}}
+";
+        assert_eq!(
+            html_export(input),
+            r#"<pre><code class="src" class="src-sh">
+echo code
+</pre></code>
+<pre class="example">
+results results
+</pre>
+<pre><code class="src" class="src-sh">
+echo both
+</pre></code>
+<pre class="example">
+both results
+</pre>
+<p> <code>1</code>, <code class=sh>echo 2</code> , <code class=sh>echo 3</code> <code>3</code></p>
+"#
+        );
+    }
+
+    #[test]
+    fn src_noweb() {
+        let input = r"#+name: greet
+#+begin_src sh
+echo hi
+#+end_src
+
+#+begin_src sh :noweb strip-export
+main() {
+    <<greet>> # greeting
+}
+#+end_src
+
+#+begin_src sh :noweb yes
+<<greet>> && <<greet>>
+#+end_src
+";
+        assert_eq!(
+            html_export(input),
+            r#"<pre><code class="src" class="src-sh" id="greet">
+echo hi
+</pre></code>
+<pre><code class="src" class="src-sh">
+main() {
+     # greeting
+}
+</pre></code>
+<pre><code class="src" class="src-sh">
+echo hi &amp;&amp; echo hi
+</pre></code>
+"#
+        );
+
+        let input = "#+begin_src sh :noweb yes\n<<missing>>\n#+end_src\n";
+        let errors = Html::export(input, ConfigOptions::default()).unwrap_err();
+        let ExportError::LogicError { span, source } = &errors[0];
+        assert_eq!(&input[span.clone()], "<<missing>>");
+        assert!(matches!(
+            source,
+            LogicErrorKind::Noweb(NowebError::UndefinedBlock { name, .. }) if name == "missing"
+        ));
+    }
+
+    #[test]
+    fn evaluate_blocks() {
+        let input = r"#+begin_src sh :exports both
+echo fresh
+#+end_src
+
+#+RESULTS:
+: stale
+
+#+begin_src sh
+echo code only
+#+end_src
+
+#+RESULTS:
+: stored
+
+#+begin_src sh :exports both
+exit 1
+#+end_src
+";
+        let conf = ConfigOptions::default().with_block_evaluation(true);
+        let errors = Html::export(input, conf.clone()).unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [ExportError::LogicError {
+                source: LogicErrorKind::Run(RunError::Failed { .. }),
+                ..
+            }]
+        ));
+
+        let input = &input[..input.rfind("\n\n#+begin_src").unwrap() + 1];
+        assert_eq!(
+            Html::export(input, conf).unwrap(),
+            r#"<pre><code class="src" class="src-sh">
+echo fresh
+</pre></code>
+<pre class="example">
+fresh
+</pre>
+<pre><code class="src" class="src-sh">
+echo code only
+</pre></code>
+"#
+        );
+    }
 }
@@ -24,7 +24,6 @@
 //! :block.
 
 use org_parser::parse_org;
-use std::borrow::Cow;
 use std::fs::read_to_string;
 use std::num::ParseIntError;
 use std::ops::Range;
@@ -33,7 +32,9 @@ use thiserror::Error;
 
 use org_parser::element::HeadingLevel;
 
+use crate::citation::Citations;
 use crate::types::{ExportError, ExporterInner, FileError};
+use crate::utils::resolve_path;
 
 /// Block types that correspond directly to types within the parser.
 #[derive(Debug)]
@@ -147,16 +148,10 @@ impl<'a> InclParams<'a> {
                 ":minlevel" => {
                     if let Some(not_kwarg) = params.next_if(is_not_kwarg) {
                         let temp = not_kwarg.parse::<usize>()?;
-                        // FIXME: generalize headline level parsing with heading.rs in the parser
-                        min_level = match temp {
-                            1 => Some(HeadingLevel::One),
-                            2 => Some(HeadingLevel::Two),
-                            3 => Some(HeadingLevel::Three),
-                            4 => Some(HeadingLevel::Four),
-                            5 => Some(HeadingLevel::Five),
-                            6 => Some(HeadingLevel::Six),
-                            _ => Err(IncludeError::InvalidMinLevel { received: temp })?,
-                        };
+                        min_level = Some(
+                            HeadingLevel::new(temp)
+                                .ok_or(IncludeError::InvalidMinLevel { received: temp })?,
+                        );
                     }
                 }
                 _ => Err(IncludeError::UnsupportedKwarg(kwarg.into()))?,
@@ -187,20 +182,7 @@ pub(crate) fn include_handle<'a>(
     let ret = InclParams::new(value)?;
     // REVIEW: little uncomfortable reding the full string in before
     // processing lines
-    let target_path: Cow<Path> = if let Some(v) = writer.config_opts().file_path().as_ref() {
-        // TODO: error handling
-        let temp_path = v.parent().unwrap().join(ret.file);
-        temp_path
-            .canonicalize()
-            .map_err(|e| FileError {
-                context: "".into(),
-                path: temp_path,
-                source: e,
-            })?
-            .into()
-    } else {
-        ret.file.into()
-    };
+    let target_path = resolve_path(writer.config_opts().file_path().as_deref(), ret.file);
     let mut out_str = read_to_string(&target_path).map_err(|e| FileError {
         context: "".into(),
         path: target_path.into(),
@@ -270,8 +252,14 @@ pub(crate) fn include_handle<'a>(
     // TODO: only-contents
 
     let parsed = parse_org(&feed_str);
+    // citations are rendered per node, so the included document needs its own
+    let mut errors = Vec::new();
+    let citations = Citations::process(&parsed, writer.config_opts(), &mut errors);
+    writer.errors().append(&mut errors);
+    let outer_citations = std::mem::replace(writer.citations(), citations);
     // TODO/FIXME: expose these errors
     writer.export_rec(&parsed.pool.root_id(), &parsed);
+    *writer.citations() = outer_citations;
 
     Ok(())
 }
@@ -288,7 +276,7 @@ pub enum IncludeError {
     UnsupportedKwarg(String),
     #[error("lines provided are not in base 10: {0}")]
     LinesError(#[from] ParseIntError),
-    #[error("expected a minlevel of 1-6, received: {received}")]
+    #[error("expected a minlevel of 1-255, received: {received}")]
     InvalidMinLevel { received: usize },
     #[error("minlevel was not a number: {0}")]
     NotStringMinlevel(String),
@@ -31,13 +31,29 @@
 //! assert_eq!(org_str, "* Hello Org!\n");
 //! ```
 
+mod babel;
+mod bibtex;
+mod citation;
+mod dynamic_block;
+mod filter;
 mod html;
 mod include;
+mod noweb;
 mod org;
 mod org_macros;
+mod runner;
+mod spreadsheet;
+mod tangle;
 mod types;
 mod utils;
 
+pub use citation::{AuthorYear, BibEntry, Bibliography, CitationProcessor, ProcessorFactory};
+pub use dynamic_block::{BlockGenerator, GeneratorRegistry};
 pub use html::Html;
+pub use noweb::{NowebContext, NowebError, expand_noweb};
 pub use org::Org;
-pub use types::{ConfigOptions, ExportError, Exporter};
+pub use runner::{RunError, run_block};
+pub use spreadsheet::{FormulaError, recalculate_table};
+pub use tangle::{TangleError, TangledFile, tangle, tangle_files};
+pub use types::{ConfigOptions, DeepHeadings, ExportError, Exporter, FileError};
+pub use utils::resolve_path;
@@ -0,0 +1,317 @@
+//! Expands noweb references in source blocks
+//!
+//! ```org
+//! #+name: imports
+//! #+begin_src python
+//! import os
+//! #+end_src
+//!
+//! #+begin_src python :noweb yes :tangle main.py
+//! def main():
+//!     <<imports>>
+//! #+end_src
+//! ```
+//!
+//! A `<<name>>` is replaced by the body of the block with that `#+NAME:`, where
+//! everything before the reference on its line prefixes every line of the body.
+//! `<<name(args)>>` is expanded the same way: blocks are not evaluated, so the
+//! arguments are ignored. Referenced blocks expand their own references according
+//! to their own `:noweb` header argument.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use org_parser::element::{Block, Noweb};
+use org_parser::{Expr, NodeID, Parser};
+use thiserror::Error;
+
+/// What the contents of a block are used for, deciding whether references are
+/// expanded, see [`expand_noweb`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NowebContext {
+    Tangle,
+    Export,
+    Eval,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum NowebError {
+    #[error("noweb reference to undefined source block `{name}`")]
+    UndefinedBlock { name: String, span: Range<usize> },
+    #[error("noweb reference `{name}` expands into itself")]
+    Cycle { name: String, span: Range<usize> },
+}
+
+impl NowebError {
+    /// The span of the `<<reference>>` in the source.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            NowebError::UndefinedBlock { span, .. } | NowebError::Cycle { span, .. } => {
+                span.clone()
+            }
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum Action {
+    Expand,
+    Strip,
+    Keep,
+}
+
+/// The contents of a source block, with its noweb references expanded, stripped
+/// or kept as written according to its `:noweb` header argument.
+///
+/// | `:noweb`       | tangle | export | eval   |
+/// |----------------|--------|--------|--------|
+/// | `yes`          | expand | expand | expand |
+/// | `no`           | keep   | keep   | keep   |
+/// | `tangle`       | expand | keep   | keep   |
+/// | `no-export`    | expand | keep   | expand |
+/// | `strip-export` | expand | strip  | expand |
+/// | `strip-tangle` | strip  | expand | expand |
+/// | `eval`         | keep   | keep   | expand |
+pub fn expand_noweb<'a>(
+    parser: &Parser<'a>,
+    block_id: NodeID,
+    context: NowebContext,
+) -> Result<Cow<'a, str>, NowebError> {
+    expand(parser, block_id, context, &mut vec![block_id])
+}
+
+fn expand<'a>(
+    parser: &Parser<'a>,
+    block_id: NodeID,
+    context: NowebContext,
+    stack: &mut Vec<NodeID>,
+) -> Result<Cow<'a, str>, NowebError> {
+    let node = &parser.pool[block_id];
+    let Expr::Block(Block::Src { contents, .. }) = node.obj else {
+        return Ok("".into());
+    };
+
+    let action = match (parser.header_args(block_id).noweb, context) {
+        (Noweb::Yes, _)
+        | (Noweb::Tangle, NowebContext::Tangle)
+        | (Noweb::NoExport | Noweb::StripExport, NowebContext::Tangle | NowebContext::Eval)
+        | (Noweb::StripTangle, NowebContext::Export | NowebContext::Eval)
+        | (Noweb::Eval, NowebContext::Eval) => Action::Expand,
+        (Noweb::StripExport, NowebContext::Export) | (Noweb::StripTangle, NowebContext::Tangle) => {
+            Action::Strip
+        }
+        _ => Action::Keep,
+    };
+    if action == Action::Keep || !contents.contains("<<") {
+        return Ok(contents.into());
+    }
+
+    // where the line being expanded starts in the source, for error spans
+    let mut line_start = parser.source[node.start..node.end]
+        .find('\n')
+        .map_or(node.end, |i| node.start + i + 1);
+    let mut ret = String::with_capacity(contents.len());
+    for line in contents.split_inclusive('\n') {
+        let mut index = 0;
+        while let Some(reference) = find_reference(&line[index..]) {
+            let start = index + reference.start;
+            ret.push_str(&line[index..start]);
+            index += reference.end;
+
+            if action == Action::Strip {
+                continue;
+            }
+            let inner = &line[start + 2..index - 2];
+            // <<name(args)>>
+            let name = inner
+                .strip_suffix(')')
+                .and_then(|inner| inner.split_once('('))
+                .map_or(inner, |(name, _)| name);
+
+            let span = line_start + start..line_start + index;
+            let Some(&ref_id) = parser.names.get(name) else {
+                return Err(NowebError::UndefinedBlock {
+                    name: name.into(),
+                    span,
+                });
+            };
+            if stack.contains(&ref_id) {
+                return Err(NowebError::Cycle {
+                    name: name.into(),
+                    span,
+                });
+            }
+
+            stack.push(ref_id);
+            let body = expand(parser, ref_id, context, stack)?;
+            stack.pop();
+
+            let prefix = &line[..start];
+            let body = body.strip_suffix('\n').unwrap_or(&body);
+            for (i, body_line) in body.split('\n').enumerate() {
+                if i > 0 {
+                    ret.push('\n');
+                    ret.push_str(prefix);
+                }
+                ret.push_str(body_line);
+            }
+        }
+        ret.push_str(&line[index..]);
+        line_start += line.len();
+    }
+
+    Ok(ret.into())
+}
+
+/// Finds the first `<<name>>` in `line`, where the name neither starts nor ends with
+/// whitespace, so shifts like `a << b >> c` are not references.
+fn find_reference(line: &str) -> Option<Range<usize>> {
+    let mut search = 0;
+    while let Some(open) = line[search..].find("<<") {
+        let start = search + open;
+        let inner_start = start + 2;
+        if let Some(close) = line[inner_start..].find(">>") {
+            let inner = &line[inner_start..inner_start + close];
+            if !inner.is_empty() && inner.trim() == inner && !inner.contains("<<") {
+                return Some(start..inner_start + close + 2);
+            }
+        }
+        search = inner_start;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use org_parser::parse_org;
+
+    fn expand_last(input: &str, context: NowebContext) -> Result<String, NowebError> {
+        let parser = parse_org(input);
+        let mut block_id = None;
+        for id in parser.pool[parser.pool.root_id()].obj.children().unwrap() {
+            if let Expr::Block(Block::Src { .. }) = parser.pool[*id].obj {
+                block_id = Some(*id);
+            }
+        }
+        expand_noweb(&parser, block_id.unwrap(), context).map(Cow::into_owned)
+    }
+
+    #[test]
+    fn prefix_indentation() {
+        let input = r"#+name: body
+#+begin_src python
+x = 1
+return x
+#+end_src
+
+#+name: args
+#+begin_src python
+a, b
+#+end_src
+
+#+begin_src python :noweb yes
+def f(<<args(1, 2)>>):
+    <<body>>  # done
+print(1 << 2, 3 >> 1, a << b >> c)
+#+end_src
+";
+        assert_eq!(
+            expand_last(input, NowebContext::Export).unwrap(),
+            r"def f(a, b):
+    x = 1
+    return x  # done
+print(1 << 2, 3 >> 1, a << b >> c)
+"
+        );
+    }
+
+    #[test]
+    fn nested_references() {
+        let input = r"#+name: inner
+#+begin_src sh
+echo inner
+#+end_src
+
+#+name: outer
+#+begin_src sh :noweb yes
+<<inner>>
+echo outer
+#+end_src
+
+#+name: plain
+#+begin_src sh
+<<inner>>
+#+end_src
+
+#+begin_src sh :noweb yes
+<<outer>>
+<<plain>>
+#+end_src
+";
+        assert_eq!(
+            expand_last(input, NowebContext::Tangle).unwrap(),
+            "echo inner\necho outer\n<<inner>>\n"
+        );
+    }
+
+    #[test]
+    fn noweb_contexts() {
+        let block = |noweb: &str| {
+            format!(
+                "#+name: a\n#+begin_src sh\nA\n#+end_src\n\n#+begin_src sh :noweb {noweb}\n<<a>>;\n#+end_src\n"
+            )
+        };
+        let all = |noweb: &str| {
+            [
+                NowebContext::Tangle,
+                NowebContext::Export,
+                NowebContext::Eval,
+            ]
+            .map(|context| expand_last(&block(noweb), context).unwrap())
+        };
+
+        assert_eq!(all("yes"), ["A;\n", "A;\n", "A;\n"]);
+        assert_eq!(all("no"), ["<<a>>;\n", "<<a>>;\n", "<<a>>;\n"]);
+        assert_eq!(all("tangle"), ["A;\n", "<<a>>;\n", "<<a>>;\n"]);
+        assert_eq!(all("no-export"), ["A;\n", "<<a>>;\n", "A;\n"]);
+        assert_eq!(all("strip-export"), ["A;\n", ";\n", "A;\n"]);
+        assert_eq!(all("strip-tangle"), [";\n", "A;\n", "A;\n"]);
+        assert_eq!(all("eval"), ["<<a>>;\n", "<<a>>;\n", "A;\n"]);
+    }
+
+    #[test]
+    fn noweb_errors() {
+        let input = r"#+begin_src sh :noweb yes
+echo start
+  <<missing>>
+#+end_src
+";
+        assert_eq!(
+            expand_last(input, NowebContext::Export),
+            Err(NowebError::UndefinedBlock {
+                name: "missing".into(),
+                span: 39..50,
+            })
+        );
+        assert_eq!(&input[39..50], "<<missing>>");
+
+        let input = r"#+name: a
+#+begin_src sh :noweb yes
+<<b>>
+#+end_src
+
+#+name: b
+#+begin_src sh :noweb yes
+<<a>>
+#+end_src
+";
+        let Err(NowebError::Cycle { name, span }) = expand_last(input, NowebContext::Tangle) else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(name, "b");
+        assert_eq!(&input[span], "<<b>>");
+    }
+}
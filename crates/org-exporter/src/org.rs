@@ -1,15 +1,23 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Write;
 
 use crate::ExportError;
+use crate::babel::{call_results, exports, hides_code, skips_results};
+use crate::citation::Citations;
+use crate::filter::{ExportFilter, Visibility};
 use crate::include::include_handle;
 use crate::org_macros::macro_handle;
+use crate::runner::{run_block, runs_at_export, stored_results};
+use crate::spreadsheet::{cell_text, recalculate_table};
 use crate::types::{ConfigOptions, Exporter, ExporterInner, LogicErrorKind};
-use org_parser::element::{Block, BulletKind, CounterKind, Priority, TableRow, Tag};
+use org_parser::element::{
+    Affiliated, Alignment, Block, BulletKind, CounterKind, Exports, Priority, TableRow, Tag,
+};
 use org_parser::object::{LatexFragment, PlainOrRec};
 
-use org_parser::{Expr, NodeID, Parser, parse_org};
+use org_parser::{Expr, Node, NodeID, Parser, parse_org};
 
 /// Org-Mode Content Exporter
 ///
@@ -23,7 +31,15 @@ pub struct Org<'buf> {
     indentation_level: u8,
     on_newline: bool,
     conf: ConfigOptions,
+    citations: Citations,
+    filter: ExportFilter,
     errors: Vec<ExportError>,
+    /// Stored results left out by `:exports` or replaced by running their blocks,
+    /// see [`runner`](crate::runner).
+    skipped: HashSet<NodeID>,
+    /// Whether the last node was left out, which leaves out the blank lines after
+    /// an element and the whitespace after an inline source block too.
+    dropped: bool,
 }
 
 macro_rules! w {
@@ -53,12 +69,18 @@ impl<'buf> Exporter<'buf> for Org<'buf> {
         buf: &'buf mut T,
         conf: ConfigOptions,
     ) -> core::result::Result<(), Vec<ExportError>> {
+        let mut errors = Vec::new();
+        let citations = Citations::process(parsed, &conf, &mut errors);
         let mut obj = Org {
             buf,
             indentation_level: 0,
             on_newline: false,
             conf,
-            errors: Vec::new(),
+            citations,
+            filter: ExportFilter::new(parsed),
+            errors,
+            skipped: HashSet::new(),
+            dropped: false,
         };
 
         obj.export_rec(&parsed.pool.root_id(), parsed);
@@ -84,7 +106,11 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
             indentation_level: 0,
             on_newline: false,
             conf: ConfigOptions::default(),
+            citations: Citations::default(),
+            filter: ExportFilter::default(),
             errors: Vec::new(),
+            skipped: HashSet::new(),
+            dropped: false,
         };
 
         obj.export_rec(&parsed.pool.root_id(), &parsed);
@@ -96,7 +122,15 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
     }
 
     fn export_rec(&mut self, node_id: &NodeID, parser: &Parser) {
+        if self.skipped.contains(node_id) {
+            return;
+        }
         let node = &parser.pool[*node_id];
+        let after_dropped = if matches!(node.obj, Expr::BlankLine) {
+            self.dropped
+        } else {
+            std::mem::take(&mut self.dropped)
+        };
         match &node.obj {
             Expr::Root(inner) => {
                 for id in inner {
@@ -104,66 +138,19 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                 }
             }
             Expr::Heading(inner) => {
-                for _ in 0..inner.heading_level.into() {
-                    w!(self, "*");
-                }
-                w!(self, " ");
-
-                if let Some(keyword) = inner.keyword {
-                    w!(self, "{keyword} ");
+                let visibility = self.visibility(parser, *node_id);
+                if visibility == Visibility::Hidden {
+                    return;
                 }
-
-                if let Some(priority) = &inner.priority {
-                    w!(self, "[#");
-                    match priority {
-                        Priority::A => w!(self, "A"),
-                        Priority::B => w!(self, "B"),
-                        Priority::C => w!(self, "C"),
-                        Priority::Num(num) => w!(self, "{num}"),
-                    };
-                    w!(self, "] ");
-                }
-
-                if let Some(title) = &inner.title {
-                    for id in &title.1 {
-                        self.export_rec(id, parser);
-                    }
+                self.headline(parser, node);
+                if visibility == Visibility::Headline {
+                    return;
                 }
 
-                // fn tag_search<T: Write>(loc: NodeID, pool: &NodePool, self: &mut T) -> Result {
-                //     if let Expr::Heading(loc) = &pool[loc].obj {
-                //         if let Some(sub_tags) = loc.tags.as_ref() {
-                //             for thang in sub_tags.iter().rev() {
-                //                 match thang {
-                //                     Tag::Raw(val) => w!(self, ":{val}"),
-                //                     Tag::Loc(id, parser) => {
-                //                         tag_search(*id, pool, self)?;
-                //                     }
-                //                 }
-                //             }
-                //         }
-                //     }
-                //     Ok(())
-                // }
-
-                if let Some(tags) = &inner.tags {
-                    let mut valid_out = String::new();
-                    for tag in tags.iter().rev() {
-                        match tag {
-                            Tag::Raw(val) => w!(&mut valid_out, ":{val}"),
-                            Tag::Loc(_id) => {
-                                // do nothing with it
-                            }
-                        }
-                    }
-                    // handles the case where a parent heading has no tags
-                    if !valid_out.is_empty() {
-                        w!(self, " {valid_out}:");
-                    }
+                if let Some(planning) = &inner.planning {
+                    w!(self, "{}\n", planning.raw);
                 }
 
-                w!(self, "\n");
-
                 if let Some(children) = &inner.children {
                     for id in children {
                         self.export_rec(id, parser);
@@ -231,9 +218,13 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                     }
                     Block::Example {
                         parameters,
+                        switches,
                         contents,
                     } => {
                         w!(self, "#+begin_example");
+                        if !switches.raw.is_empty() {
+                            w!(self, " {}", switches.raw);
+                        }
                         for (key, val) in parameters {
                             w!(self, " :{} {}", key, val);
                         }
@@ -256,15 +247,35 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                     Block::Src {
                         language,
                         parameters,
+                        switches,
                         contents,
                     } => {
-                        let lang = if let Some(word) = language { word } else { "" };
-                        w!(self, "#+begin_src {}", lang);
-                        for (key, val) in parameters {
-                            w!(self, " :{} {}", key, val);
+                        let exports = exports(parser, *node_id, &self.conf);
+                        if matches!(exports, Exports::Code | Exports::Both) {
+                            if self.conf.write_back() {
+                                let source = &parser.source[node.start..node.end];
+                                w!(self, "{}", source.lines().next().unwrap_or_default());
+                            } else {
+                                let lang = if let Some(word) = language { word } else { "" };
+                                w!(self, "#+begin_src {}", lang);
+                                if !switches.raw.is_empty() {
+                                    w!(self, " {}", switches.raw);
+                                }
+                                for (key, val) in parameters {
+                                    w!(self, " :{} {}", key, val);
+                                }
+                            }
+                            w!(self, "\n{contents}");
+                            w!(self, "#+end_src\n");
+                        }
+                        if matches!(exports, Exports::Results | Exports::Both) {
+                            // left out unless running the block writes its results
+                            self.dropped = exports == Exports::Results;
+                            self.block_results(*node_id, parser);
+                        } else {
+                            self.skipped.extend(stored_results(parser, *node_id));
+                            self.dropped = exports == Exports::None;
                         }
-                        w!(self, "\n{contents}");
-                        w!(self, "#+end_src\n");
                     }
                     Block::Verse {
                         parameters,
@@ -328,6 +339,19 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                 w!(self, "_");
             }
             Expr::BlankLine => {
+                if after_dropped {
+                    return;
+                }
+                // the newline ending a block is parsed as a blank line of its own,
+                // which would add a line to every block written back
+                if self.conf.write_back()
+                    && parser.source[..node.start]
+                        .chars()
+                        .next_back()
+                        .is_some_and(|chr| chr != '\n')
+                {
+                    return;
+                }
                 w!(self, "\n");
             }
             Expr::SoftBreak => {
@@ -340,7 +364,11 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                 w!(self, "-----\n");
             }
             Expr::Plain(inner) => {
-                w!(self, "{inner}");
+                if after_dropped {
+                    w!(self, "{}", inner.trim_start());
+                } else {
+                    w!(self, "{inner}");
+                }
             }
             Expr::Verbatim(inner) => {
                 w!(self, "={}=", inner.0);
@@ -352,20 +380,50 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                 w!(self, "# {}\n", inner.0);
             }
             Expr::InlineSrc(inner) => {
-                w!(self, "src_{}", inner.lang);
-                if let Some(args) = inner.headers {
-                    w!(self, "[{args}]");
+                if matches!(
+                    exports(parser, *node_id, &self.conf),
+                    Exports::Code | Exports::Both
+                ) {
+                    w!(self, "src_{}", inner.lang);
+                    if let Some(args) = inner.headers {
+                        w!(self, "[{args}]");
+                    }
+                    w!(self, "{{{}}}", inner.body);
+                } else {
+                    self.dropped = true;
                 }
-                w!(self, "{{{}}}", inner.body);
+            }
+            // copied as written, see `ConfigOptions::with_write_back`
+            Expr::Keyword(_)
+            | Expr::MacroDef(_)
+            | Expr::BabelCall(_)
+            | Expr::Affiliated(
+                Affiliated::Name(_)
+                | Affiliated::Caption(_)
+                | Affiliated::Attr { .. }
+                | Affiliated::Header { .. },
+            ) if self.conf.write_back() => self.source_text(parser, node),
+            Expr::Citation(_) | Expr::InlineBabelCall(_) if self.conf.write_back() => {
+                w!(self, "{}", &parser.source[node.start..node.end]);
             }
             Expr::Keyword(inner) => {
-                if inner.key.eq_ignore_ascii_case("include")
-                    && let Err(e) = include_handle(inner.val, self)
+                if inner.key.eq_ignore_ascii_case("include") {
+                    if let Err(e) = include_handle(inner.val, self) {
+                        self.errors().push(ExportError::LogicError {
+                            span: node.start..node.end,
+                            source: LogicErrorKind::Include(e),
+                        });
+                    }
+                } else if ["todo", "seq_todo", "typ_todo", "filetags", "tags"]
+                    .iter()
+                    .any(|key| inner.key.eq_ignore_ascii_case(key))
                 {
-                    self.errors().push(ExportError::LogicError {
-                        span: node.start..node.end,
-                        source: LogicErrorKind::Include(e),
-                    });
+                    // keep the keywords for the headings that use them
+                    w!(self, "#+{}: {}\n", inner.key, inner.val);
+                } else if inner.key.eq_ignore_ascii_case("print_bibliography") {
+                    for entry in self.citations.bibliography.clone() {
+                        w!(self, "- {entry}\n");
+                    }
                 }
             }
             Expr::LatexEnv(inner) => {
@@ -468,15 +526,29 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                                             indentation_level: self.indentation_level,
                                             on_newline: self.on_newline,
                                             conf: self.conf.clone(),
+                                            citations: std::mem::take(&mut self.citations),
+                                            filter: ExportFilter::default(),
                                             errors: Vec::new(),
+                                            skipped: HashSet::new(),
+                                            dropped: false,
                                         };
                                         new_obj.export_rec(id, parser);
+                                        self.citations = new_obj.citations;
                                         row_vec.push(cell_buf);
                                     }
                                 }
                                 TableRow::Rule => {
                                     // an empty vec represents an hrule
                                 }
+                                TableRow::Cookies(cookies) => {
+                                    row_vec.extend(cookies.iter().map(ToString::to_string));
+                                }
+                                TableRow::ColumnGroups(groups) => {
+                                    row_vec.push("/".into());
+                                    row_vec.extend(groups[1..].iter().map(|group| {
+                                        group.map(|group| group.to_string()).unwrap_or_default()
+                                    }));
+                                }
                             }
                             build_vec.push(row_vec);
                         }
@@ -484,15 +556,49 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                     }
                 }
 
+                if self.conf.recalculate_tables() && !inner.formulas.is_empty() {
+                    match recalculate_table(parser, *node_id) {
+                        Ok(values) => {
+                            // only replace the fields that changed, keeping
+                            // the exported form of everything else
+                            for ((row_vec, row_values), row_id) in
+                                build_vec.iter_mut().zip(values).zip(&inner.children)
+                            {
+                                let Expr::TableRow(TableRow::Standard(cells)) =
+                                    &parser.pool[*row_id].obj
+                                else {
+                                    continue;
+                                };
+                                for (col_ind, value) in row_values.into_iter().enumerate() {
+                                    let unchanged = cells.get(col_ind).is_some_and(|cell_id| {
+                                        cell_text(parser, *cell_id) == value
+                                    });
+                                    if !unchanged {
+                                        if row_vec.len() <= col_ind {
+                                            row_vec.resize(col_ind + 1, String::new());
+                                        }
+                                        row_vec[col_ind] = value;
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => self.errors().push(ExportError::LogicError {
+                            span: node.start..node.end,
+                            source: LogicErrorKind::Formula(e),
+                        }),
+                    }
+                }
+
                 // we use .get throughout because hrule rows are empty
                 // and empty cells don't appear in the table, but we still have
                 // to represent them
                 //
                 // run analysis to find column widths (padding)
                 // travel downwards down rows, finding the largest length in each column
+                // columns with a fixed width are at least that wide
                 let mut col_widths = Vec::with_capacity(inner.cols);
                 for col_ind in 0..inner.cols {
-                    let mut curr_max = 0;
+                    let mut curr_max = inner.columns[col_ind].width.unwrap_or_default();
                     for row in &build_vec {
                         curr_max = curr_max.max(row.get(col_ind).map_or_else(|| 0, |v| v.len()));
                     }
@@ -518,19 +624,21 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                         }
                     } else {
                         for (col_ind, col_width) in col_widths.iter().enumerate() {
-                            let cell = row.get(col_ind);
-                            let diff;
+                            let cell = row.get(col_ind).map_or("", String::as_str);
+                            let diff = col_width - cell.len();
+                            let (before, after) = match inner.columns[col_ind].align {
+                                Some(Alignment::Right) => (diff, 0),
+                                Some(Alignment::Center) => (diff / 2, diff - diff / 2),
+                                Some(Alignment::Left) | None => (0, diff),
+                            };
 
                             // left buffer
                             w!(self, " ");
-                            if let Some(strang) = cell {
-                                diff = col_width - strang.len();
-                                w!(self, "{strang}");
-                            } else {
-                                diff = *col_width;
-                            };
-
-                            for _ in 0..diff {
+                            for _ in 0..before {
+                                w!(self, " ");
+                            }
+                            w!(self, "{cell}");
+                            for _ in 0..after {
                                 w!(self, " ");
                             }
 
@@ -540,6 +648,10 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                     }
                     w!(self, "\n");
                 }
+
+                for formula in &inner.formulas {
+                    w!(self, "#+TBLFM: {formula}\n");
+                }
             }
 
             Expr::TableRow(_) => {
@@ -583,6 +695,13 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                 w!(self, "<<{}>>", inner.0);
             }
             Expr::Macro(macro_call) => {
+                if self.conf.write_back() {
+                    w!(self, "{}", &parser.source[node.start..node.end]);
+                    return;
+                }
+                if skips_results(parser, *node_id, &self.conf) {
+                    return;
+                }
                 let macro_contents = match macro_handle(parser, macro_call, self.config_opts()) {
                     Ok(contents) => contents,
                     Err(e) => {
@@ -614,11 +733,99 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                 }
                 w!(self, ":end:\n");
             }
+            Expr::InlineTask(inner) => {
+                let visibility = self.visibility(parser, *node_id);
+                if visibility == Visibility::Hidden {
+                    return;
+                }
+                self.headline(parser, node);
+                if visibility == Visibility::Full
+                    && let Some(children) = &inner.children
+                {
+                    for id in children {
+                        self.export_rec(id, parser);
+                    }
+                    for _ in 0..inner.heading_level.into() {
+                        w!(self, "*");
+                    }
+                    w!(self, " END\n");
+                }
+            }
+            Expr::DynamicBlock(inner) => {
+                let source = &parser.source[node.start..node.end];
+                if self.conf.write_back() {
+                    w!(self, "{}\n", source.lines().next().unwrap_or_default());
+                } else {
+                    w!(self, "#+begin: {}", inner.name);
+                    for (key, val) in &inner.parameters {
+                        w!(self, " :{key}");
+                        if !val.is_empty() {
+                            w!(self, " {val}");
+                        }
+                    }
+                    w!(self, "\n");
+                }
+                if let Some(contents) = self
+                    .conf
+                    .block_generators()
+                    .generate(inner, *node_id, parser)
+                {
+                    if self.conf.write_back() {
+                        w!(self, "{contents}");
+                        if !contents.is_empty() && !contents.ends_with('\n') {
+                            w!(self, "\n");
+                        }
+                    } else {
+                        let parsed = parse_org(&contents);
+                        self.export_rec(&parsed.pool.root_id(), &parsed);
+                    }
+                } else {
+                    for id in &inner.children {
+                        self.export_rec(id, parser);
+                    }
+                }
+                if self.conf.write_back() {
+                    let end = source.trim_end_matches('\n');
+                    w!(self, "{}\n", &end[end.rfind('\n').map_or(0, |i| i + 1)..]);
+                } else {
+                    w!(self, "#+end:\n");
+                }
+            }
             Expr::ExportSnippet(inner) => {
                 if inner.backend == "org" {
                     w!(self, "{}", inner.contents);
                 }
             }
+            Expr::Affiliated(Affiliated::Results { name, child_id }) => {
+                if skips_results(parser, *node_id, &self.conf) {
+                    self.skipped.extend(*child_id);
+                    self.dropped = true;
+                    return;
+                }
+                w!(self, "#+RESULTS:");
+                if let Some(name) = name {
+                    w!(self, " {name}");
+                }
+                w!(self, "\n");
+            }
+            Expr::Affiliated(Affiliated::Name(Some(child_id))) => {
+                if hides_code(parser, *child_id, &self.conf) {
+                    return;
+                }
+                if let Some(name) = parser
+                    .names
+                    .iter()
+                    .find_map(|(name, id)| (id == child_id).then_some(name))
+                {
+                    w!(self, "#+NAME: {name}\n");
+                }
+            }
+            Expr::Affiliated(Affiliated::Header { child_id, val }) => {
+                if child_id.is_some_and(|child_id| hides_code(parser, child_id, &self.conf)) {
+                    return;
+                }
+                w!(self, "#+HEADER: {val}\n");
+            }
             Expr::Affiliated(_) => {}
             Expr::MacroDef(_) => {}
             Expr::FootnoteDef(inner) => {
@@ -641,6 +848,67 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
                 }
                 w!(self, "]");
             }
+            // written as they appear in the source, `Display` normalizes them
+            Expr::Timestamp(_) | Expr::Clock(_) => {
+                w!(self, "{}", &parser.source[node.start..node.end]);
+            }
+            Expr::Citation(_) => {
+                if let Some(text) = self.citations.rendered.get(node_id).cloned() {
+                    w!(self, "{text}");
+                }
+            }
+            // handled by the parent citation
+            Expr::CitationReference(_) => {}
+            Expr::BabelCall(inner) => match call_results(parser, inner.name) {
+                Ok(results_id) => {
+                    // shown at the call even when left out after the called block
+                    self.skipped.remove(&results_id);
+                    self.export_rec(&results_id, parser)
+                }
+                Err(e) => self.errors().push(ExportError::LogicError {
+                    span: node.start..node.end,
+                    source: LogicErrorKind::Babel(e),
+                }),
+            },
+            Expr::InlineBabelCall(inner) => match call_results(parser, inner.name) {
+                // keep paragraph and fixed width results inline
+                Ok(results_id) => match &parser.pool[results_id].obj {
+                    Expr::Paragraph(par) => {
+                        for id in &par.0 {
+                            self.export_rec(id, parser);
+                        }
+                    }
+                    Expr::FixedWidth(fixed) => {
+                        w!(self, "{}", fixed.contents);
+                    }
+                    _ => {
+                        self.skipped.remove(&results_id);
+                        self.export_rec(&results_id, parser)
+                    }
+                },
+                Err(e) => self.errors().push(ExportError::LogicError {
+                    span: node.start..node.end,
+                    source: LogicErrorKind::Babel(e),
+                }),
+            },
+            Expr::StatisticsCookie(inner) => {
+                w!(self, "{inner}");
+            }
+            Expr::FixedWidth(inner) => {
+                for line in inner.contents.split('\n') {
+                    if line.is_empty() {
+                        w!(self, ":\n");
+                    } else {
+                        w!(self, ": {line}\n");
+                    }
+                }
+            }
+            Expr::RadioTarget(inner) => {
+                w!(self, "<<<{}>>>", inner.0);
+            }
+            Expr::RadioLink(inner) => {
+                w!(self, "{}", inner.contents);
+            }
         }
     }
 
@@ -655,6 +923,129 @@ impl<'buf> ExporterInner<'buf> for Org<'buf> {
     fn errors(&mut self) -> &mut Vec<ExportError> {
         &mut self.errors
     }
+
+    fn citations(&mut self) -> &mut Citations {
+        &mut self.citations
+    }
+}
+
+impl<'buf> Org<'buf> {
+    /// Runs a source block when evaluation is enabled, writing its results in place
+    /// of the stored ones.
+    fn block_results(&mut self, block_id: NodeID, parser: &Parser) {
+        if !self.conf.evaluate_blocks() || !runs_at_export(parser, block_id) {
+            return;
+        }
+        self.skipped.extend(stored_results(parser, block_id));
+
+        let node = &parser.pool[block_id];
+        match run_block(parser, block_id, self.conf.file_path().as_deref()) {
+            Ok(Some(results)) => {
+                w!(self, "\n");
+                // results are exported as is, without running anything they contain
+                let conf = self.conf.clone().with_block_evaluation(false);
+                if let Err(mut err_vec) = Org::export_tree(&parse_org(&results), self, conf) {
+                    self.errors().append(&mut err_vec);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => self.errors().push(ExportError::LogicError {
+                span: node.start..node.end,
+                source: LogicErrorKind::Run(e),
+            }),
+        }
+    }
+
+    /// How much of a heading or inline task is exported, everything when writing
+    /// back.
+    fn visibility(&self, parser: &Parser, node_id: NodeID) -> Visibility {
+        if self.conf.write_back() {
+            Visibility::Full
+        } else {
+            self.filter.visibility(parser, node_id)
+        }
+    }
+
+    /// Writes an element as it appears in the source, see
+    /// [`ConfigOptions::with_write_back`].
+    fn source_text(&mut self, parser: &Parser, node: &Node) {
+        let text = &parser.source[node.start..node.end];
+        w!(self, "{text}");
+        if !text.ends_with('\n') {
+            w!(self, "\n");
+        }
+    }
+
+    /// Writes the starred line of a heading or inline task.
+    fn headline(&mut self, parser: &Parser, node: &Node) {
+        let (level, keyword, priority, comment, title, tags) = match &node.obj {
+            Expr::Heading(inner) => (
+                inner.heading_level,
+                inner.keyword,
+                &inner.priority,
+                inner.comment,
+                &inner.title,
+                &inner.tags,
+            ),
+            Expr::InlineTask(inner) => (
+                inner.heading_level,
+                inner.keyword,
+                &inner.priority,
+                inner.comment,
+                &inner.title,
+                &inner.tags,
+            ),
+            _ => return,
+        };
+
+        for _ in 0..level.into() {
+            w!(self, "*");
+        }
+        w!(self, " ");
+
+        if let Some(keyword) = keyword {
+            w!(self, "{keyword} ");
+        }
+
+        if let Some(priority) = priority {
+            w!(self, "[#");
+            match priority {
+                Priority::A => w!(self, "A"),
+                Priority::B => w!(self, "B"),
+                Priority::C => w!(self, "C"),
+                Priority::Num(num) => w!(self, "{num}"),
+            };
+            w!(self, "] ");
+        }
+
+        if comment {
+            w!(self, "COMMENT ");
+        }
+
+        if let Some(title) = title {
+            for id in &title.1 {
+                self.export_rec(id, parser);
+            }
+        }
+
+        if let Some(tags) = tags {
+            let mut valid_out = String::new();
+            for tag in tags.iter().rev() {
+                match tag {
+                    Tag::Raw(val) => w!(&mut valid_out, ":{val}"),
+                    Tag::Loc(_id) => {
+                        // do nothing with it
+                    }
+                }
+            }
+            // handles the case where a parent heading has no tags
+            if !valid_out.is_empty() {
+                w!(self, " {valid_out}:");
+            }
+        }
+
+        w!(self, "\n");
+    }
 }
 
 impl<'buf> fmt::Write for Org<'buf> {
@@ -1270,6 +1661,376 @@ meowwwwwwwwww
         );
     }
 
+    #[test]
+    fn timestamp_roundtrip() {
+        let input = r"<2024-03-01 Fri 10:00>
+
+[2024-03-01 Fri 10:00-12:00 +1w -2d]
+
+<2024-03-01 Fri>--<2024-03-04 Mon>
+
+<2024-03-01 Fri 08:00 .+1d --3d> and <2024-03-01 Fri ++1m>
+
+<2024-03-01 Fri 9:15> and <2024-03-01  Fri   10:00 -2d +1w>
+";
+        assert_eq!(org_export(input), input);
+    }
+
+    #[test]
+    fn planning_roundtrip() {
+        let input = r"* TODO task
+SCHEDULED: <2024-03-01 Fri 9:15>  DEADLINE: <2024-03-08 Fri -2d +1w>
+** DONE subtask
+CLOSED: [2024-03-02 Sat 09:30]
+";
+        assert_eq!(org_export(input), input);
+    }
+
+    #[test]
+    fn logbook_roundtrip() {
+        let input = r"* task
+:LOGBOOK:
+CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 10:30] =>  1:30
+CLOCK: [2024-03-02 Sat 9:00]
+:end:
+";
+        assert_eq!(
+            org_export(input),
+            r"* task
+:LOGBOOK:
+CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 10:30] =>  1:30
+CLOCK: [2024-03-02 Sat 9:00]
+:end:
+
+"
+        );
+
+        // a clock line ending the file
+        let input = "CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 10:30] =>  1:30";
+        assert_eq!(org_export(input), input);
+    }
+
+    #[test]
+    fn radio_roundtrip() {
+        let input = r"<<<Radio>>> targets link
+
+every radio mention, even in *bold radio*.
+";
+        assert_eq!(org_export(input), input);
+    }
+
+    #[test]
+    fn citation() {
+        let input = r"As shown [cite:see @doe p. 4; @roe], and [cite/t:@doe].
+
+#+print_bibliography:
+";
+        assert_eq!(
+            org_export(input),
+            r"As shown (see doe, n.d. p. 4; roe, n.d.), and doe (n.d.).
+
+- doe
+- roe
+"
+        );
+    }
+
+    #[test]
+    fn statistics_cookies() {
+        let input = r"* Tasks [/]
+** DONE one
+** TODO two [0%]
+- [X] a
+- [ ] b
+- [X] c
+";
+        let mut parsed = parse_org(input);
+        parsed.update_statistics_cookies();
+
+        let mut out = String::new();
+        Org::export_tree(&parsed, &mut out, ConfigOptions::default()).unwrap();
+        assert_eq!(
+            out,
+            r"* Tasks [1/2]
+** DONE one
+** TODO two [66%]
+- [X] a
+- [ ] b
+- [X] c
+"
+        );
+    }
+
+    #[test]
+    fn babel_call() {
+        let input = r"#+name: square
+#+begin_src python :var x=2
+return x * x
+#+end_src
+
+#+RESULTS:
+: 4
+
+Squared: call_square(x=2)
+";
+        assert_eq!(
+            org_export(input),
+            r"#+NAME: square
+#+begin_src python :var x=2
+return x * x
+#+end_src
+
+Squared: 4
+"
+        );
+    }
+
+    #[test]
+    fn fixed_width() {
+        let input = r": *not bold*
+:
+:   indented
+";
+        assert_eq!(org_export(input), input);
+
+        let input = r"- item
+  : in a list
+";
+        assert_eq!(org_export(input), input);
+    }
+
+    #[test]
+    fn dynamic_block() {
+        use org_parser::element::DynamicBlock;
+
+        fn params(block: &DynamicBlock, _: NodeID, _: &Parser) -> String {
+            format!("scope: {}\n", block.parameter("scope").unwrap())
+        }
+
+        let input = r"#+BEGIN: clocktable :scope file
+|old|
+#+END:
+";
+        let conf = ConfigOptions::default().with_block_generator("clocktable", params);
+        assert_eq!(
+            Org::export(input, conf).unwrap(),
+            r"#+begin: clocktable :scope file
+scope: file
+#+end:
+"
+        );
+        assert_eq!(
+            org_export(input),
+            r"#+begin: clocktable :scope file
+| old |
+#+end:
+"
+        );
+
+        // parameters are written in order, and generators can capture state
+        let input = r"#+BEGIN: stamp :b 2 :flag :a 1 :c 3
+#+END:
+";
+        let stamp = String::from("2024-03-01");
+        let conf = ConfigOptions::default()
+            .with_block_generator("stamp", move |_, _, _| format!("{stamp}\n"));
+        assert_eq!(
+            Org::export(input, conf).unwrap(),
+            r"#+begin: stamp :b 2 :flag :a 1 :c 3
+2024-03-01
+#+end:
+"
+        );
+
+        // written back with the original begin and end lines
+        let input = r"#+BEGIN: clocktable :scope  file
+| old |
+  #+END:
+";
+        let conf = ConfigOptions::default()
+            .with_block_generator("clocktable", |_, _, _| "| new |\n".into())
+            .with_write_back(true);
+        assert_eq!(
+            Org::export(input, conf).unwrap(),
+            r"#+BEGIN: clocktable :scope  file
+| new |
+  #+END:
+"
+        );
+    }
+
+    #[test]
+    fn todo_keywords() {
+        let input = r"#+TODO: TODO WAITING(w@/!) | DONE
+* WAITING reply
+* DONE sent
+";
+        assert_eq!(org_export(input), input);
+    }
+
+    #[test]
+    fn deep_headings() {
+        let input = r"******* seven
+********** ten
+";
+        assert_eq!(org_export(input), input);
+    }
+
+    #[test]
+    fn export_filtering() {
+        let input = r"* Public
+*************** task :noexport:
+*************** END
+** COMMENT Drafts
+draft
+* Old :ARCHIVE:
+archived body
+* Tasks :home:
+*************** kept :work:
+body
+*************** END
+*************** dropped :chore:
+";
+        let conf = ConfigOptions::default();
+        let filtered = Org::export(&format!("#+EXCLUDE_TAGS: chore\n{input}"), conf).unwrap();
+        assert_eq!(
+            filtered,
+            r"* Public
+*************** task :noexport:
+*************** END
+* Old :ARCHIVE:
+* Tasks :home:
+*************** kept :work:
+body
+*************** END
+"
+        );
+    }
+
+    #[test]
+    fn filetags() {
+        let input = r"#+FILETAGS: :draft:
+* Heading :work:
+";
+        assert_eq!(org_export(input), input);
+
+        let excluded = format!("#+EXCLUDE_TAGS: draft\n{input}");
+        assert_eq!(org_export(&excluded), "#+FILETAGS: :draft:\n");
+    }
+
+    #[test]
+    fn tag_groups() {
+        let input = r"#+TAGS: [ private : diary finance ] { @home(h) @work(w) }
+* Plans :@home:
+* Budget :finance:
+** Receipts
+";
+        let excluded = format!("#+EXCLUDE_TAGS: private\n{input}");
+        assert_eq!(
+            org_export(&excluded),
+            r"#+TAGS: [ private : diary finance ] { @home(h) @work(w) }
+* Plans :@home:
+"
+        );
+    }
+
+    #[test]
+    fn table_formulas() {
+        let input = r"|a|b|total|
+|-
+|2|3||
+|4|5.5|stale|
+#+TBLFM: $3=$1*$2::@1$3=vsum(@I..@>)
+";
+        assert_eq!(
+            org_export(input),
+            r"| a | b   | total |
+|---+-----+-------+
+| 2 | 3   |       |
+| 4 | 5.5 | stale |
+#+TBLFM: $3=$1*$2::@1$3=vsum(@I..@>)
+"
+        );
+
+        let conf = ConfigOptions::default().with_table_recalculation(true);
+        assert_eq!(
+            Org::export(input, conf).unwrap(),
+            r"| a | b   | 28 |
+|---+-----+----+
+| 2 | 3   | 6  |
+| 4 | 5.5 | 22 |
+#+TBLFM: $3=$1*$2::@1$3=vsum(@I..@>)
+"
+        );
+    }
+
+    #[test]
+    fn block_switches() {
+        let input = r#"#+begin_src rust -n -r -l "[%s]"
+let x = 1; [init]
+#+end_src
+#+begin_example -k
+text
+#+end_example
+"#;
+        assert_eq!(
+            org_export(input),
+            r#"#+begin_src rust -n -r -l "[%s]"
+let x = 1; [init]
+#+end_src
+
+#+begin_example -k
+text
+#+end_example
+
+"#
+        );
+    }
+
+    #[test]
+    fn table_cookies() {
+        let input = r"|<r>|<c7>||
+|/|<>||
+|1|ab|long cell|
+|-
+|223|c|<l>|
+";
+        assert_eq!(
+            org_export(input),
+            r"| <r> |  <c7>   |           |
+|   / |   <>    |           |
+|   1 |   ab    | long cell |
+|-----+---------+-----------+
+| 223 |    c    | <l>       |
+"
+        );
+    }
+
+    #[test]
+    fn invalid_table_formula() {
+        let conf = ConfigOptions::default().with_table_recalculation(true);
+        let errors = Org::export("|1|\n#+TBLFM: $1=\n", conf).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [ExportError::LogicError {
+                source: LogicErrorKind::Formula(_),
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn inline_task() {
+        let input = r"* Heading :work:
+**************** TODO [#A] task :phone:
+body
+**************** END
+*************** note
+after
+";
+        assert_eq!(org_export(input), input);
+    }
+
     #[test]
     fn drawer() {
         let a = org_export(
@@ -1304,6 +2065,169 @@ four
 four
 :end:
 
+"
+        );
+    }
+
+    #[test]
+    fn header_keyword() {
+        let input = r"#+header: :var x=1
+#+HEADER: :results output
+#+begin_src python
+print(x)
+#+end_src
+";
+        assert_eq!(
+            org_export(input),
+            r"#+HEADER: :var x=1
+#+HEADER: :results output
+#+begin_src python
+print(x)
+#+end_src
+
+"
+        );
+    }
+
+    #[test]
+    fn src_exports() {
+        let input = r"#+begin_src sh
+echo code
+#+end_src
+
+#+RESULTS:
+: code results
+
+#+name: hidden
+#+header: :var x=1
+#+begin_src sh :exports results
+echo results
+#+end_src
+
+#+RESULTS: hidden
+: results results
+
+Inline src_sh{echo 1} {{{results(=1=)}}} and src_sh[:exports both]{echo 2} {{{results(=2=)}}}.
+";
+        assert_eq!(
+            org_export(input),
+            r"#+begin_src sh
+echo code
+#+end_src
+
+#+RESULTS: hidden
+: results results
+
+Inline =1= and src_sh[:exports both]{echo 2} =2=.
+"
+        );
+
+        let conf = ConfigOptions::default().with_write_back(true);
+        let output = Org::export(input, conf).unwrap();
+        assert!(output.contains("#+name: hidden\n#+header: :var x=1\n"));
+        assert!(output.contains("src_sh{echo 1} {{{results(=1=)}}}"));
+
+        // named results before their block are replaced too
+        let input = r"#+RESULTS: late
+: stale
+
+#+name: late
+#+begin_src sh
+echo fresh
+#+end_src
+";
+        let conf = ConfigOptions::default()
+            .with_block_evaluation(true)
+            .with_write_back(true);
+        let output = Org::export(input, conf).unwrap();
+        assert!(!output.contains("stale"));
+        assert!(output.contains("#+end_src\n\n#+RESULTS: late\n: fresh\n"));
+    }
+
+    #[test]
+    fn write_back() {
+        let input = r"#+title: Kept
+#+macro: greet hello $1
+#+include: other.org
+
+{{{greet(you)}}}
+
+#+name: fig
+#+caption: A caption
+#+attr_html: :width 10
+[[file:x.png]]
+
+* COMMENT draft
+secret
+* Private :noexport:
+hidden
+* Work
+#+header: :var x=1
+#+name: sq
+#+begin_src sh :results output   :exports none
+echo $x
+#+end_src
+
+#+RESULTS: sq
+: 1
+
+#+call: sq()
+
+Inline call_sq() and [cite:@doe].
+";
+        let conf = ConfigOptions::default().with_write_back(true);
+        assert_eq!(Org::export(input, conf).unwrap(), input);
+    }
+
+    #[test]
+    fn evaluate_blocks() {
+        let input = r"#+name: greet
+#+begin_src sh :var who=world
+echo hello $who
+#+end_src
+
+#+RESULTS: greet
+: stale
+
+#+begin_src bash :results list
+echo a; echo b
+#+end_src
+
+#+begin_src sh :eval no
+echo skipped
+#+end_src
+
+#+RESULTS:
+: kept
+";
+        // written back into the document, keeping every block
+        let conf = ConfigOptions::default()
+            .with_block_evaluation(true)
+            .with_write_back(true);
+        assert_eq!(
+            Org::export(input, conf).unwrap(),
+            r"#+name: greet
+#+begin_src sh :var who=world
+echo hello $who
+#+end_src
+
+#+RESULTS: greet
+: hello world
+
+#+begin_src bash :results list
+echo a; echo b
+#+end_src
+
+#+RESULTS:
+- a
+- b
+
+#+begin_src sh :eval no
+echo skipped
+#+end_src
+
+#+RESULTS:
+: kept
 "
         );
     }
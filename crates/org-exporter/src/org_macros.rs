@@ -9,7 +9,7 @@ use thiserror::Error;
 
 use crate::ConfigOptions;
 use crate::types::FileError;
-use crate::utils::keyword_lookup;
+use crate::utils::{keyword_lookup, resolve_path};
 
 pub(crate) fn macro_handle<'a>(
     parser: &'a Parser,
@@ -35,6 +35,16 @@ pub(crate) fn macro_handle<'a>(
             }
             keyword_file(&macro_call.args[0], &macro_call.args[1], config)
         }
+        // the stored results of an inline source block
+        "results" => {
+            if macro_call.args.len() != 1 {
+                Err(MacroError::InvalidParameters {
+                    expected: 1,
+                    received: macro_call.args.len(),
+                })?
+            }
+            Ok(Cow::Owned(macro_call.args[0].to_string()))
+        }
         special_keyword @ ("title" | "author" | "email") => {
             if macro_call.args.is_empty() {
                 keyword_macro(parser, special_keyword)
@@ -107,20 +117,7 @@ pub(crate) fn keyword_file<'a>(
 ) -> Result<Cow<'a, str>, MacroError> {
     let path = Path::new(file.trim());
 
-    let target_path: Cow<Path> = if let Some(v) = config.file_path().as_ref() {
-        // TODO: error handling
-        let temp_path = v.parent().unwrap().join(path);
-        temp_path
-            .canonicalize()
-            .map_err(|e| FileError {
-                context: "Error during macro invocation of kw-file. ".into(),
-                path: temp_path,
-                source: e,
-            })?
-            .into()
-    } else {
-        path.into()
-    };
+    let target_path = resolve_path(config.file_path().as_deref(), path);
 
     let out_str = read_to_string(&target_path).map_err(|e| FileError {
         context: "Error during macro invocation of kw-file. ".into(),
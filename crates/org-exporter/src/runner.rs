@@ -0,0 +1,442 @@
+//! Runs source blocks as local subprocesses
+//!
+//! Running blocks is opt-in, see
+//! [`ConfigOptions::with_block_evaluation`](crate::ConfigOptions::with_block_evaluation).
+//! When enabled, [`Org`](crate::Org) writes fresh `#+RESULTS:` after every block it
+//! can run, and [`Html`](crate::Html) renders them in place of the stored results of
+//! blocks exporting their results.
+//!
+//! Supports `sh`, `bash` and `python` blocks with the header arguments:
+//!
+//! - `:var name=value`, where quoted values and anything that isn't a number are
+//!   passed as strings
+//! - `:dir`, the working directory, which defaults to the directory of the org file
+//! - `:results output` for what the block prints, `:results value` for the
+//!   returned value of python blocks and what shell blocks print
+//! - `:results verbatim|scalar|table|list|raw|drawer`, how the results are formatted,
+//!   where values of tab separated fields become tables unless told otherwise
+//! - `:results silent|none|discard`, which run the block but leave its results out
+//! - `:eval no|never|no-export|never-export`, which keep the block from running
+//!
+//! References to other blocks or tables in `:var` are not resolved, and results are
+//! always replaced, never appended or prepended.
+
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+use org_parser::element::{
+    Block, Collection, Handling, HeaderArgs, ResultsArgs, ResultsFormat, ResultsKind,
+};
+use org_parser::{Expr, NodeID, Parser};
+use thiserror::Error;
+
+use crate::noweb::{NowebContext, NowebError, expand_noweb};
+use crate::utils::{resolve_path, unquote};
+
+/// Output longer than this is wrapped in an example block instead of `: ` lines.
+// org-babel-min-lines-for-block-output
+const MIN_LINES_FOR_BLOCK: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum RunError {
+    #[error("node is not a source block")]
+    NotSrcBlock,
+    #[error("running `{language}` source blocks is not supported")]
+    UnsupportedLanguage { language: String },
+    #[error("failed to run `{program}`: {source}")]
+    Spawn {
+        program: String,
+        source: std::io::Error,
+    },
+    #[error("source block exited with {status}: {stderr}")]
+    Failed { status: ExitStatus, stderr: String },
+    #[error("{0}")]
+    Noweb(#[from] NowebError),
+}
+
+/// Runs a source block, returning its `#+RESULTS:` as org text.
+///
+/// Returns `None` when the block's `:results` are `silent`, `none` or `discard`.
+/// Relative paths are resolved from the directory of `file_path`, the org file
+/// containing the block.
+pub fn run_block(
+    parser: &Parser,
+    block_id: NodeID,
+    file_path: Option<&Path>,
+) -> Result<Option<String>, RunError> {
+    let Expr::Block(Block::Src { language, .. }) = &parser.pool[block_id].obj else {
+        return Err(RunError::NotSrcBlock);
+    };
+    let language = language.unwrap_or_default();
+    let program = interpreter(language).ok_or_else(|| RunError::UnsupportedLanguage {
+        language: language.into(),
+    })?;
+
+    let args = parser.header_args(block_id);
+    let body = expand_noweb(parser, block_id, NowebContext::Eval)?;
+    let script = if program == "python3" {
+        python_script(&args, &body)
+    } else {
+        shell_script(&args, &body)
+    };
+
+    let mut command = Command::new(program);
+    command.arg("-c").arg(script);
+    let dir = match &args.dir {
+        Some(dir) => Some(resolve_path(file_path, Path::new(unquote(dir))).into_owned()),
+        None => file_path.and_then(Path::parent).map(Path::to_path_buf),
+    };
+    if let Some(dir) = dir.filter(|dir| !dir.as_os_str().is_empty()) {
+        command.current_dir(dir);
+    }
+
+    let output = command.output().map_err(|source| RunError::Spawn {
+        program: program.into(),
+        source,
+    })?;
+    if !output.status.success() {
+        return Err(RunError::Failed {
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().into(),
+        });
+    }
+
+    let name = parser
+        .names
+        .iter()
+        .find_map(|(name, id)| (*id == block_id).then_some(*name));
+    Ok(format_results(
+        &String::from_utf8_lossy(&output.stdout),
+        &args.results,
+        name,
+    ))
+}
+
+/// Whether exporting runs the block, leaving out unsupported languages and blocks
+/// with an `:eval` that forbids it.
+pub(crate) fn runs_at_export(parser: &Parser, block_id: NodeID) -> bool {
+    let Expr::Block(Block::Src { language, .. }) = &parser.pool[block_id].obj else {
+        return false;
+    };
+    interpreter(language.unwrap_or_default()).is_some()
+        && !matches!(
+            parser
+                .header_args(block_id)
+                .raw
+                .get("eval")
+                .map(AsRef::as_ref),
+            Some("no" | "never" | "no-export" | "never-export")
+        )
+}
+
+/// The stored results replaced by running a block: its `#+RESULTS:` keyword, the
+/// element it holds and the blank lines between the block and the keyword.
+pub(crate) fn stored_results(parser: &Parser, block_id: NodeID) -> Vec<NodeID> {
+    let Some(keyword_id) = parser.results_keyword(block_id) else {
+        return Vec::new();
+    };
+
+    let mut ret = Vec::new();
+    if let Some(parent) = parser.pool[block_id].parent
+        && parser.pool[keyword_id].parent == Some(parent)
+        && let Some(siblings) = parser.pool[parent].obj.children()
+        && let Some(pos) = siblings.iter().position(|id| *id == block_id)
+    {
+        ret.extend(
+            siblings[pos + 1..]
+                .iter()
+                .take_while(|id| matches!(parser.pool[**id].obj, Expr::BlankLine)),
+        );
+    }
+    ret.push(keyword_id);
+    ret.extend(parser.results(block_id));
+    ret
+}
+
+fn interpreter(language: &str) -> Option<&'static str> {
+    match language {
+        "sh" | "shell" => Some("sh"),
+        "bash" => Some("bash"),
+        "python" => Some("python3"),
+        _ => None,
+    }
+}
+
+fn shell_script(args: &HeaderArgs, body: &str) -> String {
+    let mut ret = String::new();
+    for (name, val) in &args.vars {
+        let val = unquote(val).replace('\'', r"'\''");
+        ret.push_str(&format!("{name}='{val}'\n"));
+    }
+    ret.push_str(body);
+    ret
+}
+
+fn python_script(args: &HeaderArgs, body: &str) -> String {
+    let mut ret = String::new();
+    for (name, val) in &args.vars {
+        ret.push_str(&format!("{name} = {}\n", python_value(val)));
+    }
+
+    if args.results.collection == Collection::Output {
+        ret.push_str(body);
+        return ret;
+    }
+
+    // the value of a block is what its body returns, printed like org-mode
+    // reads tables back: one row per line, tab separated.
+    // the body becomes the body of a function through its syntax tree rather than
+    // by indenting its text, which would change multi-line strings
+    ret.push_str(&format!("__org_source = {}\n", python_string(body)));
+    ret.push_str(
+        r#"import ast, io, sys
+__org_module = ast.parse(__org_source)
+__org_def = ast.parse("def __org_main():\n    pass\n").body[0]
+__org_def.body = __org_module.body or __org_def.body
+__org_module.body = [__org_def]
+exec(compile(ast.fix_missing_locations(__org_module), "<org-babel>", "exec"))
+__org_stdout, sys.stdout = sys.stdout, io.StringIO()
+__org_value = __org_main()
+sys.stdout = __org_stdout
+if isinstance(__org_value, (list, tuple)):
+    for __org_row in __org_value:
+        if isinstance(__org_row, (list, tuple)):
+            print("\t".join(map(str, __org_row)))
+        else:
+            print(__org_row)
+elif __org_value is not None:
+    print(__org_value)
+"#,
+    );
+    ret
+}
+
+fn python_value(val: &str) -> String {
+    let is_number = val.parse::<f64>().is_ok()
+        && val
+            .chars()
+            .all(|chr| chr.is_ascii_digit() || matches!(chr, '+' | '-' | '.' | 'e' | 'E'));
+    if is_number {
+        val.into()
+    } else {
+        python_string(unquote(val))
+    }
+}
+
+/// A python string literal holding `val`.
+fn python_string(val: &str) -> String {
+    let mut ret = String::from('"');
+    for chr in val.chars() {
+        match chr {
+            '\\' => ret.push_str(r"\\"),
+            '"' => ret.push_str(r#"\""#),
+            '\n' => ret.push_str(r"\n"),
+            '\r' => ret.push_str(r"\r"),
+            '\t' => ret.push_str(r"\t"),
+            _ => ret.push(chr),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+/// Formats the output of a block as `#+RESULTS:` according to its `:results`.
+fn format_results(output: &str, results: &ResultsArgs, name: Option<&str>) -> Option<String> {
+    if matches!(
+        results.handling,
+        Handling::Silent | Handling::None | Handling::Discard
+    ) {
+        return None;
+    }
+
+    let mut ret = String::from("#+RESULTS:");
+    if let Some(name) = name {
+        ret.push(' ');
+        ret.push_str(name);
+    }
+    ret.push('\n');
+
+    let output = output.trim_end_matches('\n');
+    if output.is_empty() {
+        return Some(ret);
+    }
+
+    // values made of tab separated fields read back as tables
+    let kind = results.kind.or_else(|| {
+        (results.collection == Collection::Value && output.lines().all(|line| line.contains('\t')))
+            .then_some(ResultsKind::Table)
+    });
+
+    let mut body = String::new();
+    if results.format == Some(ResultsFormat::Raw) {
+        body.push_str(output);
+        body.push('\n');
+    } else {
+        match kind {
+            Some(ResultsKind::Table) => {
+                for line in output.lines() {
+                    let fields: Vec<&str> = if line.contains('\t') {
+                        line.split('\t').map(str::trim).collect()
+                    } else {
+                        line.split_whitespace().collect()
+                    };
+                    body.push_str("| ");
+                    body.push_str(&fields.join(" | "));
+                    body.push_str(" |\n");
+                }
+            }
+            Some(ResultsKind::List) => {
+                for line in output.lines() {
+                    body.push_str("- ");
+                    body.push_str(line);
+                    body.push('\n');
+                }
+            }
+            _ if output.lines().count() < MIN_LINES_FOR_BLOCK => {
+                for line in output.lines() {
+                    if line.is_empty() {
+                        body.push_str(":\n");
+                    } else {
+                        body.push_str(": ");
+                        body.push_str(line);
+                        body.push('\n');
+                    }
+                }
+            }
+            _ => {
+                body.push_str("#+begin_example\n");
+                body.push_str(output);
+                body.push_str("\n#+end_example\n");
+            }
+        }
+    }
+
+    if results.format == Some(ResultsFormat::Drawer) {
+        ret.push_str(":results:\n");
+        ret.push_str(&body);
+        ret.push_str(":end:\n");
+    } else {
+        ret.push_str(&body);
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use org_parser::parse_org;
+
+    fn run_last(input: &str) -> Result<Option<String>, RunError> {
+        let parser = parse_org(input);
+        let block_id = *parser.pool[parser.pool.root_id()]
+            .obj
+            .children()
+            .unwrap()
+            .iter()
+            .rfind(|id| matches!(parser.pool[**id].obj, Expr::Block(Block::Src { .. })))
+            .unwrap();
+        run_block(&parser, block_id, None)
+    }
+
+    #[test]
+    fn shell_vars() {
+        let input = r#"#+name: greeting
+#+begin_src sh :var name="it's me" :var n=2
+echo "$name"
+echo $((n + 1))
+#+end_src
+"#;
+        assert_eq!(
+            run_last(input).unwrap().unwrap(),
+            "#+RESULTS: greeting\n: it's me\n: 3\n"
+        );
+    }
+
+    #[test]
+    fn results_formats() {
+        let block = |results: &str, script: &str| {
+            run_last(&format!(
+                "#+begin_src bash :results {results}\n{script}\n#+end_src\n"
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(
+            block("table", r"printf 'a\t1\nb c\t2\n'").unwrap(),
+            "#+RESULTS:\n| a | 1 |\n| b c | 2 |\n"
+        );
+        assert_eq!(
+            block("list", "echo one; echo two").unwrap(),
+            "#+RESULTS:\n- one\n- two\n"
+        );
+        assert_eq!(
+            block("raw", "echo '*bold*'").unwrap(),
+            "#+RESULTS:\n*bold*\n"
+        );
+        assert_eq!(
+            block("verbatim drawer", "echo a; echo; echo b").unwrap(),
+            "#+RESULTS:\n:results:\n: a\n:\n: b\n:end:\n"
+        );
+        assert_eq!(
+            block("output", "seq 10").unwrap(),
+            "#+RESULTS:\n#+begin_example\n1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n#+end_example\n"
+        );
+        assert_eq!(block("silent", "echo hidden"), None);
+        assert_eq!(block("replace", "true").unwrap(), "#+RESULTS:\n");
+    }
+
+    #[test]
+    fn python_value_and_output() {
+        let input = r#"#+begin_src python :var rows=2 :var label="x"
+print("ignored")
+return [[label, i * 2] for i in range(rows)]
+#+end_src
+"#;
+        assert_eq!(
+            run_last(input).unwrap().unwrap(),
+            "#+RESULTS:\n| x | 0 |\n| x | 2 |\n"
+        );
+
+        let input = r#"#+begin_src python
+text = """a
+  b\tc"""
+return text
+#+end_src
+"#;
+        assert_eq!(
+            run_last(input).unwrap().unwrap(),
+            "#+RESULTS:\n: a\n:   b\tc\n"
+        );
+
+        let input = r#"#+begin_src python :results output table
+print("a\tb")
+#+end_src
+"#;
+        assert_eq!(run_last(input).unwrap().unwrap(), "#+RESULTS:\n| a | b |\n");
+    }
+
+    #[test]
+    fn working_dir() {
+        let dir = std::env::temp_dir().join("org-rust-runner-dir");
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = format!("#+begin_src sh :dir {}\npwd\n#+end_src\n", dir.display());
+        assert_eq!(
+            run_last(&input).unwrap().unwrap(),
+            format!("#+RESULTS:\n: {}\n", dir.canonicalize().unwrap().display())
+        );
+    }
+
+    #[test]
+    fn run_errors() {
+        assert!(matches!(
+            run_last("#+begin_src sh\necho oops >&2; exit 3\n#+end_src\n"),
+            Err(RunError::Failed { stderr, .. }) if stderr == "oops"
+        ));
+        assert!(matches!(
+            run_last("#+begin_src rust\nfn main() {}\n#+end_src\n"),
+            Err(RunError::UnsupportedLanguage { language }) if language == "rust"
+        ));
+    }
+}
@@ -0,0 +1,826 @@
+//! Evaluates `#+TBLFM:` table formulas
+//!
+//! ```org
+//! | item  | qty | price | total |
+//! |-------+-----+-------+-------|
+//! | pens  |   3 |   1.5 |       |
+//! | paper |   2 |     4 |       |
+//! |-------+-----+-------+-------|
+//! | sum   |     |       |       |
+//! #+TBLFM: $4=$2*$3::@>$4=vsum(@I..@II)
+//! ```
+//!
+//! Supports a subset of the org-table spreadsheet:
+//!
+//! - column formulas (`$4=`) for every row below the header
+//! - field formulas (`@3$4=`), which take precedence over column formulas
+//! - rows and columns by number, from the edges (`@<`, `@>`, `$>`) or relative to
+//!   the current field (`@-1`, `$+1`)
+//! - hline references (`@I`, `@II`, `@-I`, `@+I`) and ranges (`@I..@II`, `$1..$3`)
+//! - `+ - * / ^`, `vsum`, `vmean`, `vmin`, `vmax`, `vcount` and `round`
+//! - the `;%.2f` format and the `;N` flag, which reads non-numbers as zero
+//!
+//! Fields that fail to evaluate are set to `#ERROR`, like org-mode does. Rows of
+//! alignment cookies or column groups count towards row numbers, but read as
+//! empty fields and are never assigned to.
+
+use org_parser::element::TableRow;
+use org_parser::{Expr, NodeID, Parser};
+use thiserror::Error;
+
+/// Recomputes a table from the first of its `#+TBLFM:` lines.
+///
+/// Returns the text of every field, one entry per table row. Horizontal rules and
+/// rows of alignment cookies or column groups are empty rows. A table without
+/// formulas is returned unchanged.
+pub fn recalculate_table(
+    parser: &Parser,
+    table_id: NodeID,
+) -> Result<Vec<Vec<String>>, FormulaError> {
+    let Expr::Table(table) = &parser.pool[table_id].obj else {
+        return Err(FormulaError::NotATable);
+    };
+
+    let mut sheet = Sheet {
+        lines: Vec::with_capacity(table.children.len()),
+        special: Vec::new(),
+        cols: table.cols,
+    };
+    for (line, id) in table.children.iter().enumerate() {
+        sheet.lines.push(match &parser.pool[*id].obj {
+            Expr::TableRow(TableRow::Standard(cells)) => {
+                let mut fields: Vec<String> = cells
+                    .iter()
+                    .map(|cell_id| cell_text(parser, *cell_id).into())
+                    .collect();
+                fields.resize(table.cols, String::new());
+                Some(fields)
+            }
+            Expr::TableRow(TableRow::Cookies(_) | TableRow::ColumnGroups(_)) => {
+                sheet.special.push(line);
+                Some(vec![String::new(); table.cols])
+            }
+            _ => None,
+        });
+    }
+
+    if let Some(line) = table.formulas.first() {
+        let mut formulas = line
+            .split("::")
+            .filter(|formula| !formula.trim().is_empty())
+            .map(Formula::parse)
+            .collect::<Result<Vec<Formula>, FormulaError>>()?;
+        // field formulas overwrite what column formulas computed
+        formulas.sort_by_key(|formula| matches!(formula.target, Target::Field(..)));
+
+        for formula in &formulas {
+            sheet.apply(formula);
+        }
+    }
+
+    Ok(sheet
+        .lines
+        .into_iter()
+        .enumerate()
+        .map(|(line, fields)| {
+            if sheet.special.contains(&line) {
+                Vec::new()
+            } else {
+                fields.unwrap_or_default()
+            }
+        })
+        .collect())
+}
+
+/// The trimmed source text of a table cell.
+pub(crate) fn cell_text<'a>(parser: &Parser<'a>, cell_id: NodeID) -> &'a str {
+    let node = &parser.pool[cell_id];
+    parser.source[node.start..node.end]
+        .trim_end_matches(['|', '\n'])
+        .trim()
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum FormulaError {
+    #[error("node is not a table")]
+    NotATable,
+    #[error("invalid table formula `{formula}`: {reason}")]
+    InvalidFormula { formula: String, reason: String },
+}
+
+/// The rows of a table, `None` for horizontal rules.
+struct Sheet {
+    lines: Vec<Option<Vec<String>>>,
+    /// Rows of alignment cookies or column groups.
+    special: Vec<usize>,
+    cols: usize,
+}
+
+struct Formula {
+    target: Target,
+    term: Term,
+    format: Format,
+}
+
+enum Target {
+    Column(ColRef),
+    Field(RowRef, ColRef),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RowRef {
+    /// `@3`
+    Abs(usize),
+    /// `@-1`, `@+1`
+    Rel(isize),
+    /// `@<`, `@<<`, counting from one
+    First(usize),
+    /// `@>`, `@>>`, counting from one
+    Last(usize),
+    /// `@II+1`: the hline (counting from one), whether it's searched for
+    /// relative to the current row, and a line offset
+    Hline {
+        index: usize,
+        direction: Option<Direction>,
+        offset: isize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColRef {
+    Abs(usize),
+    Rel(isize),
+    First(usize),
+    Last(usize),
+}
+
+/// A field reference, missing parts refer to the current row or column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FieldRef {
+    row: Option<RowRef>,
+    col: Option<ColRef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Num(f64),
+    Field(FieldRef),
+    Range(FieldRef, FieldRef),
+    Neg(Box<Term>),
+    Binary(char, Box<Term>, Box<Term>),
+    Call(Function, Vec<Term>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Count,
+    Round,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Format {
+    /// `%.2f`
+    precision: Option<usize>,
+    /// `N`
+    numbers: bool,
+}
+
+impl Formula {
+    fn parse(source: &str) -> Result<Self, FormulaError> {
+        let err = |reason: &str| FormulaError::InvalidFormula {
+            formula: source.trim().into(),
+            reason: reason.into(),
+        };
+
+        let (lhs, rhs) = source.split_once('=').ok_or_else(|| err("missing `=`"))?;
+        let (rhs, format) = rhs.split_once(';').unwrap_or((rhs, ""));
+
+        let mut lhs = Lexer::new(lhs.trim());
+        let target = match lhs.field_ref().map_err(|e| err(&e))? {
+            Some(FieldRef {
+                row: None,
+                col: Some(col),
+            }) if !matches!(col, ColRef::Rel(_)) => Target::Column(col),
+            Some(FieldRef {
+                row: Some(row),
+                col: Some(col),
+            }) if !matches!(row, RowRef::Rel(_)) && !matches!(col, ColRef::Rel(_)) => {
+                Target::Field(row, col)
+            }
+            _ => return Err(err("expected a column or field to assign to")),
+        };
+        if !lhs.at_end() {
+            return Err(err("expected a column or field to assign to"));
+        }
+
+        let mut rhs = Lexer::new(rhs.trim());
+        let term = rhs.expr().map_err(|e| err(&e))?;
+        if !rhs.at_end() {
+            return Err(err("unexpected input after the expression"));
+        }
+
+        Ok(Self {
+            target,
+            term,
+            format: Format::parse(format).ok_or_else(|| err("unsupported format"))?,
+        })
+    }
+}
+
+impl Format {
+    fn parse(source: &str) -> Option<Self> {
+        let mut ret = Self::default();
+        let mut rest = source.trim();
+        while !rest.is_empty() {
+            if let Some(spec) = rest.strip_prefix("%.") {
+                let digits = spec.find(|chr: char| !chr.is_ascii_digit())?;
+                ret.precision = Some(spec[..digits].parse().ok()?);
+                rest = spec[digits..].strip_prefix('f')?;
+            } else if let Some(spec) = rest.strip_prefix("%d") {
+                ret.precision = Some(0);
+                rest = spec;
+            } else if let Some(spec) = rest.strip_prefix('N') {
+                ret.numbers = true;
+                rest = spec;
+            } else {
+                return None;
+            }
+        }
+        Some(ret)
+    }
+
+    fn display(&self, val: f64) -> String {
+        if let Some(precision) = self.precision {
+            format!("{val:.precision$}")
+        } else if val.fract() == 0.0 && val.abs() < 1e15 {
+            format!("{}", val as i64)
+        } else {
+            // at most 12 significant digits, like Calc
+            let digits = 11 - val.abs().log10().floor().clamp(-12.0, 11.0) as i32;
+            let ret = format!("{val:.0$}", digits.max(0) as usize);
+            ret.trim_end_matches('0').trim_end_matches('.').into()
+        }
+    }
+}
+
+/// A recursive descent parser over the right-hand side of a formula.
+struct Lexer<'a> {
+    source: &'a str,
+    index: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source, index: 0 }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.index..]
+    }
+
+    fn skip_ws(&mut self) {
+        self.index = self.source.len() - self.rest().trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_ws();
+        self.rest().is_empty()
+    }
+
+    fn eat(&mut self, word: &str) -> bool {
+        self.skip_ws();
+        if self.rest().starts_with(word) {
+            self.index += word.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, func: impl Fn(char) -> bool) -> &'a str {
+        let start = self.index;
+        let len = self
+            .rest()
+            .find(|chr: char| !func(chr))
+            .unwrap_or(self.rest().len());
+        self.index += len;
+        &self.source[start..self.index]
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn expr(&mut self) -> Result<Term, String> {
+        let mut ret = self.product()?;
+        loop {
+            let op = if self.eat("+") {
+                '+'
+            } else if self.eat("-") {
+                '-'
+            } else {
+                return Ok(ret);
+            };
+            ret = Term::Binary(op, Box::new(ret), Box::new(self.product()?));
+        }
+    }
+
+    // product := unary (('*' | '/') unary)*
+    fn product(&mut self) -> Result<Term, String> {
+        let mut ret = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                '*'
+            } else if self.eat("/") {
+                '/'
+            } else {
+                return Ok(ret);
+            };
+            ret = Term::Binary(op, Box::new(ret), Box::new(self.unary()?));
+        }
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> Result<Term, String> {
+        if self.eat("-") {
+            Ok(Term::Neg(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    // power := primary ('^' unary)?
+    fn power(&mut self) -> Result<Term, String> {
+        let base = self.primary()?;
+        if self.eat("^") {
+            Ok(Term::Binary('^', Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn primary(&mut self) -> Result<Term, String> {
+        self.skip_ws();
+        if self.eat("(") {
+            let ret = self.expr()?;
+            return if self.eat(")") {
+                Ok(ret)
+            } else {
+                Err("unclosed parenthesis".into())
+            };
+        }
+
+        if let Some(start) = self.field_ref()? {
+            return if self.eat("..") {
+                let end = self
+                    .field_ref()?
+                    .ok_or_else(|| String::from("expected the end of the range"))?;
+                Ok(Term::Range(start, end))
+            } else {
+                Ok(Term::Field(start))
+            };
+        }
+
+        let rest = self.rest();
+        if rest.starts_with(|chr: char| chr.is_ascii_digit() || chr == '.') {
+            let num = self.take_while(|chr| chr.is_ascii_digit() || chr == '.');
+            return num
+                .parse()
+                .map(Term::Num)
+                .map_err(|_| format!("invalid number `{num}`"));
+        }
+
+        let name = self.take_while(|chr| chr.is_ascii_alphanumeric());
+        let func = match name {
+            "vsum" => Function::Sum,
+            "vmean" => Function::Mean,
+            "vmin" => Function::Min,
+            "vmax" => Function::Max,
+            "vcount" => Function::Count,
+            "round" => Function::Round,
+            "" => return Err("expected a value".into()),
+            _ => return Err(format!("unknown function `{name}`")),
+        };
+        if !self.eat("(") {
+            return Err(format!("expected arguments to `{name}`"));
+        }
+        let mut args = vec![self.expr()?];
+        while self.eat(",") {
+            args.push(self.expr()?);
+        }
+        if !self.eat(")") {
+            return Err(format!("unclosed arguments to `{name}`"));
+        }
+        Ok(Term::Call(func, args))
+    }
+
+    /// Parses `@row$col`, `@row` or `$col`.
+    fn field_ref(&mut self) -> Result<Option<FieldRef>, String> {
+        self.skip_ws();
+        let row = if self.eat("@") {
+            Some(self.row_ref()?)
+        } else {
+            None
+        };
+        let col = if self.rest().starts_with('$') {
+            self.index += 1;
+            Some(self.col_ref()?)
+        } else {
+            None
+        };
+
+        if row.is_none() && col.is_none() {
+            Ok(None)
+        } else {
+            Ok(Some(FieldRef { row, col }))
+        }
+    }
+
+    fn row_ref(&mut self) -> Result<RowRef, String> {
+        let direction = if self.rest().starts_with("-I") {
+            self.index += 1;
+            Some(Direction::Up)
+        } else if self.rest().starts_with("+I") {
+            self.index += 1;
+            Some(Direction::Down)
+        } else {
+            None
+        };
+
+        let hlines = self.take_while(|chr| chr == 'I');
+        if !hlines.is_empty() {
+            let offset = if let Some(sign @ ('+' | '-')) = self.rest().chars().next()
+                && self.rest()[1..].starts_with(|chr: char| chr.is_ascii_digit())
+            {
+                self.index += 1;
+                let num = self.number()? as isize;
+                if sign == '-' { -num } else { num }
+            } else {
+                0
+            };
+            return Ok(RowRef::Hline {
+                index: hlines.len(),
+                direction,
+                offset,
+            });
+        }
+
+        match self.edge_or_number()? {
+            Edge::First(count) => Ok(RowRef::First(count)),
+            Edge::Last(count) => Ok(RowRef::Last(count)),
+            Edge::Abs(num) => Ok(RowRef::Abs(num)),
+            Edge::Rel(num) => Ok(RowRef::Rel(num)),
+        }
+    }
+
+    fn col_ref(&mut self) -> Result<ColRef, String> {
+        match self.edge_or_number()? {
+            Edge::First(count) => Ok(ColRef::First(count)),
+            Edge::Last(count) => Ok(ColRef::Last(count)),
+            Edge::Abs(num) => Ok(ColRef::Abs(num)),
+            Edge::Rel(num) => Ok(ColRef::Rel(num)),
+        }
+    }
+
+    // <, >>, 3, -1, +2
+    fn edge_or_number(&mut self) -> Result<Edge, String> {
+        let first = self.take_while(|chr| chr == '<');
+        if !first.is_empty() {
+            return Ok(Edge::First(first.len()));
+        }
+        let last = self.take_while(|chr| chr == '>');
+        if !last.is_empty() {
+            return Ok(Edge::Last(last.len()));
+        }
+
+        if self.rest().starts_with('-') {
+            self.index += 1;
+            Ok(Edge::Rel(-(self.number()? as isize)))
+        } else if self.rest().starts_with('+') {
+            self.index += 1;
+            Ok(Edge::Rel(self.number()? as isize))
+        } else {
+            Ok(Edge::Abs(self.number()?))
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, String> {
+        let num = self.take_while(|chr| chr.is_ascii_digit());
+        num.parse()
+            .map_err(|_| format!("invalid reference at `{}`", self.rest()))
+    }
+}
+
+enum Edge {
+    First(usize),
+    Last(usize),
+    Abs(usize),
+    Rel(isize),
+}
+
+/// A value produced while evaluating a formula.
+enum Value {
+    Num(f64),
+    Vector(Vec<f64>),
+}
+
+impl Sheet {
+    fn apply(&mut self, formula: &Formula) {
+        match formula.target {
+            Target::Column(col) => {
+                let Some(col) = self.col(col, 0) else {
+                    return;
+                };
+                // rows above the first hline make up the header, unless
+                // there are no rows below it
+                let header_end = self
+                    .lines
+                    .iter()
+                    .position(Option::is_none)
+                    .filter(|hline| self.data_lines().any(|line| line > *hline));
+                let lines: Vec<usize> = self
+                    .data_lines()
+                    .filter(|line| header_end.is_none_or(|hline| *line > hline))
+                    .collect();
+                for line in lines {
+                    self.assign(formula, line, col);
+                }
+            }
+            Target::Field(row, col) => {
+                if let (Some(line), Some(col)) = (self.row(row, 0), self.col(col, 0)) {
+                    self.assign(formula, line, col);
+                }
+            }
+        }
+    }
+
+    fn assign(&mut self, formula: &Formula, line: usize, col: usize) {
+        if self.special.contains(&line) {
+            return;
+        }
+        let val = match self.eval(&formula.term, formula.format, line, col) {
+            Some(Value::Num(num)) if num.is_finite() => formula.format.display(num),
+            _ => "#ERROR".into(),
+        };
+        if let Some(Some(fields)) = self.lines.get_mut(line) {
+            fields[col] = val;
+        }
+    }
+
+    fn data_lines(&self) -> impl Iterator<Item = usize> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| line.as_ref().map(|_| i))
+    }
+
+    /// Resolves a row reference to the index of a line, relative to `curr`.
+    fn row(&self, row: RowRef, curr: usize) -> Option<usize> {
+        let data: Vec<usize> = self.data_lines().collect();
+        match row {
+            RowRef::Abs(num) => data.get(num.checked_sub(1)?).copied(),
+            RowRef::First(count) => data.get(count - 1).copied(),
+            RowRef::Last(count) => data.get(data.len().checked_sub(count)?).copied(),
+            RowRef::Rel(diff) => {
+                let pos = data.iter().position(|line| *line == curr)?;
+                data.get(pos.checked_add_signed(diff)?).copied()
+            }
+            RowRef::Hline {
+                index,
+                direction,
+                offset,
+            } => {
+                let hlines: Vec<usize> = (0..self.lines.len())
+                    .filter(|line| self.lines[*line].is_none())
+                    .collect();
+                let hline = match direction {
+                    None => hlines.get(index - 1),
+                    Some(Direction::Up) => hlines
+                        .iter()
+                        .rev()
+                        .filter(|line| **line < curr)
+                        .nth(index - 1),
+                    Some(Direction::Down) => {
+                        hlines.iter().filter(|line| **line > curr).nth(index - 1)
+                    }
+                }?;
+                hline.checked_add_signed(offset)
+            }
+        }
+    }
+
+    fn col(&self, col: ColRef, curr: usize) -> Option<usize> {
+        let ret = match col {
+            ColRef::Abs(num) => num.checked_sub(1)?,
+            ColRef::First(count) => count - 1,
+            ColRef::Last(count) => self.cols.checked_sub(count)?,
+            ColRef::Rel(diff) => curr.checked_add_signed(diff)?,
+        };
+        (ret < self.cols).then_some(ret)
+    }
+
+    fn field(&self, field: FieldRef, line: usize, col: usize) -> Option<(usize, usize)> {
+        let row = field.row.map_or(Some(line), |row| self.row(row, line))?;
+        let col = field
+            .col
+            .map_or(Some(col), |col_ref| self.col(col_ref, col))?;
+        Some((row, col))
+    }
+
+    /// Reads a field as a number, `None` when it's empty.
+    fn number(&self, line: usize, col: usize, format: Format) -> Option<Option<f64>> {
+        let text = self.lines.get(line)?.as_ref()?.get(col)?.trim();
+        if text.is_empty() {
+            Some(None)
+        } else if let Ok(num) = text.parse() {
+            Some(Some(num))
+        } else if format.numbers {
+            Some(Some(0.0))
+        } else {
+            None
+        }
+    }
+
+    fn eval(&self, term: &Term, format: Format, line: usize, col: usize) -> Option<Value> {
+        let num = |term: &Term| match self.eval(term, format, line, col)? {
+            Value::Num(num) => Some(num),
+            Value::Vector(_) => None,
+        };
+
+        Some(match term {
+            Term::Num(num) => Value::Num(*num),
+            Term::Field(field) => {
+                let (line, col) = self.field(*field, line, col)?;
+                Value::Num(self.number(line, col, format)?.unwrap_or_default())
+            }
+            Term::Range(start, end) => {
+                let (start_line, start_col) = self.field(*start, line, col)?;
+                let (end_line, end_col) = self.field(*end, line, col)?;
+                let mut ret = Vec::new();
+                for line in start_line.min(end_line)..=start_line.max(end_line) {
+                    if self.lines.get(line)?.is_none() {
+                        continue;
+                    }
+                    for col in start_col.min(end_col)..=start_col.max(end_col) {
+                        // empty fields are left out of ranges
+                        ret.extend(self.number(line, col, format)?);
+                    }
+                }
+                Value::Vector(ret)
+            }
+            Term::Neg(inner) => Value::Num(-num(inner)?),
+            Term::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (num(lhs)?, num(rhs)?);
+                Value::Num(match op {
+                    '+' => lhs + rhs,
+                    '-' => lhs - rhs,
+                    '*' => lhs * rhs,
+                    '/' => lhs / rhs,
+                    _ => lhs.powf(rhs),
+                })
+            }
+            Term::Call(Function::Round, args) => {
+                let val = num(args.first()?)?;
+                let digits = args.get(1).map_or(Some(0.0), num)?;
+                let scale = 10f64.powi(digits as i32);
+                Value::Num((val * scale).round() / scale)
+            }
+            Term::Call(func, args) => {
+                let mut vals = Vec::new();
+                for arg in args {
+                    match self.eval(arg, format, line, col)? {
+                        Value::Num(num) => vals.push(num),
+                        Value::Vector(nums) => vals.extend(nums),
+                    }
+                }
+                Value::Num(match func {
+                    Function::Sum => vals.iter().sum(),
+                    Function::Mean => vals.iter().sum::<f64>() / vals.len() as f64,
+                    Function::Min => vals.iter().copied().reduce(f64::min)?,
+                    Function::Max => vals.iter().copied().reduce(f64::max)?,
+                    Function::Count => vals.len() as f64,
+                    Function::Round => unreachable!("handled above"),
+                })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use org_parser::parse_org;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn recalc(input: &str) -> Result<Vec<Vec<String>>, FormulaError> {
+        let parser = parse_org(input);
+        let table_id = parser.pool[parser.pool.root_id()].obj.children().unwrap()[0];
+        recalculate_table(&parser, table_id)
+    }
+
+    #[test]
+    fn column_and_field_formulas() {
+        let input = r"| item  | qty | price | total |
+|-------+-----+-------+-------|
+| pens  |   3 |   1.5 |       |
+| paper |   2 |     4 |       |
+|-------+-----+-------+-------|
+| sum   |     |       |       |
+#+TBLFM: @>$4=vsum(@I..@II)::$4=$2*$3::@>$2=vmax(@2..@-1);%.2f
+";
+        assert_eq!(
+            recalc(input).unwrap(),
+            [
+                vec!["item", "qty", "price", "total"],
+                vec![],
+                vec!["pens", "3", "1.5", "4.5"],
+                vec!["paper", "2", "4", "8"],
+                vec![],
+                vec!["sum", "3.00", "", "12.5"],
+            ]
+        );
+    }
+
+    #[test]
+    fn references() {
+        let input = r"| 1 |   |   |
+|---+---+---|
+| 2 |   |   |
+| 3 |   |   |
+#+TBLFM: $2=@-1$1+$-1::$3=round(vmean($1..$2) / 3, 2)::@1$2=@>$1^2
+";
+        assert_eq!(
+            recalc(input).unwrap(),
+            [
+                vec!["1", "9", ""],
+                vec![],
+                vec!["2", "3", "0.83"],
+                vec!["3", "5", "1.33"],
+            ]
+        );
+    }
+
+    #[test]
+    fn field_errors() {
+        let input = r"| a | 1 |   |
+| 4 | 0 |   |
+#+TBLFM: $3=$1/$2::@1$1=vcount(@1$2..@>$2)
+";
+        assert_eq!(
+            recalc(input).unwrap(),
+            [vec!["2", "1", "#ERROR"], vec!["4", "0", "#ERROR"]]
+        );
+
+        let input = "| a | 1 |   |\n#+TBLFM: $3=$1+$2;N\n";
+        assert_eq!(recalc(input).unwrap(), [vec!["a", "1", "1"]]);
+    }
+
+    #[test]
+    fn special_rows() {
+        let input = r"| / | <  | >   |
+| 1 | 2  |     |
+|   |    | <r> |
+| 3 | 4  |     |
+#+TBLFM: $3=$1+$2::@3$1=vsum(@1$2..@>$2)
+";
+        assert_eq!(
+            recalc(input).unwrap(),
+            [vec![], vec!["1", "2", "3"], vec![], vec!["3", "4", "7"],]
+        );
+    }
+
+    #[test]
+    fn invalid_formulas() {
+        for formula in [
+            "$1",
+            "$1=foo($2)",
+            "$1=($2",
+            "@-1$1=2",
+            "$1=2 3",
+            "$1=$2;%x",
+        ] {
+            let input = format!("| 1 | 2 |\n#+TBLFM: {formula}\n");
+            assert!(
+                matches!(recalc(&input), Err(FormulaError::InvalidFormula { .. })),
+                "{formula}"
+            );
+        }
+    }
+
+    #[test]
+    fn number_display() {
+        let format = Format::default();
+        assert_eq!(format.display(3.0), "3");
+        assert_eq!(format.display(-0.5), "-0.5");
+        assert_eq!(format.display(1.0 / 3.0), "0.333333333333");
+        assert_eq!(format.display(0.1 + 0.2), "0.3");
+    }
+}
@@ -0,0 +1,441 @@
+//! Extracts source blocks into the files named by their `:tangle` header argument
+//!
+//! ```org
+//! #+begin_src sh :tangle install.sh :shebang "#!/bin/sh" :mkdirp yes
+//! echo installing
+//! #+end_src
+//! ```
+//!
+//! Supports the header arguments:
+//!
+//! - `:tangle yes|no|path`, `yes` names the file after the org file with the
+//!   extension of the block's language
+//! - `:mkdirp yes`, creates missing parent directories
+//! - `:shebang`, the first line of the file, which is also made executable
+//! - `:padline no`, drops the blank line between blocks of the same file
+//! - `:comments link`, surrounds every block with comments linking back to it
+//! - `:noweb`, see [`expand_noweb`](crate::expand_noweb)
+//!
+//! Blocks under a `COMMENT` heading are not tangled.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use org_parser::element::{Block, Tangle};
+use org_parser::{Expr, NodeID, Parser};
+use thiserror::Error;
+
+use crate::noweb::{NowebContext, NowebError, expand_noweb};
+use crate::types::FileError;
+use crate::utils::{relative_path, resolve_path, unquote};
+
+/// A file produced by tangling, see [`tangle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TangledFile {
+    pub path: PathBuf,
+    pub contents: String,
+    /// Whether missing parent directories are created.
+    pub mkdirp: bool,
+    /// Whether the file is made executable, set when it starts with a `:shebang`.
+    pub executable: bool,
+    /// The number of blocks written to the file.
+    pub blocks: usize,
+}
+
+#[derive(Debug, Error)]
+pub enum TangleError {
+    #[error("`:tangle yes` requires the path of the org file to name the tangled file")]
+    NoFileName,
+    #[error("{0}")]
+    File(#[from] FileError),
+    #[error("{}-{}: {0}", .0.span().start, .0.span().end)]
+    Noweb(#[from] NowebError),
+}
+
+/// Collects the source blocks of a document into the files they are tangled to.
+///
+/// Relative paths are resolved from the directory of `file_path`, the org file
+/// being tangled. Files are returned in the order they are first tangled to.
+pub fn tangle(parser: &Parser, file_path: Option<&Path>) -> Result<Vec<TangledFile>, TangleError> {
+    let mut blocks = Vec::new();
+    collect_blocks(parser, parser.pool.root_id(), &mut blocks);
+
+    let mut files: Vec<TangledFile> = Vec::new();
+    let mut indices: HashMap<PathBuf, usize> = HashMap::new();
+    // blocks tangled so far under each heading, for the names of link comments
+    let mut heading_counts: HashMap<Option<NodeID>, usize> = HashMap::new();
+
+    for block_id in blocks {
+        let Expr::Block(Block::Src { language, .. }) = &parser.pool[block_id].obj else {
+            unreachable!()
+        };
+        let heading = enclosing_heading(parser, block_id);
+        if is_commented(parser, heading) {
+            continue;
+        }
+
+        let args = parser.header_args(block_id);
+        let target = match &args.tangle {
+            Tangle::No => continue,
+            Tangle::Yes => {
+                let file_path = file_path.ok_or(TangleError::NoFileName)?;
+                let extension = language.map_or("txt", lang_extension);
+                file_path.with_extension(extension)
+            }
+            Tangle::File(path) => resolve_path(file_path, Path::new(unquote(path))).into_owned(),
+        };
+        let flag = |name: &str| args.raw.get(name).map(|val| unquote(val));
+
+        let index = *indices.entry(target.clone()).or_insert_with(|| {
+            files.push(TangledFile {
+                path: target.clone(),
+                contents: String::new(),
+                mkdirp: false,
+                executable: false,
+                blocks: 0,
+            });
+            files.len() - 1
+        });
+        let file = &mut files[index];
+
+        file.mkdirp |= flag("mkdirp") == Some("yes");
+        if file.blocks == 0 {
+            if let Some(shebang) = flag("shebang").filter(|shebang| !shebang.is_empty()) {
+                file.contents.push_str(shebang);
+                file.contents.push('\n');
+                file.executable = true;
+            }
+        } else if flag("padline") != Some("no") {
+            file.contents.push('\n');
+        }
+
+        let mut body = expand_noweb(parser, block_id, NowebContext::Tangle)?.into_owned();
+        if !body.is_empty() && !body.ends_with('\n') {
+            body.push('\n');
+        }
+
+        if matches!(flag("comments"), Some("link" | "yes" | "both")) {
+            let count = heading_counts.entry(heading).or_default();
+            *count += 1;
+            let (search, name) = link_target(parser, block_id, heading, *count);
+            let comment = comment_start(language.unwrap_or_default());
+            let org_path = file_path.map_or(PathBuf::new(), |file_path| {
+                relative_path(file_path, target.parent().unwrap_or(Path::new("")))
+            });
+
+            // writing to a String is infallible
+            let _ = writeln!(
+                file.contents,
+                "{comment} [[file:{}::{search}][{name}]]",
+                org_path.display()
+            );
+            file.contents.push_str(&body);
+            let _ = writeln!(file.contents, "{comment} {name} ends here");
+        } else {
+            file.contents.push_str(&body);
+        }
+        file.blocks += 1;
+    }
+
+    Ok(files)
+}
+
+/// Tangles a document and writes the resulting files, see [`tangle`].
+pub fn tangle_files(
+    parser: &Parser,
+    file_path: Option<&Path>,
+) -> Result<Vec<TangledFile>, TangleError> {
+    let files = tangle(parser, file_path)?;
+    for file in &files {
+        file.write()?;
+    }
+    Ok(files)
+}
+
+impl TangledFile {
+    /// Writes the file, replacing its previous contents.
+    pub fn write(&self) -> Result<(), TangleError> {
+        let file_error = |context: &str, path: &Path| {
+            let path = path.to_path_buf();
+            let context = context.to_string();
+            move |source| FileError {
+                context,
+                path,
+                source,
+            }
+        };
+
+        if self.mkdirp
+            && let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)
+                .map_err(file_error("failed to create directory ", parent))?;
+        }
+        std::fs::write(&self.path, &self.contents)
+            .map_err(file_error("failed to write tangled file ", &self.path))?;
+
+        #[cfg(unix)]
+        if self.executable {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mut permissions = std::fs::metadata(&self.path)
+                .map_err(file_error("failed to read permissions of ", &self.path))?
+                .permissions();
+            permissions.set_mode(permissions.mode() | 0o111);
+            std::fs::set_permissions(&self.path, permissions)
+                .map_err(file_error("failed to make executable ", &self.path))?;
+        }
+        Ok(())
+    }
+}
+
+fn collect_blocks(parser: &Parser, id: NodeID, blocks: &mut Vec<NodeID>) {
+    let obj = &parser.pool[id].obj;
+    if let Expr::Block(Block::Src { .. }) = obj {
+        blocks.push(id);
+    } else if let Some(children) = obj.children() {
+        for child in children {
+            collect_blocks(parser, *child, blocks);
+        }
+    }
+}
+
+fn enclosing_heading(parser: &Parser, id: NodeID) -> Option<NodeID> {
+    let mut curr = parser.pool[id].parent;
+    while let Some(id) = curr {
+        if let Expr::Heading(_) = parser.pool[id].obj {
+            return Some(id);
+        }
+        curr = parser.pool[id].parent;
+    }
+    None
+}
+
+fn is_commented(parser: &Parser, heading: Option<NodeID>) -> bool {
+    let mut curr = heading;
+    while let Some(id) = curr {
+        if let Expr::Heading(heading) = &parser.pool[id].obj
+            && heading.comment
+        {
+            return true;
+        }
+        curr = parser.pool[id].parent;
+    }
+    false
+}
+
+/// The search option and description of the link back to a block, named like
+/// org-mode does: after the block's `#+NAME:`, or its heading and position under it.
+fn link_target(
+    parser: &Parser,
+    block_id: NodeID,
+    heading: Option<NodeID>,
+    count: usize,
+) -> (String, String) {
+    if let Some((name, _)) = parser.names.iter().find(|(_, id)| **id == block_id) {
+        return (name.to_string(), name.to_string());
+    }
+
+    let title = heading.and_then(|id| match &parser.pool[id].obj {
+        Expr::Heading(heading) => heading.title.as_ref().map(|(title, _)| title.trim()),
+        _ => None,
+    });
+    match title {
+        Some(title) => (format!("*{title}"), format!("{title}:{count}")),
+        None => (String::new(), format!("No heading:{count}")),
+    }
+}
+
+// org-babel-tangle-lang-exts
+fn lang_extension(language: &str) -> &str {
+    match language {
+        "emacs-lisp" | "elisp" => "el",
+        "python" => "py",
+        "shell" | "sh" | "bash" | "zsh" => "sh",
+        "rust" => "rs",
+        "C" => "c",
+        "C++" | "cpp" => "cpp",
+        "javascript" => "js",
+        "haskell" => "hs",
+        "ruby" => "rb",
+        "perl" => "pl",
+        "latex" => "tex",
+        "markdown" => "md",
+        "clojure" => "clj",
+        "elixir" => "ex",
+        "typescript" => "ts",
+        "nix" => "nix",
+        other => other,
+    }
+}
+
+fn comment_start(language: &str) -> &'static str {
+    match language {
+        "C" | "C++" | "cpp" | "rust" | "js" | "javascript" | "typescript" | "ts" | "go"
+        | "java" | "kotlin" | "swift" | "scala" | "dart" | "zig" => "//",
+        "emacs-lisp" | "elisp" | "lisp" | "scheme" | "clojure" | "racket" => ";;",
+        "lua" | "sql" | "haskell" => "--",
+        "latex" | "matlab" | "octave" => "%",
+        _ => "#",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use org_parser::parse_org;
+
+    fn tangle_str(input: &str) -> Vec<(String, String)> {
+        let parser = parse_org(input);
+        tangle(&parser, Some(Path::new("notes/doc.org")))
+            .unwrap()
+            .into_iter()
+            .map(|file| (file.path.display().to_string(), file.contents))
+            .collect()
+    }
+
+    #[test]
+    fn document_order() {
+        let input = r#"#+begin_src python :tangle out.py
+a = 1
+#+end_src
+#+begin_src sh :tangle other.sh
+echo
+#+end_src
+* Heading
+:PROPERTIES:
+:header-args: :tangle out.py
+:END:
+#+begin_src python :padline no
+b = 2
+#+end_src
+#+begin_src python
+c = 3
+#+end_src
+#+begin_src python :tangle no
+d = 4
+#+end_src
+#+begin_src rust :tangle yes
+fn main() {}
+#+end_src
+* COMMENT Skipped
+#+begin_src python :tangle out.py
+e = 5
+#+end_src
+"#;
+        assert_eq!(
+            tangle_str(input),
+            [
+                ("notes/out.py".into(), "a = 1\nb = 2\n\nc = 3\n".into()),
+                ("notes/other.sh".into(), "echo\n".into()),
+                ("notes/doc.rs".into(), "fn main() {}\n".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn shebang_and_mkdirp() {
+        let input = r##"#+begin_src sh :tangle "/tmp/bin/run it" :shebang "#!/bin/sh" :mkdirp yes
+echo one
+#+end_src
+#+begin_src sh :tangle "/tmp/bin/run it" :shebang "#!/bin/bash"
+echo two
+#+end_src
+"##;
+        let parser = parse_org(input);
+        assert_eq!(
+            tangle(&parser, None).unwrap(),
+            [TangledFile {
+                path: "/tmp/bin/run it".into(),
+                contents: "#!/bin/sh\necho one\n\necho two\n".into(),
+                mkdirp: true,
+                executable: true,
+                blocks: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn link_comments() {
+        let input = r"#+PROPERTY: header-args :comments link
+#+begin_src python :tangle ../out/a.py
+x = 1
+#+end_src
+* Setup
+#+begin_src rust :tangle a.rs
+let x = 1;
+#+end_src
+#+name: second
+#+begin_src rust :tangle a.rs
+let y = 2;
+#+end_src
+#+begin_src rust :tangle a.rs :comments no
+let z = 3;
+#+end_src
+";
+        assert_eq!(
+            tangle_str(input),
+            [
+                (
+                    "notes/../out/a.py".into(),
+                    r"# [[file:../notes/doc.org::][No heading:1]]
+x = 1
+# No heading:1 ends here
+"
+                    .into()
+                ),
+                (
+                    "notes/a.rs".into(),
+                    r"// [[file:doc.org::*Setup][Setup:1]]
+let x = 1;
+// Setup:1 ends here
+
+// [[file:doc.org::second][second]]
+let y = 2;
+// second ends here
+
+let z = 3;
+"
+                    .into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn tangle_yes_needs_file() {
+        let parser = parse_org("#+begin_src sh :tangle yes\necho\n#+end_src\n");
+        assert!(matches!(
+            tangle(&parser, None),
+            Err(TangleError::NoFileName)
+        ));
+    }
+
+    #[test]
+    fn noweb_tangle() {
+        let input = r"#+name: setup
+#+begin_src python
+import os
+#+end_src
+
+#+begin_src python :tangle main.py :noweb tangle
+<<setup>>
+print(os.name)
+#+end_src
+";
+        assert_eq!(
+            tangle_str(input),
+            [("notes/main.py".into(), "import os\nprint(os.name)\n".into())]
+        );
+
+        let parser = parse_org("#+begin_src sh :tangle a.sh :noweb yes\n<<nope>>\n#+end_src\n");
+        assert!(matches!(
+            tangle(&parser, None),
+            Err(TangleError::Noweb(NowebError::UndefinedBlock { .. }))
+        ));
+    }
+}
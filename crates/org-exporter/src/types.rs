@@ -1,14 +1,45 @@
 use core::fmt;
+use org_parser::element::DynamicBlock;
 use org_parser::{NodeID, Parser};
 use std::{ops::Range, path::PathBuf};
 use thiserror::Error;
 
+use crate::babel::BabelError;
+use crate::citation::{AuthorYear, CitationError, CitationProcessor, Citations, ProcessorFactory};
+use crate::dynamic_block::GeneratorRegistry;
+use crate::noweb::NowebError;
+use crate::runner::RunError;
+use crate::spreadsheet::FormulaError;
 use crate::{include::IncludeError, org_macros::MacroError};
 
 #[derive(Debug, Clone, Default)]
 pub struct ConfigOptions {
     /// Used for evaluating relative paths in #+include: statements
     file_path: Option<PathBuf>,
+    /// Creates the processor used to format citations, defaults to [`AuthorYear`]
+    citation_processor: Option<ProcessorFactory>,
+    /// Regenerates the contents of dynamic blocks by name
+    block_generators: GeneratorRegistry,
+    /// How headings past level six are rendered in HTML
+    deep_headings: DeepHeadings,
+    /// Whether [`Org`](crate::Org) recomputes tables from their `#+TBLFM:` formulas
+    recalculate_tables: bool,
+    /// Whether source blocks are run to refresh their results, see [`run_block`](crate::run_block)
+    evaluate_blocks: bool,
+    /// Whether [`Org`](crate::Org) writes the document back over itself: every heading,
+    /// keyword and block is kept as written, ignoring export filters and `:exports`,
+    /// and only the results of blocks that are run change
+    write_back: bool,
+}
+
+/// How [`Html`](crate::Html) renders headings deeper than `<h6>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeepHeadings {
+    /// `<h6 class="level-N">`
+    #[default]
+    Clamp,
+    /// A nested `<ul>` whose item holds the title and contents of the heading.
+    List,
 }
 
 #[derive(Debug, Error)]
@@ -26,6 +57,16 @@ pub enum LogicErrorKind {
     Include(#[from] IncludeError),
     #[error("{0}")]
     Macro(#[from] MacroError),
+    #[error("{0}")]
+    Citation(#[from] CitationError),
+    #[error("{0}")]
+    Babel(#[from] BabelError),
+    #[error("{0}")]
+    Formula(#[from] FormulaError),
+    #[error("{0}")]
+    Noweb(#[from] NowebError),
+    #[error("{0}")]
+    Run(#[from] RunError),
 }
 
 #[derive(Debug, Error)]
@@ -38,11 +79,65 @@ pub struct FileError {
 
 impl ConfigOptions {
     pub fn new(file_path: Option<PathBuf>) -> Self {
-        Self { file_path }
+        Self {
+            file_path,
+            citation_processor: None,
+            block_generators: GeneratorRegistry::default(),
+            deep_headings: DeepHeadings::default(),
+            recalculate_tables: false,
+            evaluate_blocks: false,
+            write_back: false,
+        }
+    }
+    pub fn with_citation_processor(mut self, factory: ProcessorFactory) -> Self {
+        self.citation_processor = Some(factory);
+        self
+    }
+    pub fn with_block_generator(
+        mut self,
+        name: &str,
+        generator: impl Fn(&DynamicBlock, NodeID, &Parser) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.block_generators.register(name, generator);
+        self
+    }
+    pub fn with_deep_headings(mut self, deep_headings: DeepHeadings) -> Self {
+        self.deep_headings = deep_headings;
+        self
+    }
+    pub fn with_table_recalculation(mut self, recalculate: bool) -> Self {
+        self.recalculate_tables = recalculate;
+        self
+    }
+    pub fn with_block_evaluation(mut self, evaluate: bool) -> Self {
+        self.evaluate_blocks = evaluate;
+        self
+    }
+    pub fn with_write_back(mut self, write_back: bool) -> Self {
+        self.write_back = write_back;
+        self
     }
     pub fn file_path(&self) -> &Option<PathBuf> {
         &self.file_path
     }
+    pub fn citation_processor(&self) -> fn() -> Box<dyn CitationProcessor> {
+        self.citation_processor.unwrap_or(AuthorYear::boxed)
+    }
+    pub fn block_generators(&self) -> &GeneratorRegistry {
+        &self.block_generators
+    }
+    pub fn deep_headings(&self) -> DeepHeadings {
+        self.deep_headings
+    }
+    pub fn recalculate_tables(&self) -> bool {
+        self.recalculate_tables
+    }
+    pub fn evaluate_blocks(&self) -> bool {
+        self.evaluate_blocks
+    }
+    pub fn write_back(&self) -> bool {
+        self.write_back
+    }
 }
 
 /// Trait for exporter implementations
@@ -87,4 +182,6 @@ pub(crate) trait ExporterInner<'buf> {
     fn backend_name() -> &'static str;
     fn config_opts(&self) -> &ConfigOptions;
     fn errors(&mut self) -> &mut Vec<ExportError>;
+    /// The citations of the document being exported.
+    fn citations(&mut self) -> &mut Citations;
 }
@@ -1,20 +1,96 @@
+use std::borrow::Cow;
 use std::error::Error;
+use std::path::{Component, Path, PathBuf};
 use std::rc::Rc;
 
 use org_parser::{NodeID, Parser, element::Heading};
 
+use crate::filter::{ExportFilter, Visibility};
+
 pub(crate) fn keyword_lookup<'a>(parser: &'a Parser, name: &'a str) -> Option<&'a str> {
     parser.keywords.get(name).copied()
 }
 
-#[derive(Default, Debug, Clone, Copy)]
+/// Resolves `path` relative to the directory of the org file at `file_path`.
+///
+/// Absolute paths, and every path when there's no org file, are returned as
+/// is. Nothing is looked up on disk, so the path doesn't have to exist yet.
+pub fn resolve_path<'a>(file_path: Option<&Path>, path: &'a Path) -> Cow<'a, Path> {
+    match file_path.and_then(Path::parent) {
+        Some(dir) if path.is_relative() => dir.join(path).into(),
+        _ => path.into(),
+    }
+}
+
+/// The path of `target` as seen from the directory `dir`.
+pub(crate) fn relative_path(target: &Path, dir: &Path) -> PathBuf {
+    let target_dir = target.parent().unwrap_or(Path::new(""));
+    // lexically, since the paths might not exist yet
+    fn normal(path: &Path) -> Vec<Component<'_>> {
+        let mut ret = Vec::new();
+        for comp in path.components() {
+            match comp {
+                Component::CurDir => {}
+                Component::ParentDir if matches!(ret.last(), Some(Component::Normal(_))) => {
+                    ret.pop();
+                }
+                _ => ret.push(comp),
+            }
+        }
+        ret
+    }
+    let (target_comps, dir_comps) = (normal(target_dir), normal(dir));
+    let common = target_comps
+        .iter()
+        .zip(&dir_comps)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ret = PathBuf::new();
+    for _ in common..dir_comps.len() {
+        ret.push("..");
+    }
+    for comp in &target_comps[common..] {
+        ret.push(comp);
+    }
+    if let Some(name) = target.file_name() {
+        ret.push(name);
+    }
+    ret
+}
+
+/// Strips the quotes around a header argument value: `:tangle "a file.sh"`.
+pub(crate) fn unquote(val: &str) -> &str {
+    val.strip_prefix('"')
+        .and_then(|val| val.strip_suffix('"'))
+        .unwrap_or(val)
+}
+
+#[derive(Debug, Clone, Copy)]
 pub(crate) struct Options {
     toc: Option<u8>,
+    /// `p:` whether to export planning info (SCHEDULED/DEADLINE/CLOSED)
+    pub(crate) planning: bool,
+    /// `c:` whether to export CLOCK lines
+    pub(crate) clocks: bool,
+    /// `todo:` whether to export the TODO keywords of headings
+    pub(crate) todo: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new(None, false, false, true)
+    }
 }
 
 impl Options {
-    pub(crate) fn new(toc: Option<u8>) -> Self {
-        Self { toc }
+    pub(crate) fn new(toc: Option<u8>, planning: bool, clocks: bool, todo: bool) -> Self {
+        Self {
+            toc,
+            planning,
+            clocks,
+            todo,
+        }
     }
 
     pub(crate) fn handle_opts(parser: &Parser) -> Result<Options, Box<dyn Error>> {
@@ -22,21 +98,35 @@ impl Options {
         if let Some(options) = keyword_lookup(parser, "options") {
             let ret = options.split_ascii_whitespace();
             let mut toc = None;
+            let mut planning = false;
+            let mut clocks = false;
+            let mut todo = true;
             for optpair in ret {
-                if let Some(("toc", val)) = optpair.split_once(':') {
-                    {
+                match optpair.split_once(':') {
+                    Some(("toc", val)) => {
                         toc = if val == "nil" {
                             None
                         } else if let Ok(num) = val.parse::<u8>() {
                             Some(num)
                         } else {
-                            Some(6)
+                            // every level
+                            Some(u8::MAX)
                         };
                     }
+                    Some(("p", val)) => {
+                        planning = val != "nil";
+                    }
+                    Some(("c", val)) => {
+                        clocks = val != "nil";
+                    }
+                    Some(("todo", val)) => {
+                        todo = val != "nil";
+                    }
+                    _ => {}
                 }
             }
 
-            Ok(Options::new(toc))
+            Ok(Options::new(toc, planning, clocks, todo))
         } else {
             Ok(Options::default())
         }
@@ -54,6 +144,7 @@ pub struct TocItem<'a> {
 pub(crate) fn process_toc<'a>(
     parser: &'a Parser,
     opts: &Options,
+    filter: &ExportFilter,
 ) -> Result<Vec<TocItem<'a>>, Box<dyn Error>> {
     let mut tocs: Vec<TocItem> = Vec::new();
 
@@ -63,8 +154,10 @@ pub(crate) fn process_toc<'a>(
 
     for sub_id in parser.pool[parser.pool.root_id()].obj.children().unwrap() {
         let node = &parser.pool[*sub_id];
+        let visibility = filter.visibility(parser, *sub_id);
         if let org_parser::Expr::Heading(heading) = &node.obj
             && global_toc_level >= heading.heading_level.into()
+            && visibility != Visibility::Hidden
         {
             if let Some(properties) = &heading.properties
                 && let Some(val) = properties.get("unnumbered")
@@ -77,6 +170,8 @@ pub(crate) fn process_toc<'a>(
                 heading,
                 node.id_target.clone(),
                 global_toc_level,
+                filter,
+                visibility,
             ));
         }
     }
@@ -104,13 +199,19 @@ fn handle_babies<'a>(
     heading: &'a Heading,
     target: Option<Rc<str>>,
     global_toc_level: u8,
+    filter: &ExportFilter,
+    visibility: Visibility,
 ) -> TocItem<'a> {
     let mut children_vec = Vec::new();
-    if let Some(childs) = &heading.children {
+    if visibility == Visibility::Full
+        && let Some(childs) = &heading.children
+    {
         for child in childs {
             let node = &p.pool[*child];
+            let visibility = filter.visibility(p, *child);
             if let org_parser::Expr::Heading(heading) = &node.obj
                 && global_toc_level >= heading.heading_level.into()
+                && visibility != Visibility::Hidden
             {
                 if let Some(properties) = &heading.properties
                     && let Some(val) = properties.get("unnumbered")
@@ -123,6 +224,8 @@ fn handle_babies<'a>(
                     heading,
                     node.id_target.clone(),
                     global_toc_level,
+                    filter,
+                    visibility,
                 ));
             }
         }
@@ -181,4 +284,30 @@ ul {
         println!("{a}");
         Ok(())
     }
+
+    #[test]
+    fn paths() {
+        let org = Path::new("notes/doc.org");
+        assert_eq!(
+            resolve_path(Some(org), Path::new("src/a.sh")),
+            Path::new("notes/src/a.sh")
+        );
+        assert_eq!(
+            resolve_path(Some(org), Path::new("/tmp/a.sh")),
+            Path::new("/tmp/a.sh")
+        );
+        assert_eq!(
+            resolve_path(None, Path::new("src/a.sh")),
+            Path::new("src/a.sh")
+        );
+
+        assert_eq!(
+            relative_path(org, Path::new("notes/src")),
+            Path::new("../doc.org")
+        );
+        assert_eq!(
+            relative_path(org, Path::new("./notes/../notes")),
+            Path::new("doc.org")
+        );
+    }
 }
@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+
+use crate::constants::{COLON, LBRACK, LPAREN, NEWLINE, RBRACK, RPAREN};
+use crate::element::{Affiliated, Block};
+use crate::node_pool::NodeID;
+use crate::types::{Cursor, Expr, MatchError, ParseOpts, Parseable, Parser, Result};
+use crate::utils::Match;
+
+/// A call to a named source block.
+///
+/// ```text
+/// #+call: name[:inside header](arg=value, arg2=value) :end header
+/// ```
+///
+/// The call is resolved against the `#+name:`d block of the same name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BabelCall<'a> {
+    pub name: &'a str,
+    /// Header arguments applied to the called block.
+    pub inside_header: Vec<(&'a str, &'a str)>,
+    pub arguments: Vec<CallArgument<'a>>,
+    /// Header arguments applied to the results of the call.
+    pub end_header: Vec<(&'a str, &'a str)>,
+}
+
+/// An entry in the argument list of a [`BabelCall`] or
+/// [`InlineBabelCall`](crate::object::InlineBabelCall).
+///
+/// `name=value`, or just `value` to assign to the block's variables in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallArgument<'a> {
+    pub name: Option<&'a str>,
+    pub value: &'a str,
+}
+
+impl<'a> Parseable<'a> for BabelCall<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        cursor.word("#+")?;
+        let key = cursor.fn_until(|chr: u8| chr == COLON || chr.is_ascii_whitespace())?;
+        if !key.obj.eq_ignore_ascii_case("call") {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.index = key.end;
+        cursor.word(":")?;
+        cursor.skip_ws();
+
+        let name = parse_call_name(cursor)?;
+        cursor.index = name.end;
+
+        let inside_header = if cursor.try_curr()? == LBRACK {
+            let header = parse_delimited(cursor, LBRACK, RBRACK)?;
+            cursor.index = header.end;
+            header_arg_pairs(header.obj)
+        } else {
+            Vec::new()
+        };
+
+        let arguments = if cursor.try_curr()? == LPAREN {
+            let args = parse_delimited(cursor, LPAREN, RPAREN)?;
+            cursor.index = args.end;
+            parse_arguments(args.obj)
+        } else {
+            Vec::new()
+        };
+
+        let end_header = cursor.fn_until(|chr: u8| chr == NEWLINE)?;
+        if !end_header.obj.trim().is_empty() && !end_header.obj.trim_start().starts_with(':') {
+            return Err(MatchError::InvalidLogic);
+        }
+
+        Ok(parser.alloc(
+            Self {
+                name: name.obj,
+                inside_header,
+                arguments,
+                end_header: header_arg_pairs(end_header.obj),
+            },
+            start,
+            end_header.end + 1,
+            parent,
+        ))
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// The element holding the `#+RESULTS:` of a source block or babel call.
+    ///
+    /// Results named after the node (`#+RESULTS: name`) are preferred,
+    /// otherwise they must directly follow the node.
+    pub fn results(&self, node_id: NodeID) -> Option<NodeID> {
+        match &self.pool[self.results_keyword(node_id)?].obj {
+            Expr::Affiliated(Affiliated::Results { child_id, .. }) => *child_id,
+            _ => None,
+        }
+    }
+
+    /// The `#+RESULTS:` keyword of a source block or babel call, see [`Parser::results`].
+    pub fn results_keyword(&self, node_id: NodeID) -> Option<NodeID> {
+        self.block_results.get(&node_id).copied().filter(|id| {
+            matches!(
+                self.pool[*id].obj,
+                Expr::Affiliated(Affiliated::Results { .. })
+            )
+        })
+    }
+
+    /// The source block, babel call or inline source block that the `#+RESULTS:`
+    /// keyword or `{{{results(...)}}}` macro at `results_id` belongs to.
+    pub fn results_owner(&self, results_id: NodeID) -> Option<NodeID> {
+        self.block_results
+            .iter()
+            .find_map(|(owner, id)| (*id == results_id).then_some(*owner))
+    }
+}
+
+/// Pairs source blocks, babel calls and inline source blocks with their results,
+/// see [`Parser::block_results`].
+pub(crate) fn link_results(parser: &mut Parser) {
+    let mut named = HashMap::new();
+    let mut owners = Vec::new();
+    let mut stack = vec![parser.pool.root_id()];
+    // depth first, in document order
+    while let Some(id) = stack.pop() {
+        let obj = &parser.pool[id].obj;
+        match obj {
+            Expr::Affiliated(Affiliated::Results {
+                name: Some(name), ..
+            }) => {
+                named.entry(*name).or_insert(id);
+            }
+            Expr::Block(Block::Src { .. }) | Expr::BabelCall(_) | Expr::InlineSrc(_) => {
+                owners.push(id);
+            }
+            _ => {}
+        }
+        if let Some(children) = obj.children() {
+            stack.extend(children.iter().rev());
+        }
+    }
+
+    for owner in owners {
+        let name = parser
+            .names
+            .iter()
+            .find_map(|(name, id)| (*id == owner).then_some(*name));
+        let results = name
+            .and_then(|name| named.get(name).copied())
+            .or_else(|| adjacent_results(parser, owner));
+        if let Some(results) = results {
+            parser.block_results.insert(owner, results);
+        }
+    }
+}
+
+/// Unnamed results: the `#+RESULTS:` keyword after a block or call, or the
+/// `{{{results(...)}}}` macro after an inline source block.
+fn adjacent_results(parser: &Parser, node_id: NodeID) -> Option<NodeID> {
+    let parent = parser.pool[node_id].parent?;
+    let siblings = parser.pool[parent].obj.children()?;
+    let pos = siblings.iter().position(|id| *id == node_id)?;
+    let mut following = siblings[pos + 1..]
+        .iter()
+        .map(|id| (*id, &parser.pool[*id].obj));
+
+    if let Expr::InlineSrc(_) = parser.pool[node_id].obj {
+        following
+            .find(|(_, obj)| !matches!(obj, Expr::Plain(text) if text.trim().is_empty()))
+            .filter(|(_, obj)| matches!(obj, Expr::Macro(call) if call.name == "results"))
+            .map(|(id, _)| id)
+    } else {
+        following
+            .find(|(_, obj)| !matches!(obj, Expr::BlankLine))
+            .filter(|(_, obj)| matches!(obj, Expr::Affiliated(Affiliated::Results { .. })))
+            .map(|(id, _)| id)
+    }
+}
+
+/// The name of the called block, which ends at a header, argument list or whitespace.
+pub(crate) fn parse_call_name(cursor: Cursor<'_>) -> Result<Match<&str>> {
+    let name = cursor.fn_until(|chr: u8| {
+        chr == LBRACK
+            || chr == LPAREN
+            || chr == RBRACK
+            || chr == RPAREN
+            || chr.is_ascii_whitespace()
+    })?;
+    if name.obj.is_empty() {
+        Err(MatchError::InvalidLogic)
+    } else {
+        Ok(name)
+    }
+}
+
+/// Contents of a balanced `[...]` or `(...)` group on a single line,
+/// the cursor must be on the opening delimiter.
+pub(crate) fn parse_delimited(mut cursor: Cursor<'_>, open: u8, close: u8) -> Result<Match<&str>> {
+    cursor.word(&(open as char).to_string())?;
+    let start = cursor.index;
+    let mut depth = 0;
+    loop {
+        match cursor.try_curr()? {
+            NEWLINE => return Err(MatchError::InvalidLogic),
+            chr if chr == open => depth += 1,
+            chr if chr == close => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+        cursor.next();
+    }
+
+    Ok(Match {
+        start: start - 1,
+        end: cursor.index + 1,
+        obj: cursor.clamp_backwards(start),
+    })
+}
+
+/// `:key val :key2 val2` in order, keeping repeated keys.
+pub(crate) fn header_arg_pairs(input: &str) -> Vec<(&str, &str)> {
+    let mut ret = Vec::new();
+    // a colon only starts a key at the beginning or after whitespace
+    let key_starts: Vec<usize> = input
+        .char_indices()
+        .filter(|&(i, chr)| {
+            chr == ':'
+                && input[..i]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|prev| prev.is_ascii_whitespace())
+        })
+        .map(|(i, _)| i)
+        .collect();
+
+    for (n, &key_start) in key_starts.iter().enumerate() {
+        let pair_end = key_starts.get(n + 1).copied().unwrap_or(input.len());
+        let pair = &input[key_start + 1..pair_end];
+        let (key, val) = pair.split_once(char::is_whitespace).unwrap_or((pair, ""));
+        if !key.is_empty() {
+            ret.push((key, val.trim()));
+        }
+    }
+    ret
+}
+
+/// `name=value, value2` separated by commas outside of quotes and parentheses.
+pub(crate) fn parse_arguments(input: &str) -> Vec<CallArgument<'_>> {
+    let mut ret = Vec::new();
+    let mut depth = 0;
+    let mut in_quote = false;
+    let mut arg_start = 0;
+
+    for (i, chr) in input.char_indices() {
+        match chr {
+            '"' => in_quote = !in_quote,
+            '(' if !in_quote => depth += 1,
+            ')' if !in_quote => depth -= 1,
+            ',' if !in_quote && depth == 0 => {
+                ret.extend(parse_argument(&input[arg_start..i]));
+                arg_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    ret.extend(parse_argument(&input[arg_start..]));
+
+    ret
+}
+
+fn parse_argument(arg: &str) -> Option<CallArgument<'_>> {
+    let arg = arg.trim();
+    if arg.is_empty() {
+        return None;
+    }
+
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|chr| chr.is_alphanumeric() || chr == '_' || chr == '-')
+    };
+    Some(match arg.split_once('=') {
+        Some((name, value)) if is_name(name.trim()) => CallArgument {
+            name: Some(name.trim()),
+            value: value.trim(),
+        },
+        _ => CallArgument {
+            name: None,
+            value: arg,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{expr_in_pool, parse_org};
+
+    #[test]
+    fn basic_call() {
+        let input = "#+call: double(n=4)\n";
+        let parsed = parse_org(input);
+        let call = expr_in_pool!(parsed, BabelCall).unwrap();
+
+        assert_eq!(
+            call,
+            &BabelCall {
+                name: "double",
+                inside_header: Vec::new(),
+                arguments: vec![CallArgument {
+                    name: Some("n"),
+                    value: "4"
+                }],
+                end_header: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn full_call() {
+        let input =
+            "#+CALL: fetch[:session s :dir /tmp](url=\"a,b\", f(1, 2)) :results raw :wrap\n";
+        let parsed = parse_org(input);
+        let call = expr_in_pool!(parsed, BabelCall).unwrap();
+
+        assert_eq!(call.name, "fetch");
+        assert_eq!(call.inside_header, [("session", "s"), ("dir", "/tmp")]);
+        assert_eq!(
+            call.arguments,
+            [
+                CallArgument {
+                    name: Some("url"),
+                    value: "\"a,b\""
+                },
+                CallArgument {
+                    name: None,
+                    value: "f(1, 2)"
+                }
+            ]
+        );
+        assert_eq!(call.end_header, [("results", "raw"), ("wrap", "")]);
+    }
+
+    #[test]
+    fn call_without_arguments() {
+        let parsed = parse_org("#+call: name\n");
+        let call = expr_in_pool!(parsed, BabelCall).unwrap();
+        assert_eq!(call.name, "name");
+        assert!(call.arguments.is_empty());
+    }
+
+    #[test]
+    fn block_results() {
+        let input = r"#+name: answer
+#+begin_src python
+return 42
+#+end_src
+
+#+RESULTS:
+: 42
+
+#+name: other
+#+begin_src python
+return 1
+#+end_src
+
+#+RESULTS: other
+: 1
+
+#+name: nothing
+#+begin_src python
+#+end_src
+";
+        let parsed = parse_org(input);
+        let results_text = |name: &str| {
+            parsed
+                .results(parsed.names[name])
+                .map(|id| &input[parsed.pool[id].start..parsed.pool[id].end])
+        };
+
+        assert_eq!(results_text("answer"), Some(": 42\n"));
+        assert_eq!(results_text("other"), Some(": 1\n"));
+        assert_eq!(results_text("nothing"), None);
+    }
+
+    #[test]
+    fn results_owners() {
+        let input = r"#+RESULTS: later
+: 2
+
+#+begin_src sh
+echo 1
+#+end_src
+
+#+RESULTS:
+: 1
+
+#+name: later
+#+begin_src sh
+echo 2
+#+end_src
+
+#+RESULTS:
+: unrelated
+
+src_sh{echo 3} {{{results(=3=)}}} and src_sh{echo 4} {{{keyword(title)}}}
+";
+        let parsed = parse_org(input);
+        let mut pairs: Vec<_> = parsed
+            .block_results
+            .iter()
+            .map(|(owner, results)| {
+                let text = |id: &NodeID| &input[parsed.pool[*id].start..parsed.pool[*id].end];
+                (
+                    text(owner).lines().nth(1).unwrap_or(text(owner)),
+                    text(results),
+                )
+            })
+            .collect();
+        pairs.sort();
+
+        assert_eq!(
+            pairs,
+            [
+                ("echo 1", "#+RESULTS:\n"),
+                ("echo 2", "#+RESULTS: later\n"),
+                ("src_sh{echo 3}", "{{{results(=3=)}}}"),
+            ]
+        );
+        for (owner, results) in &parsed.block_results {
+            assert_eq!(parsed.results_owner(*results), Some(*owner));
+        }
+    }
+
+    #[test]
+    fn invalid_calls() {
+        for input in [
+            "#+call:\n",
+            "#+call: name(unclosed\n",
+            "#+call: name() junk\n",
+        ] {
+            let parsed = parse_org(input);
+            assert!(expr_in_pool!(parsed, BabelCall).is_none(), "{input}");
+        }
+    }
+}
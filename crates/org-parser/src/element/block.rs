@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::constants::NEWLINE;
 use crate::node_pool::NodeID;
 use crate::parse::parse_element;
 use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result, process_attrs};
@@ -42,6 +43,7 @@ pub enum Block<'a> {
     },
     Example {
         parameters: HashMap<&'a str, &'a str>,
+        switches: Switches<'a>,
         contents: &'a str,
     },
     Export {
@@ -52,6 +54,7 @@ pub enum Block<'a> {
     Src {
         language: Option<&'a str>,
         parameters: HashMap<&'a str, &'a str>,
+        switches: Switches<'a>,
         contents: &'a str,
     },
     Verse {
@@ -98,6 +101,7 @@ impl<'a> Parseable<'a> for Block<'a> {
                 } else {
                     language = Some(trimmed);
                 }
+                cursor.index = lang_match.end;
                 cursor.skip_ws();
             }
             BlockKind::Export => {
@@ -113,7 +117,15 @@ impl<'a> Parseable<'a> for Block<'a> {
             }
             _ => (),
         }
-        // TODO: src switches
+
+        let mut switches = Switches::default();
+        if matches!(block_kind, BlockKind::Src | BlockKind::Example) {
+            let mut line_end = cursor;
+            line_end.adv_till_byte(NEWLINE);
+            switches = Switches::parse(line_end.clamp_backwards(cursor.index));
+            cursor.index += switches.raw.len();
+        }
+
         let (mut cursor, parameters) = process_attrs(cursor)?;
         // skip newline
         cursor.next();
@@ -155,7 +167,7 @@ impl<'a> Parseable<'a> for Block<'a> {
 
         if block_kind.is_lesser() {
             let contents = cursor.clamp_forwards(loc);
-            Ok(parser.alloc(
+            let block_id = parser.alloc(
                 match block_kind {
                     BlockKind::Center | BlockKind::Quote | BlockKind::Special(_) => unreachable!(),
                     BlockKind::Comment => Block::Comment {
@@ -164,6 +176,7 @@ impl<'a> Parseable<'a> for Block<'a> {
                     },
                     BlockKind::Example => Block::Example {
                         parameters,
+                        switches,
                         contents,
                     },
                     BlockKind::Export => Block::Export {
@@ -174,6 +187,7 @@ impl<'a> Parseable<'a> for Block<'a> {
                     BlockKind::Src => Block::Src {
                         language,
                         parameters,
+                        switches,
                         contents,
                     },
                     BlockKind::Verse => Block::Verse {
@@ -184,7 +198,22 @@ impl<'a> Parseable<'a> for Block<'a> {
                 start,
                 end,
                 parent,
-            ))
+            );
+
+            if matches!(block_kind, BlockKind::Src | BlockKind::Example) {
+                for (i, line) in contents.lines().enumerate() {
+                    if let Some((_, label)) = switches.coderef(line) {
+                        parser.coderefs.insert(
+                            label,
+                            Coderef {
+                                block: block_id,
+                                line: i + 1,
+                            },
+                        );
+                    }
+                }
+            }
+            Ok(block_id)
         } else {
             let mut contents: Vec<NodeID> = Vec::new();
             let reserve_id = parser.pool.reserve_id();
@@ -229,6 +258,103 @@ impl<'a> Parseable<'a> for Block<'a> {
     }
 }
 
+/// The switches of a src or example block, written before its parameters.
+///
+/// ```text
+/// #+begin_src rust -n -r -l "[%s]" :tangle yes
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Switches<'a> {
+    /// The switches as written.
+    pub raw: &'a str,
+    /// `-r`: remove coderef labels from the code, links to them show line numbers.
+    pub remove_labels: bool,
+    /// `-k`: keep coderef labels in the code, even when `-r` is set.
+    pub keep_labels: bool,
+    /// `-l "fmt"`: the format of coderef labels, `%s` standing for the label.
+    pub label_format: Option<&'a str>,
+}
+
+/// Where a coderef label is defined: `(ref:label)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coderef {
+    /// The src or example block holding the label.
+    pub block: NodeID,
+    /// The line of the block it's on, counting from one.
+    pub line: usize,
+}
+
+impl<'a> Switches<'a> {
+    const DEFAULT_LABEL_FORMAT: &'static str = "(ref:%s)";
+
+    // -n -r -l "(%s)" :params
+    fn parse(line: &'a str) -> Self {
+        let mut ret = Self::default();
+        let mut index = 0;
+        loop {
+            let rest = &line[index..];
+            let token_start = index + rest.len() - rest.trim_start().len();
+            let token = &line[token_start..];
+            if !token.starts_with(['-', '+']) {
+                break;
+            }
+            let token = &token[..token.find(char::is_whitespace).unwrap_or(token.len())];
+            index = token_start + token.len();
+
+            let rest = &line[index..];
+            let arg = rest.trim_start();
+            match token {
+                "-r" => ret.remove_labels = true,
+                "-k" => ret.keep_labels = true,
+                "-l" => {
+                    if let Some(format) = arg.strip_prefix('"')
+                        && let Some(close) = format.find('"')
+                    {
+                        ret.label_format = Some(&format[..close]);
+                        index += rest.len() - arg.len() + close + 2;
+                    }
+                }
+                // line numbers, optionally starting from a given number
+                "-n" | "+n" => {
+                    let digits = arg
+                        .find(|chr: char| !chr.is_ascii_digit())
+                        .unwrap_or(arg.len());
+                    if digits > 0 {
+                        index += rest.len() - arg.len() + digits;
+                    }
+                }
+                _ => {}
+            }
+        }
+        ret.raw = &line[..index];
+        ret
+    }
+
+    /// Finds the coderef label at the end of a line of code.
+    ///
+    /// Returns the label along with the index the marker starts at.
+    pub fn coderef<'b>(&self, line: &'b str) -> Option<(usize, &'b str)> {
+        let (prefix, suffix) = self
+            .label_format
+            .unwrap_or(Self::DEFAULT_LABEL_FORMAT)
+            .split_once("%s")?;
+        let line = line.trim_end();
+        let start = line.strip_suffix(suffix)?.rfind(prefix)?;
+        let label = &line[start + prefix.len()..line.len() - suffix.len()];
+
+        let valid = !label.is_empty()
+            && label
+                .chars()
+                .all(|chr| chr.is_alphanumeric() || matches!(chr, '-' | '_' | ' '));
+        valid.then_some((start, label))
+    }
+
+    /// Whether coderef labels are left out of the exported code.
+    pub fn strip_labels(&self) -> bool {
+        self.remove_labels && !self.keep_labels
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum BlockKind<'a> {
     // Greater
@@ -305,7 +431,7 @@ impl<'a> From<BlockKind<'a>> for &'a str {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::element::Block;
+    use crate::element::{Block, Coderef, Switches};
     use crate::types::Expr;
     use crate::{expr_in_pool, parse_org};
 
@@ -355,6 +481,7 @@ mod tests {
             &Block::Src {
                 language: Some("python"),
                 parameters: HashMap::new(),
+                switches: Switches::default(),
                 contents: ""
             }
         )
@@ -371,6 +498,7 @@ mod tests {
             l,
             &Block::Example {
                 parameters: HashMap::from([("gotta", ""), ("love", ""), ("examples", "")]),
+                switches: Switches::default(),
                 contents: ""
             }
         )
@@ -387,6 +515,7 @@ mod tests {
             l,
             &Block::Example {
                 parameters: HashMap::new(),
+                switches: Switches::default(),
                 contents: "smallexp
 "
             }
@@ -412,6 +541,7 @@ one two three
             l,
             &Block::Example {
                 parameters: HashMap::new(),
+                switches: Switches::default(),
                 contents: r"this is a larger example gotta love examples
 to demonstrate that it works
 string substring
@@ -477,6 +607,7 @@ here is after
             &Block::Src {
                 language: Some("python"),
                 parameters: HashMap::new(),
+                switches: Switches::default(),
                 contents: r"
 here is some text
 "
@@ -484,6 +615,72 @@ here is some text
         )
     }
 
+    #[test]
+    fn block_switches() {
+        let input = r#"#+begin_src rust -n 10 -r -l "[%s]" -k :tangle yes
+let x = 1; [first]
+let y = 2; (ref:plain)
+#+end_src
+
+#+begin_example -r
+a (ref:ex-2 b)
+#+end_example
+"#;
+
+        let parsed = parse_org(input);
+        let root = parsed.pool[parsed.pool.root_id()].obj.children().unwrap();
+        assert_eq!(
+            expr_in_pool!(parsed, Block).unwrap(),
+            &Block::Src {
+                language: Some("rust"),
+                parameters: HashMap::from([("tangle", "yes")]),
+                switches: Switches {
+                    raw: r#"-n 10 -r -l "[%s]" -k"#,
+                    remove_labels: true,
+                    keep_labels: true,
+                    label_format: Some("[%s]"),
+                },
+                contents: "let x = 1; [first]\nlet y = 2; (ref:plain)\n",
+            }
+        );
+
+        assert_eq!(
+            parsed.coderefs,
+            HashMap::from([
+                (
+                    "first",
+                    Coderef {
+                        block: root[0],
+                        line: 1
+                    }
+                ),
+                (
+                    "ex-2 b",
+                    Coderef {
+                        block: root[3],
+                        line: 1
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn coderef_labels() {
+        let switches = Switches::default();
+        assert_eq!(switches.coderef("x (ref:a)  "), Some((2, "a")));
+        assert_eq!(switches.coderef("(ref:a) x"), None);
+        assert_eq!(switches.coderef("x (ref:)"), None);
+        assert_eq!(switches.coderef("x (ref:a.b)"), None);
+
+        let switches = Switches {
+            label_format: Some("<<%s>>"),
+            ..Default::default()
+        };
+        assert_eq!(switches.coderef("x <<one>>"), Some((2, "one")));
+        assert_eq!(switches.coderef("x (ref:one)"), None);
+    }
+
     #[test]
     fn lesser_block_indented() {
         let input = r"
@@ -499,6 +696,7 @@ here is some text
             l,
             &Block::Example {
                 parameters: HashMap::new(),
+                switches: Switches::default(),
                 contents: r"             we are eating so good?
 "
             }
@@ -553,6 +751,7 @@ text
             &Block::Src {
                 language: None,
                 parameters: HashMap::new(),
+                switches: Switches::default(),
                 contents: r"
 
 hiiiiiiiiiiiiiiiiiii
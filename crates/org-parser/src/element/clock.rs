@@ -0,0 +1,259 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::constants::NEWLINE;
+use crate::node_pool::NodeID;
+use crate::object::{DateTime, TimestampEnd, parse_timestamp};
+use crate::types::{Cursor, Expr, MatchError, ParseOpts, Parseable, Parser, Result};
+
+/// A clock line, recording time spent on a task.
+///
+/// ```text
+/// CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 10:30] =>  1:30
+/// CLOCK: [2024-03-01 Fri 11:00]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clock<'a> {
+    pub start: DateTime<'a>,
+    /// `None` while the clock is still running.
+    pub end: Option<DateTime<'a>>,
+    /// The `=> H:MM` cookie, if present.
+    pub duration: Option<Duration>,
+}
+
+impl<'a> Parseable<'a> for Clock<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        cursor.word("CLOCK:")?;
+        cursor.skip_ws();
+
+        let ts_match = parse_timestamp(cursor)?;
+        let ts = ts_match.obj;
+        if ts.active || ts.repeater.is_some() || ts.warning.is_some() {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.index = ts_match.end;
+
+        let end = match ts.end {
+            Some(TimestampEnd::Date { end, .. }) => Some(end),
+            Some(TimestampEnd::Time(time)) => Some(DateTime {
+                date: ts.start.date,
+                time: Some(time),
+            }),
+            None => None,
+        };
+
+        cursor.skip_ws();
+        let duration = if cursor.word("=>").is_ok() {
+            cursor.skip_ws();
+            // the cookie can end the file, so running out of input ends the number
+            let digits = |cursor: &mut Cursor<'a>| {
+                let start = cursor.index;
+                while cursor.try_curr().is_ok_and(|chr| chr.is_ascii_digit()) {
+                    cursor.next();
+                }
+                cursor.clamp_backwards(start)
+            };
+            let hours = digits(&mut cursor);
+            cursor.word(":")?;
+            let minutes = digits(&mut cursor);
+
+            let hours: u64 = hours.parse().or(Err(MatchError::InvalidLogic))?;
+            let minutes: u64 = minutes.parse().or(Err(MatchError::InvalidLogic))?;
+            cursor.skip_ws();
+            Some(Duration::from_secs((hours * 60 + minutes) * 60))
+        } else {
+            None
+        };
+
+        // clock lines can't contain anything else
+        match cursor.try_curr() {
+            Ok(NEWLINE) => cursor.next(),
+            Ok(_) => return Err(MatchError::InvalidLogic),
+            Err(_) => {}
+        }
+
+        Ok(parser.alloc(
+            Self {
+                start: ts.start,
+                end,
+                duration,
+            },
+            start,
+            cursor.index,
+            parent,
+        ))
+    }
+}
+
+impl Clock<'_> {
+    /// Time elapsed between the start and end of the clock.
+    ///
+    /// Falls back to the `=>` cookie if the timestamps are unusable.
+    /// Running clocks have no elapsed time.
+    pub fn elapsed(&self) -> Option<Duration> {
+        if let Some(end) = &self.end
+            && let Ok(minutes) =
+                u64::try_from(end.minutes_since_epoch() - self.start.minutes_since_epoch())
+        {
+            Some(Duration::from_secs(minutes * 60))
+        } else {
+            self.duration
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Sums the time clocked under a node, including time clocked in
+    /// any child headings.
+    ///
+    /// Running clocks do not contribute to the total.
+    pub fn clocked_time(&self, node_id: NodeID) -> Duration {
+        let node = &self.pool[node_id];
+        let mut total = if let Expr::Clock(clock) = &node.obj {
+            clock.elapsed().unwrap_or_default()
+        } else {
+            Duration::ZERO
+        };
+
+        if let Some(children) = node.obj.children() {
+            for child in children {
+                total += self.clocked_time(*child);
+            }
+        }
+        total
+    }
+}
+
+impl Display for Clock<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CLOCK: [{}]", self.start)?;
+        if let Some(end) = &self.end {
+            write!(f, "--[{end}]")?;
+        }
+        if let Some(duration) = self.duration {
+            let minutes = duration.as_secs() / 60;
+            write!(f, " => {:>2}:{:02}", minutes / 60, minutes % 60)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::element::Clock;
+    use crate::object::{Date, DateTime, Time};
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
+
+    #[test]
+    fn basic_clock() {
+        let input = r"CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 10:30] =>  1:30
+";
+        let parsed = parse_org(input);
+        let clock = expr_in_pool!(parsed, Clock).unwrap();
+        assert_eq!(
+            clock,
+            &Clock {
+                start: DateTime {
+                    date: Date {
+                        year: 2024,
+                        month: 3,
+                        day: 1,
+                        dayname: Some("Fri"),
+                    },
+                    time: Some(Time { hour: 9, minute: 0 }),
+                },
+                end: Some(DateTime {
+                    date: Date {
+                        year: 2024,
+                        month: 3,
+                        day: 1,
+                        dayname: Some("Fri"),
+                    },
+                    time: Some(Time {
+                        hour: 10,
+                        minute: 30
+                    }),
+                }),
+                duration: Some(Duration::from_secs(90 * 60)),
+            }
+        );
+        assert_eq!(clock.to_string(), input.trim_end());
+    }
+
+    #[test]
+    fn running_clock() {
+        let input = "CLOCK: [2024-03-01 Fri 09:00]";
+        let parsed = parse_org(input);
+        let clock = expr_in_pool!(parsed, Clock).unwrap();
+        assert!(clock.end.is_none());
+        assert!(clock.elapsed().is_none());
+    }
+
+    #[test]
+    fn not_clock() {
+        let input = "CLOCK: <2024-03-01 Fri 09:00>\n";
+        let parsed = parse_org(input);
+        assert!(expr_in_pool!(parsed, Clock).is_none());
+
+        let input = "CLOCK: [2024-03-01 Fri 09:00] and then\n";
+        let parsed = parse_org(input);
+        assert!(expr_in_pool!(parsed, Clock).is_none());
+    }
+
+    #[test]
+    fn clock_across_midnight() {
+        let input = "CLOCK: [2024-02-28 Wed 23:00]--[2024-03-01 Fri 01:00] => 26:00\n";
+        let parsed = parse_org(input);
+        let clock = expr_in_pool!(parsed, Clock).unwrap();
+        assert_eq!(clock.elapsed(), Some(Duration::from_secs(26 * 60 * 60)));
+        assert_eq!(clock.to_string(), input.trim_end());
+
+        // a cookie at the end of the file
+        let parsed = parse_org(input.trim_end());
+        let clock = expr_in_pool!(parsed, Clock).unwrap();
+        assert_eq!(clock.duration, Some(Duration::from_secs(26 * 60 * 60)));
+    }
+
+    #[test]
+    fn clocked_time_sum() {
+        let input = r"* Client
+:LOGBOOK:
+CLOCK: [2024-03-01 Fri 09:00]--[2024-03-01 Fri 10:30] =>  1:30
+CLOCK: [2024-03-02 Sat 09:00]
+:END:
+** Subtask
+:LOGBOOK:
+CLOCK: [2024-03-01 Fri 11:00]--[2024-03-01 Fri 11:45] =>  0:45
+:END:
+* Other
+CLOCK: [2024-03-01 Fri 13:00]--[2024-03-01 Fri 14:00] =>  1:00
+";
+        let parsed = parse_org(input);
+        let root = parsed.pool[parsed.pool.root_id()].obj.children().unwrap();
+        let (client, other) = (root[0], root[1]);
+        let subtask = *parsed.pool[client]
+            .obj
+            .children()
+            .unwrap()
+            .iter()
+            .find(|id| matches!(parsed.pool[**id].obj, Expr::Heading(_)))
+            .unwrap();
+
+        let minutes = |id| parsed.clocked_time(id).as_secs() / 60;
+        assert_eq!(minutes(client), 135);
+        assert_eq!(minutes(subtask), 45);
+        assert_eq!(minutes(other), 60);
+        assert_eq!(minutes(parsed.pool.root_id()), 195);
+    }
+}
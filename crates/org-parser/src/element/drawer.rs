@@ -3,9 +3,9 @@ use std::collections::HashMap;
 
 use crate::constants::{COLON, HYPHEN, NEWLINE, UNDERSCORE};
 use crate::node_pool::NodeID;
-use crate::object::parse_node_property;
+use crate::object::{Timestamp, parse_node_property, parse_timestamp};
 use crate::parse::parse_element;
-use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result};
+use crate::types::{Cursor, Expr, MatchError, ParseOpts, Parseable, Parser, Result};
 use crate::utils::Match;
 
 use lazy_static::lazy_static;
@@ -20,6 +20,24 @@ lazy_static! {
 pub struct Drawer<'a> {
     pub children: Vec<NodeID>,
     pub name: &'a str,
+    /// State change notes found in the list items of a `:LOGBOOK:` drawer.
+    pub state_changes: Vec<StateChange<'a>>,
+}
+
+/// A logged change in a heading's TODO state:
+///
+/// ```text
+/// :LOGBOOK:
+/// - State "DONE"       from "TODO"       [2024-03-01 Fri 10:00]
+/// :END:
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange<'a> {
+    /// The [`Item`](super::Item) holding the entry.
+    pub item: NodeID,
+    pub to: Option<&'a str>,
+    pub from: Option<&'a str>,
+    pub timestamp: Timestamp<'a>,
 }
 
 impl<'a> Parseable<'a> for Drawer<'a> {
@@ -68,10 +86,30 @@ impl<'a> Parseable<'a> for Drawer<'a> {
             temp_cursor.index = parser.pool[element_id].end;
         }
 
+        let mut state_changes = Vec::new();
+        if name_match.obj.eq_ignore_ascii_case("logbook") {
+            for child in &children {
+                let Expr::PlainList(list) = &parser.pool[*child].obj else {
+                    continue;
+                };
+                for item_id in &list.children {
+                    if let Expr::Item(item) = &parser.pool[*item_id].obj
+                        && let Some(first) = item.children.first()
+                    {
+                        cursor.index = parser.pool[*first].start;
+                        if let Ok(change) = parse_state_change(cursor, *item_id) {
+                            state_changes.push(change);
+                        }
+                    }
+                }
+            }
+        }
+
         Ok(parser.alloc_with_id(
             Self {
                 children,
                 name: name_match.obj,
+                state_changes,
             },
             start,
             end,
@@ -81,6 +119,37 @@ impl<'a> Parseable<'a> for Drawer<'a> {
     }
 }
 
+// State "DONE"       from "TODO"       [2024-03-01 Fri 10:00]
+fn parse_state_change(mut cursor: Cursor, item: NodeID) -> Result<StateChange> {
+    fn quoted<'a>(cursor: &mut Cursor<'a>) -> Result<Option<&'a str>> {
+        if cursor.try_curr()? != b'"' {
+            return Ok(None);
+        }
+        cursor.next();
+        let state = cursor.fn_until(|chr| chr == b'"' || chr == NEWLINE)?;
+        cursor.index = state.end;
+        cursor.word("\"")?;
+        Ok(Some(state.obj))
+    }
+
+    cursor.word("State")?;
+    cursor.skip_ws();
+    let to = quoted(&mut cursor)?;
+    cursor.skip_ws();
+    cursor.word("from")?;
+    cursor.skip_ws();
+    let from = quoted(&mut cursor)?;
+    cursor.skip_ws();
+    let timestamp = parse_timestamp(cursor)?.obj;
+
+    Ok(StateChange {
+        item,
+        to,
+        from,
+        timestamp,
+    })
+}
+
 pub type PropertyDrawer<'a> = HashMap<&'a str, Cow<'a, str>>;
 
 pub(crate) fn parse_property(mut cursor: Cursor) -> Result<Match<PropertyDrawer>> {
@@ -130,7 +199,8 @@ pub(crate) fn parse_property(mut cursor: Cursor) -> Result<Match<PropertyDrawer>
 
 #[cfg(test)]
 mod tests {
-    use crate::parse_org;
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
 
     #[test]
     fn basic_drawer() {
@@ -161,4 +231,42 @@ halloo
         let pool = parse_org(input);
         pool.print_tree();
     }
+
+    #[test]
+    fn logbook_state_changes() {
+        let input = r#"* DONE task
+:LOGBOOK:
+- State "DONE"       from "TODO"       [2024-03-01 Fri 10:00]
+- State "TODO"       from              [2024-02-28 Wed 09:00] \\
+  created
+- Note taken on [2024-02-28 Wed 09:05]
+CLOCK: [2024-02-28 Wed 09:00]--[2024-02-28 Wed 10:00] =>  1:00
+:END:
+"#;
+
+        let parsed = parse_org(input);
+        let drawer = expr_in_pool!(parsed, Drawer).unwrap();
+        let changes = &drawer.state_changes;
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].to, Some("DONE"));
+        assert_eq!(changes[0].from, Some("TODO"));
+        assert_eq!(changes[0].timestamp.start.date.day, 1);
+        assert_eq!(changes[1].to, Some("TODO"));
+        assert_eq!(changes[1].from, None);
+        assert!(matches!(parsed.pool[changes[1].item].obj, Expr::Item(_)));
+        assert!(expr_in_pool!(parsed, Clock).is_some());
+    }
+
+    #[test]
+    fn state_changes_only_in_logbook() {
+        let input = r#":NOTES:
+- State "DONE"       from "TODO"       [2024-03-01 Fri 10:00]
+:END:
+"#;
+
+        let parsed = parse_org(input);
+        let drawer = expr_in_pool!(parsed, Drawer).unwrap();
+        assert!(drawer.state_changes.is_empty());
+    }
 }
@@ -0,0 +1,138 @@
+use crate::constants::NEWLINE;
+use crate::element::header_arg_pairs;
+use crate::node_pool::NodeID;
+use crate::parse::parse_element;
+use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result};
+
+use lazy_static::lazy_static;
+use regex::bytes::Regex;
+
+lazy_static! {
+    static ref END_RE: Regex = Regex::new(r"(?mi)^[ \t]*#\+end:[\t ]*$").unwrap();
+}
+
+/// A block whose contents are generated from its name and parameters.
+///
+/// ```text
+/// #+BEGIN: clocktable :scope file :maxlevel 2
+/// contents
+/// #+END:
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicBlock<'a> {
+    pub name: &'a str,
+    /// `:key val` pairs in the order they were written.
+    pub parameters: Vec<(&'a str, &'a str)>,
+    pub children: Vec<NodeID>,
+}
+
+impl<'a> DynamicBlock<'a> {
+    /// The value of the last `:key` parameter, if present.
+    pub fn parameter(&self, key: &str) -> Option<&'a str> {
+        self.parameters
+            .iter()
+            .rev()
+            .find(|(param, _)| *param == key)
+            .map(|&(_, val)| val)
+    }
+}
+
+impl<'a> Parseable<'a> for DynamicBlock<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        cursor.word("#+")?;
+        let key = cursor.fn_until(|chr: u8| chr == b':' || chr.is_ascii_whitespace())?;
+        if !key.obj.eq_ignore_ascii_case("begin") {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.index = key.end;
+        cursor.word(":")?;
+        cursor.skip_ws();
+
+        let name = cursor.fn_until(|chr: u8| chr.is_ascii_whitespace())?;
+        if name.obj.is_empty() {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.index = name.end;
+        let params = cursor.fn_until(|chr: u8| chr == NEWLINE)?;
+        cursor.index = params.end + 1;
+
+        let matched_reg = END_RE.find(cursor.rest()).ok_or(MatchError::InvalidLogic)?;
+        let loc = matched_reg.start() + cursor.index;
+        // eat the newline after #+END: if there is one
+        let end = (matched_reg.end() + cursor.index + 1).min(cursor.len());
+
+        let mut children: Vec<NodeID> = Vec::new();
+        let reserve_id = parser.pool.reserve_id();
+        let mut temp_cursor = cursor.cut_off(loc);
+        while let Ok(element_id) =
+            parse_element(parser, temp_cursor, Some(reserve_id), ParseOpts::default())
+        {
+            children.push(element_id);
+            temp_cursor.index = parser.pool[element_id].end;
+        }
+
+        Ok(parser.alloc_with_id(
+            Self {
+                name: name.obj,
+                parameters: header_arg_pairs(params.obj),
+                children,
+            },
+            start,
+            end,
+            parent,
+            reserve_id,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
+
+    #[test]
+    fn basic_dynamic_block() {
+        let input = r"#+BEGIN: clocktable :scope file :maxlevel 2
+| old | table |
+#+END:
+after
+";
+        let parsed = parse_org(input);
+        let block = expr_in_pool!(parsed, DynamicBlock).unwrap();
+
+        assert_eq!(block.name, "clocktable");
+        assert_eq!(block.parameters, [("scope", "file"), ("maxlevel", "2")]);
+        assert_eq!(block.parameter("maxlevel"), Some("2"));
+        assert_eq!(block.parameter("id"), None);
+        assert_eq!(block.children.len(), 1);
+        assert!(matches!(parsed.pool[block.children[0]].obj, Expr::Table(_)));
+        assert!(expr_in_pool!(parsed, Keyword).is_none());
+        assert!(expr_in_pool!(parsed, Paragraph).is_some());
+    }
+
+    #[test]
+    fn empty_dynamic_block() {
+        let parsed = parse_org("#+begin: index\n#+end:");
+        let block = expr_in_pool!(parsed, DynamicBlock).unwrap();
+
+        assert_eq!(block.name, "index");
+        assert!(block.parameters.is_empty());
+        assert!(block.children.is_empty());
+    }
+
+    #[test]
+    fn unterminated_dynamic_block() {
+        for input in ["#+begin: index\ncontents\n", "#+begin:\n#+end:\n"] {
+            let parsed = parse_org(input);
+            assert!(expr_in_pool!(parsed, DynamicBlock).is_none(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::borrow::Cow;
+
+use crate::constants::{COLON, NEWLINE, SPACE};
+use crate::node_pool::NodeID;
+use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result};
+
+/// A verbatim area made of consecutive lines starting with a colon.
+///
+/// ```text
+/// : first line
+/// :   indented second line
+/// :
+/// ```
+///
+/// The colon must be followed by a space or the end of the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedWidth<'a> {
+    /// The lines without their leading `: `, joined by newlines.
+    pub contents: Cow<'a, str>,
+}
+
+impl<'a> Parseable<'a> for FixedWidth<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        let mut lines: Vec<&str> = Vec::new();
+
+        loop {
+            cursor.word(":")?;
+            match cursor.try_curr() {
+                Ok(SPACE) => cursor.next(),
+                Ok(NEWLINE) | Err(_) => {}
+                Ok(_) => return Err(MatchError::InvalidLogic),
+            }
+            let line_start = cursor.index;
+            cursor.adv_till_byte(NEWLINE);
+            lines.push(cursor.clamp_backwards(line_start));
+            if cursor.curr_valid().is_err() {
+                break;
+            }
+            // eat the newline
+            cursor.next();
+
+            // the next line continues the area if it also starts with a colon
+            let Ok(next_line) = cursor.fn_while(|chr: u8| chr == SPACE || chr == b'\t') else {
+                break;
+            };
+            if cursor.get(next_line.end) == Some(&COLON)
+                && cursor
+                    .get(next_line.end + 1)
+                    .is_none_or(|&chr| chr == SPACE || chr == NEWLINE)
+            {
+                cursor.index = next_line.end;
+            } else {
+                break;
+            }
+        }
+
+        let contents = if let [line] = lines[..] {
+            Cow::Borrowed(line)
+        } else {
+            Cow::Owned(lines.join("\n"))
+        };
+
+        Ok(parser.alloc(Self { contents }, start, cursor.index, parent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
+
+    #[test]
+    fn basic_fixed_width() {
+        let parsed = parse_org(": some *verbatim* text\n");
+        let fixed = expr_in_pool!(parsed, FixedWidth).unwrap();
+        assert_eq!(fixed.contents, "some *verbatim* text");
+        assert!(expr_in_pool!(parsed, Bold).is_none());
+    }
+
+    #[test]
+    fn multiline_fixed_width() {
+        let input = r":   indented
+:
+: last
+after
+";
+        let parsed = parse_org(input);
+        let fixed = expr_in_pool!(parsed, FixedWidth).unwrap();
+        assert_eq!(fixed.contents, "  indented\n\nlast");
+        assert!(expr_in_pool!(parsed, Paragraph).is_some());
+    }
+
+    #[test]
+    fn fixed_width_eof() {
+        let parsed = parse_org(": one\n  : two");
+        let fixed = expr_in_pool!(parsed, FixedWidth).unwrap();
+        assert_eq!(fixed.contents, "one\ntwo");
+
+        let parsed = parse_org(":");
+        let fixed = expr_in_pool!(parsed, FixedWidth).unwrap();
+        assert_eq!(fixed.contents, "");
+    }
+
+    #[test]
+    fn fixed_width_in_paragraph() {
+        let input = r"paragraph
+: fixed
+";
+        let parsed = parse_org(input);
+        let fixed = expr_in_pool!(parsed, FixedWidth).unwrap();
+        assert_eq!(fixed.contents, "fixed");
+    }
+
+    #[test]
+    fn not_fixed_width() {
+        for input in [":not fixed\n", "a : b\n"] {
+            let parsed = parse_org(input);
+            assert!(expr_in_pool!(parsed, FixedWidth).is_none(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,571 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::element::{Block, PropertyDrawer, header_arg_pairs, parse_arguments};
+use crate::node_pool::NodeID;
+use crate::types::{Expr, Parser};
+
+/// The effective header arguments of a source block.
+///
+/// Built by [`Parser::header_args`] from every place a block can take its
+/// arguments from, each overriding the ones before it:
+///
+/// 1. org's defaults, which export the results instead of the code of inline
+///    source blocks and babel calls
+/// 2. the defaults of the block's language, see [`language_defaults`]
+/// 3. `#+PROPERTY: header-args` and `#+PROPERTY: header-args:lang`
+/// 4. `header-args` and `header-args:lang` properties of the enclosing headings,
+///    outermost first
+/// 5. `#+HEADER:` lines above the block
+/// 6. the `#+begin_src` line, the `[...]` of an inline source block or the
+///    headers of a babel call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderArgs<'a> {
+    pub results: ResultsArgs,
+    pub exports: Exports,
+    pub tangle: Tangle<'a>,
+    /// `:var name=value` assignments, in order. Assigning to a variable again
+    /// replaces its value.
+    pub vars: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    /// `None` for `:session none`.
+    pub session: Option<Cow<'a, str>>,
+    pub noweb: Noweb,
+    pub dir: Option<Cow<'a, str>>,
+    pub cache: bool,
+    /// The value of every argument after merging, including the ones without a
+    /// field of their own.
+    pub raw: HashMap<Cow<'a, str>, Cow<'a, str>>,
+}
+
+/// `:results`, made up of one word from each of its classes: `:results output table replace`.
+///
+/// Every word only overrides the setting of its own class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResultsArgs {
+    pub collection: Collection,
+    pub kind: Option<ResultsKind>,
+    pub format: Option<ResultsFormat>,
+    pub handling: Handling,
+}
+
+/// How results are collected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Collection {
+    /// `value`: the value of the last expression.
+    #[default]
+    Value,
+    /// `output`: everything written to standard output.
+    Output,
+}
+
+/// What the results are interpreted as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsKind {
+    /// `table`, `vector`
+    Table,
+    /// `list`
+    List,
+    /// `scalar`, `verbatim`
+    Scalar,
+    /// `file`
+    File,
+}
+
+/// How the results are wrapped when inserted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsFormat {
+    Raw,
+    Org,
+    Html,
+    Latex,
+    Code,
+    Pp,
+    Drawer,
+    Link,
+}
+
+/// What happens to the results of a previous evaluation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Handling {
+    #[default]
+    Replace,
+    Silent,
+    None,
+    Discard,
+    Append,
+    Prepend,
+}
+
+/// `:exports`, what makes it into an export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Exports {
+    #[default]
+    Code,
+    Results,
+    Both,
+    None,
+}
+
+/// `:tangle`, where the block is written to when tangling.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Tangle<'a> {
+    #[default]
+    No,
+    /// A file named after the org file, with the extension of the block's language.
+    Yes,
+    File(Cow<'a, str>),
+}
+
+/// `:noweb`, when `<<references>>` are expanded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Noweb {
+    #[default]
+    No,
+    Yes,
+    Tangle,
+    NoExport,
+    StripExport,
+    StripTangle,
+    Eval,
+}
+
+impl Default for HeaderArgs<'_> {
+    // org-babel-default-header-args
+    fn default() -> Self {
+        Self {
+            results: ResultsArgs::default(),
+            exports: Exports::default(),
+            tangle: Tangle::default(),
+            vars: Vec::new(),
+            session: None,
+            noweb: Noweb::default(),
+            dir: None,
+            cache: false,
+            raw: HashMap::from([
+                ("session".into(), "none".into()),
+                ("results".into(), "replace".into()),
+                ("exports".into(), "code".into()),
+                ("cache".into(), "no".into()),
+                ("noweb".into(), "no".into()),
+                ("tangle".into(), "no".into()),
+            ]),
+        }
+    }
+}
+
+impl<'a> HeaderArgs<'a> {
+    /// Applies a line of arguments on top of the current ones: `:results output :var x=2`.
+    pub fn apply(&mut self, args: Cow<'a, str>) {
+        match args {
+            Cow::Borrowed(args) => {
+                for (key, val) in header_arg_pairs(args) {
+                    self.set(key.into(), val.into());
+                }
+            }
+            Cow::Owned(args) => {
+                for (key, val) in header_arg_pairs(&args) {
+                    self.set(key.to_owned().into(), val.to_owned().into());
+                }
+            }
+        }
+    }
+
+    /// Sets a single argument, `key` without its leading colon.
+    pub fn set(&mut self, key: Cow<'a, str>, val: Cow<'a, str>) {
+        match key.to_ascii_lowercase().as_str() {
+            "results" => self.results.apply(&val),
+            "exports" => {
+                self.exports = match val.as_ref() {
+                    "code" => Exports::Code,
+                    "results" => Exports::Results,
+                    "both" => Exports::Both,
+                    "none" => Exports::None,
+                    _ => self.exports,
+                }
+            }
+            "tangle" => {
+                self.tangle = match val.as_ref() {
+                    "no" => Tangle::No,
+                    "yes" => Tangle::Yes,
+                    _ => Tangle::File(val.clone()),
+                }
+            }
+            "var" => self.assign_vars(val.clone()),
+            "session" => self.session = (val != "none").then(|| val.clone()),
+            "noweb" => {
+                self.noweb = match val.as_ref() {
+                    "no" => Noweb::No,
+                    "yes" => Noweb::Yes,
+                    "tangle" => Noweb::Tangle,
+                    "no-export" => Noweb::NoExport,
+                    "strip-export" => Noweb::StripExport,
+                    "strip-tangle" => Noweb::StripTangle,
+                    "eval" => Noweb::Eval,
+                    _ => self.noweb,
+                }
+            }
+            "dir" => self.dir = Some(val.clone()),
+            "cache" => self.cache = val == "yes",
+            _ => {}
+        }
+        self.raw.insert(key, val);
+    }
+
+    // name=value, name2=value2
+    fn assign_vars(&mut self, val: Cow<'a, str>) {
+        let mut assign = |name: Cow<'a, str>, value: Cow<'a, str>| {
+            if let Some(var) = self.vars.iter_mut().find(|(var, _)| *var == name) {
+                var.1 = value;
+            } else {
+                self.vars.push((name, value));
+            }
+        };
+
+        match val {
+            Cow::Borrowed(val) => {
+                for arg in parse_arguments(val) {
+                    if let Some(name) = arg.name {
+                        assign(name.into(), arg.value.into());
+                    }
+                }
+            }
+            Cow::Owned(val) => {
+                for arg in parse_arguments(&val) {
+                    if let Some(name) = arg.name {
+                        assign(name.to_owned().into(), arg.value.to_owned().into());
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ResultsArgs {
+    fn apply(&mut self, val: &str) {
+        for word in val.split_ascii_whitespace() {
+            match word {
+                "value" => self.collection = Collection::Value,
+                "output" => self.collection = Collection::Output,
+
+                "table" | "vector" => self.kind = Some(ResultsKind::Table),
+                "list" => self.kind = Some(ResultsKind::List),
+                "scalar" | "verbatim" => self.kind = Some(ResultsKind::Scalar),
+                "file" => self.kind = Some(ResultsKind::File),
+
+                "raw" => self.format = Some(ResultsFormat::Raw),
+                "org" => self.format = Some(ResultsFormat::Org),
+                "html" => self.format = Some(ResultsFormat::Html),
+                "latex" => self.format = Some(ResultsFormat::Latex),
+                "code" => self.format = Some(ResultsFormat::Code),
+                "pp" => self.format = Some(ResultsFormat::Pp),
+                "drawer" => self.format = Some(ResultsFormat::Drawer),
+                "link" | "graphics" => self.format = Some(ResultsFormat::Link),
+
+                "replace" => self.handling = Handling::Replace,
+                "silent" => self.handling = Handling::Silent,
+                "none" => self.handling = Handling::None,
+                "discard" => self.handling = Handling::Discard,
+                "append" => self.handling = Handling::Append,
+                "prepend" => self.handling = Handling::Prepend,
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// The effective header arguments of a source block, inline source block or
+    /// babel call, see [`HeaderArgs`].
+    pub fn header_args(&self, block_id: NodeID) -> HeaderArgs<'a> {
+        let node = &self.pool[block_id];
+        let language = match &node.obj {
+            Expr::Block(Block::Src { language, .. }) => *language,
+            Expr::InlineSrc(inner) => Some(inner.lang),
+            _ => None,
+        };
+
+        let mut ret = HeaderArgs::default();
+        // org-babel-default-inline-header-args, org-babel-default-lob-header-args
+        if matches!(node.obj, Expr::InlineSrc(_) | Expr::BabelCall(_)) {
+            ret.set("exports".into(), "results".into());
+        }
+        if let Some(language) = language {
+            ret.apply(language_defaults(language).into());
+        }
+        apply_properties(&mut ret, &self.properties, language);
+
+        let mut headings = Vec::new();
+        let mut curr = node.parent;
+        while let Some(id) = curr {
+            if let Expr::Heading(heading) = &self.pool[id].obj
+                && let Some(properties) = &heading.properties
+            {
+                headings.push(properties);
+            }
+            curr = self.pool[id].parent;
+        }
+        for properties in headings.into_iter().rev() {
+            apply_properties(&mut ret, properties, language);
+        }
+
+        for val in self.block_headers.get(&block_id).into_iter().flatten() {
+            ret.apply((*val).into());
+        }
+
+        match &node.obj {
+            Expr::Block(Block::Src { switches, .. }) => {
+                let line_args = self.block_line_args(block_id, language, switches.raw);
+                ret.apply(line_args.into());
+            }
+            Expr::InlineSrc(inner) => {
+                if let Some(headers) = inner.headers {
+                    ret.apply(headers.into());
+                }
+            }
+            Expr::BabelCall(call) => {
+                for (key, val) in call.inside_header.iter().chain(&call.end_header) {
+                    ret.set((*key).into(), (*val).into());
+                }
+            }
+            _ => {}
+        }
+        ret
+    }
+
+    /// The arguments on the `#+begin_src` line, keeping repeated keys.
+    fn block_line_args(&self, block_id: NodeID, language: Option<&str>, switches: &str) -> &'a str {
+        let node = &self.pool[block_id];
+        let line = self.source[node.start..node.end]
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim_start();
+        // #+begin_src
+        let rest = line.get("#+begin_src".len()..).unwrap_or_default();
+        let rest = rest.trim_start();
+        let rest = rest
+            .strip_prefix(language.unwrap_or_default())
+            .unwrap_or(rest)
+            .trim_start();
+        rest.strip_prefix(switches).unwrap_or(rest)
+    }
+}
+
+/// The header arguments org applies to every block of a language, its
+/// `org-babel-default-header-args:lang`.
+///
+/// Languages that render a file, such as `dot`, export their results. Shell
+/// and python blocks have no defaults of their own.
+pub fn language_defaults(language: &str) -> &'static str {
+    match language {
+        "emacs-lisp" | "elisp" => ":lexical yes",
+        "dot" | "ditaa" | "plantuml" => ":results file :exports results",
+        "gnuplot" => ":results file :exports results :session none",
+        "latex" => ":results latex :exports results",
+        _ => "",
+    }
+}
+
+fn apply_properties<'a>(
+    args: &mut HeaderArgs<'a>,
+    properties: &PropertyDrawer<'a>,
+    language: Option<&str>,
+) {
+    let get = |name: &str| {
+        properties
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, val)| val.clone())
+    };
+
+    if let Some(val) = get("header-args") {
+        args.apply(val);
+    }
+    if let Some(language) = language
+        && let Some(val) = get(&format!("header-args:{language}"))
+    {
+        args.apply(val);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::element::Affiliated;
+    use crate::node_pool::make_node_id;
+    use crate::{expr_in_pool, parse_org};
+
+    fn block_args<'a>(parser: &Parser<'a>) -> HeaderArgs<'a> {
+        let index = parser
+            .pool
+            .iter()
+            .position(|node| matches!(node.obj, Expr::Block(Block::Src { .. })))
+            .unwrap();
+        parser.header_args(make_node_id(index as u32))
+    }
+
+    #[test]
+    fn defaults() {
+        let parser = parse_org("#+begin_src python\n#+end_src\n");
+        let args = block_args(&parser);
+        assert_eq!(args.exports, Exports::Code);
+        assert_eq!(args.tangle, Tangle::No);
+        assert_eq!(args.session, None);
+        assert_eq!(args.results, ResultsArgs::default());
+        assert!(!args.cache);
+        assert_eq!(args.raw.get("exports").map(AsRef::as_ref), Some("code"));
+    }
+
+    #[test]
+    fn precedence() {
+        let input = r#"#+PROPERTY: header-args :results silent :exports both :var a=1
+#+PROPERTY: header-args:python :session py
+#+PROPERTY: header-args:shell :session sh
+* Outer
+:PROPERTIES:
+:header-args: :tangle outer.py :var b=2
+:header-args+: :dir /tmp
+:END:
+** Inner
+:PROPERTIES:
+:HEADER-ARGS:python: :results output :var a=3
+:END:
+#+NAME: block
+#+HEADER: :cache yes :noweb yes
+#+header: :var c="x, y"
+#+begin_src python -n :tangle inner.py :var b=4 :var d=5
+#+end_src
+"#;
+        let parser = parse_org(input);
+        let args = block_args(&parser);
+
+        assert_eq!(
+            args.results,
+            ResultsArgs {
+                collection: Collection::Output,
+                kind: None,
+                format: None,
+                handling: Handling::Silent,
+            }
+        );
+        assert_eq!(args.exports, Exports::Both);
+        assert_eq!(args.tangle, Tangle::File("inner.py".into()));
+        assert_eq!(args.session.as_deref(), Some("py"));
+        assert_eq!(args.dir.as_deref(), Some("/tmp"));
+        assert!(args.cache);
+        assert_eq!(args.noweb, Noweb::Yes);
+        assert_eq!(
+            args.vars,
+            [
+                ("a".into(), "3".into()),
+                ("b".into(), "4".into()),
+                ("c".into(), r#""x, y""#.into()),
+                ("d".into(), "5".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn precedence_chain() {
+        // every layer overrides one argument of the layer before it
+        let input = r"#+PROPERTY: header-args :exports both :dir /doc
+#+PROPERTY: header-args:dot :cache yes
+* Heading
+:PROPERTIES:
+:header-args:dot: :dir /heading :noweb yes
+:END:
+#+HEADER: :noweb tangle :var x=1
+#+HEADER: :var x=2
+#+begin_src dot :var x=3
+#+end_src
+
+#+call: f[:results list :session a]() :results table
+";
+        let parser = parse_org(input);
+        let args = block_args(&parser);
+
+        // org's defaults
+        assert_eq!(args.raw.get("tangle").map(AsRef::as_ref), Some("no"));
+        // the language's defaults
+        assert_eq!(args.results.kind, Some(ResultsKind::File));
+        // #+PROPERTY:
+        assert_eq!(args.exports, Exports::Both);
+        assert!(args.cache);
+        // the heading's properties
+        assert_eq!(args.dir.as_deref(), Some("/heading"));
+        // #+HEADER: lines, in order
+        assert_eq!(args.noweb, Noweb::Tangle);
+        // the #+begin_src line
+        assert_eq!(args.vars, [("x".into(), "3".into())]);
+
+        let (index, _) = parser
+            .pool
+            .iter()
+            .enumerate()
+            .find(|(_, node)| matches!(node.obj, Expr::BabelCall(_)))
+            .unwrap();
+        let args = parser.header_args(make_node_id(index as u32));
+        assert_eq!(args.results.kind, Some(ResultsKind::Table));
+        assert_eq!(args.session.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn header_keyword() {
+        let parser = parse_org("#+header: :exports none\n#+begin_src c\n#+end_src\n");
+        assert!(matches!(
+            expr_in_pool!(parser, Affiliated),
+            Some(Affiliated::Header {
+                child_id: Some(_),
+                val: ":exports none"
+            })
+        ));
+        assert_eq!(block_args(&parser).exports, Exports::None);
+    }
+
+    #[test]
+    fn inline_and_call_args() {
+        let input = r"#+PROPERTY: header-args:sh :results raw
+src_sh{echo 1} src_sh[:exports code]{echo 2}
+
+#+call: name[:results list]() :exports none
+";
+        let parser = parse_org(input);
+        let args: Vec<_> = parser
+            .pool
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches!(node.obj, Expr::InlineSrc(_) | Expr::BabelCall(_)))
+            .map(|(index, _)| parser.header_args(make_node_id(index as u32)))
+            .collect();
+
+        assert_eq!(args.len(), 3);
+        assert_eq!(args[0].exports, Exports::Results);
+        assert_eq!(args[0].results.format, Some(ResultsFormat::Raw));
+        assert_eq!(
+            args[0].raw.get("exports").map(AsRef::as_ref),
+            Some("results")
+        );
+        assert_eq!(args[1].exports, Exports::Code);
+        assert_eq!(args[2].exports, Exports::None);
+        assert_eq!(args[2].results.kind, Some(ResultsKind::List));
+    }
+
+    #[test]
+    fn results_classes() {
+        let mut results = ResultsArgs::default();
+        results.apply("output table");
+        results.apply("raw append");
+        results.apply("value bogus");
+        assert_eq!(
+            results,
+            ResultsArgs {
+                collection: Collection::Value,
+                kind: Some(ResultsKind::Table),
+                format: Some(ResultsFormat::Raw),
+                handling: Handling::Append,
+            }
+        );
+    }
+}
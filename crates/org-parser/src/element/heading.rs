@@ -6,9 +6,7 @@ use crate::parse::{parse_element, parse_object};
 use crate::types::{Cursor, Expr, MatchError, ParseOpts, Parseable, Parser, Result};
 use crate::utils::{Match, bytes_to_str};
 
-use super::{PropertyDrawer, parse_property};
-
-const ORG_TODO_KEYWORDS: [&str; 2] = ["TODO", "DONE"];
+use super::{Planning, PropertyDrawer, parse_planning, parse_property};
 
 // STARS KEYWORD PRIORITY TITLE TAGS
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -16,11 +14,16 @@ pub struct Heading<'a> {
     pub heading_level: HeadingLevel,
     // Org-Todo type stuff
     pub keyword: Option<&'a str>,
+    /// Whether `keyword` is a done state, see [`TodoKeywords`](super::TodoKeywords).
+    pub done: bool,
     pub priority: Option<Priority>,
+    /// Whether the heading is commented out with `COMMENT`.
+    pub comment: bool,
     // plain text of title + parsed nodeIDs
     // store both to accomdote targets
     pub title: Option<(&'a str, Vec<NodeID>)>,
     pub tags: Option<Vec<Tag<'a>>>,
+    pub planning: Option<Box<Planning<'a>>>,
     pub properties: Option<PropertyDrawer<'a>>,
     pub children: Option<Vec<NodeID>>,
 }
@@ -56,20 +59,30 @@ pub enum HeadingLevel {
     Four,
     Five,
     Six,
+    /// Any level past six, holding the number of stars.
+    Deep(u8),
+}
+
+impl HeadingLevel {
+    /// The level for a number of stars, `None` for 0 or more than [`u8::MAX`].
+    pub fn new(value: usize) -> Option<Self> {
+        match value {
+            0 => None,
+            1 => Some(HeadingLevel::One),
+            2 => Some(HeadingLevel::Two),
+            3 => Some(HeadingLevel::Three),
+            4 => Some(HeadingLevel::Four),
+            5 => Some(HeadingLevel::Five),
+            6 => Some(HeadingLevel::Six),
+            _ => u8::try_from(value).ok().map(HeadingLevel::Deep),
+        }
+    }
 }
 
 // Implemented not via `TryFrom` so that `MatchError` can be private
 // while keeping the struct Public
 fn try_heading_levelfrom(value: usize) -> Result<HeadingLevel> {
-    match value {
-        1 => Ok(HeadingLevel::One),
-        2 => Ok(HeadingLevel::Two),
-        3 => Ok(HeadingLevel::Three),
-        4 => Ok(HeadingLevel::Four),
-        5 => Ok(HeadingLevel::Five),
-        6 => Ok(HeadingLevel::Six),
-        _ => Err(MatchError::InvalidLogic),
-    }
+    HeadingLevel::new(value).ok_or(MatchError::InvalidLogic)
 }
 
 impl From<HeadingLevel> for u8 {
@@ -81,6 +94,7 @@ impl From<HeadingLevel> for u8 {
             HeadingLevel::Four => 4,
             HeadingLevel::Five => 5,
             HeadingLevel::Six => 6,
+            HeadingLevel::Deep(level) => level,
         }
     }
 }
@@ -100,12 +114,14 @@ impl<'a> Parseable<'a> for Heading<'a> {
         // guaranteed to allocate since this is a valid headline. Setup the id
         let reserved_id = parser.pool.reserve_id();
 
-        let keyword: Option<&str> = if let Ok(keyword_match) = Heading::parse_keyword(cursor) {
-            cursor.move_to(keyword_match.end);
-            Some(keyword_match.obj)
-        } else {
-            None
-        };
+        let keyword: Option<&str> =
+            if let Ok(keyword_match) = Heading::parse_keyword(parser, cursor) {
+                cursor.move_to(keyword_match.end);
+                Some(keyword_match.obj)
+            } else {
+                None
+            };
+        let done = keyword.is_some_and(|keyword| parser.todo_keywords.is_done(keyword));
 
         let priority: Option<Priority> = if let Ok(prio_match) = Heading::parse_priority(cursor) {
             cursor.move_to(prio_match.end);
@@ -114,6 +130,13 @@ impl<'a> Parseable<'a> for Heading<'a> {
             None
         };
 
+        let comment = if let Ok(comment_match) = Heading::parse_comment(cursor) {
+            cursor.move_to(comment_match.end);
+            true
+        } else {
+            false
+        };
+
         let tag_match = Heading::parse_tag(cursor);
         // if the tags are valid:
         // tag_match.start: space
@@ -144,6 +167,13 @@ impl<'a> Parseable<'a> for Heading<'a> {
 
         // Handle subelements
 
+        let planning = if let Ok(ret) = parse_planning(cursor) {
+            cursor.index = ret.end;
+            Some(Box::new(ret.obj))
+        } else {
+            None
+        };
+
         let properties = if let Ok(ret) = parse_property(cursor) {
             cursor.index = ret.end;
             Some(ret.obj)
@@ -154,16 +184,25 @@ impl<'a> Parseable<'a> for Heading<'a> {
         let mut section_vec: Vec<NodeID> = Vec::new();
 
         while let Ok(element_id) = parse_element(parser, cursor, Some(reserved_id), parse_opts) {
-            if let Expr::Heading(ref mut heading) = parser.pool[element_id].obj {
-                if u8::from(heading_level) < u8::from(heading.heading_level) {
-                    if let Some(tag_vec) = &mut heading.tags {
-                        tag_vec.push(Tag::Loc(reserved_id));
+            match parser.pool[element_id].obj {
+                Expr::Heading(ref mut heading) => {
+                    if u8::from(heading_level) < u8::from(heading.heading_level) {
+                        if let Some(tag_vec) = &mut heading.tags {
+                            tag_vec.push(Tag::Loc(reserved_id));
+                        } else {
+                            heading.tags = Some(vec![Tag::Loc(reserved_id)]);
+                        }
                     } else {
-                        heading.tags = Some(vec![Tag::Loc(reserved_id)]);
+                        break;
                     }
-                } else {
-                    break;
                 }
+                // inline tasks inherit tags without starting a section
+                Expr::InlineTask(ref mut task) => {
+                    task.tags
+                        .get_or_insert_with(Vec::new)
+                        .push(Tag::Loc(reserved_id));
+                }
+                _ => {}
             }
 
             section_vec.push(element_id);
@@ -180,9 +219,12 @@ impl<'a> Parseable<'a> for Heading<'a> {
             Self {
                 heading_level,
                 keyword,
+                done,
                 priority,
+                comment,
                 title,
                 tags,
+                planning,
                 children,
                 properties,
             },
@@ -196,8 +238,70 @@ impl<'a> Parseable<'a> for Heading<'a> {
     }
 }
 
+impl<'a> Parser<'a> {
+    /// The effective tags of a heading or inline task, without duplicates.
+    ///
+    /// Starts with the `#+FILETAGS:`, followed by the tags inherited from parent
+    /// headings and then the heading's own tags. Returns nothing for other nodes.
+    pub fn all_tags(&self, node_id: NodeID) -> Vec<&'a str> {
+        self.all_tags_excluding(node_id, &[])
+    }
+
+    /// Like [`Parser::all_tags`], but the tags in `no_inherit` only apply to the
+    /// heading that sets them, as with `org-tags-exclude-from-inheritance`.
+    pub fn all_tags_excluding(&self, node_id: NodeID, no_inherit: &[&str]) -> Vec<&'a str> {
+        if !matches!(
+            self.pool[node_id].obj,
+            Expr::Heading(_) | Expr::InlineTask(_)
+        ) {
+            return Vec::new();
+        }
+
+        let mut ret: Vec<&'a str> = Vec::new();
+        for tag in &self.file_tags {
+            if !no_inherit.contains(tag) && !ret.contains(tag) {
+                ret.push(tag);
+            }
+        }
+        self.collect_tags(node_id, no_inherit, false, &mut ret);
+        ret
+    }
+
+    fn collect_tags(
+        &self,
+        node_id: NodeID,
+        no_inherit: &[&str],
+        inherited: bool,
+        ret: &mut Vec<&'a str>,
+    ) {
+        let tags = match &self.pool[node_id].obj {
+            Expr::Heading(inner) => &inner.tags,
+            Expr::InlineTask(inner) => &inner.tags,
+            _ => return,
+        };
+        let Some(tags) = tags else {
+            return;
+        };
+
+        for tag in tags {
+            if let Tag::Loc(parent_id) = tag {
+                self.collect_tags(*parent_id, no_inherit, true, ret);
+            }
+        }
+        // raw tags are stored back to front
+        for tag in tags.iter().rev() {
+            if let Tag::Raw(val) = tag
+                && !(inherited && no_inherit.contains(val))
+                && !ret.contains(val)
+            {
+                ret.push(val);
+            }
+        }
+    }
+}
+
 impl<'a> Heading<'a> {
-    fn parse_stars(cursor: Cursor) -> Result<Match<HeadingLevel>> {
+    pub(super) fn parse_stars(cursor: Cursor) -> Result<Match<HeadingLevel>> {
         let ret = cursor.fn_while(|chr: u8| chr == STAR)?;
 
         if cursor[ret.end] != SPACE {
@@ -213,29 +317,23 @@ impl<'a> Heading<'a> {
         }
     }
 
-    fn parse_keyword(mut cursor: Cursor<'_>) -> Result<Match<&str>> {
+    pub(super) fn parse_keyword<'b>(
+        parser: &Parser,
+        mut cursor: Cursor<'b>,
+    ) -> Result<Match<&'b str>> {
         let start = cursor.index;
         cursor.skip_ws();
 
-        for (i, val) in ORG_TODO_KEYWORDS.iter().enumerate() {
-            // TODO: read up to a whitespace and determine if it's in phf set for keywords
-            // this is currently O(n), we can make it O(1)
-            if cursor.word(val).is_ok() {
-                // keep going in if not whitespace
-                // because a keyword might be a subset of another,,,
-                if cursor.try_curr()?.is_ascii_whitespace() {
-                    return Ok(Match {
-                        start,
-                        end: cursor.index, // don't move 1 ahead, in case it's a newline
-                        obj: val,
-                    });
-                } else {
-                    cursor.index -= val.len();
-                }
-            }
+        let word = cursor.fn_until(|chr: u8| chr.is_ascii_whitespace())?;
+        if parser.todo_keywords.get(word.obj).is_some() {
+            Ok(Match {
+                start,
+                end: word.end, // don't move 1 ahead, in case it's a newline
+                obj: word.obj,
+            })
+        } else {
+            Err(MatchError::InvalidLogic)
         }
-
-        Err(MatchError::InvalidLogic)
     }
 
     // Recognizes the following patterns:
@@ -243,7 +341,7 @@ impl<'a> Heading<'a> {
     // [#1]
     // [#12]
     // TODO: we don't respect the 65 thing for numbers
-    fn parse_priority(mut cursor: Cursor) -> Result<Match<Priority>> {
+    pub(super) fn parse_priority(mut cursor: Cursor) -> Result<Match<Priority>> {
         let start = cursor.index;
         cursor.skip_ws();
         // TODO: check if this is true
@@ -283,7 +381,26 @@ impl<'a> Heading<'a> {
         })
     }
 
-    fn parse_tag(mut cursor: Cursor) -> Match<Option<Vec<Tag>>> {
+    // COMMENT, followed by whitespace or EOF
+    pub(super) fn parse_comment(mut cursor: Cursor) -> Result<Match<()>> {
+        let start = cursor.index;
+        cursor.skip_ws();
+        cursor.word("COMMENT")?;
+        if cursor
+            .try_curr()
+            .is_ok_and(|chr| !chr.is_ascii_whitespace())
+        {
+            return Err(MatchError::InvalidLogic);
+        }
+
+        Ok(Match {
+            start,
+            end: cursor.index,
+            obj: (),
+        })
+    }
+
+    pub(super) fn parse_tag(mut cursor: Cursor) -> Match<Option<Vec<Tag>>> {
         // we parse tags backwards
         let start = cursor.index;
         cursor.adv_till_byte(NEWLINE);
@@ -338,7 +455,7 @@ impl<'a> Heading<'a> {
         // we reached the start element, without hitting a space. no tags
     }
 
-    fn parse_title(
+    pub(super) fn parse_title(
         parser: &mut Parser<'a>,
         cursor: Cursor<'a>,
         mut title_end: usize,
@@ -421,9 +538,12 @@ mod tests {
             Heading {
                 heading_level: crate::element::HeadingLevel::One,
                 keyword: None,
+                done: false,
                 priority: None,
+                comment: false,
                 title: None,
                 tags: None,
+                planning: None,
                 properties: None,
                 children: None,
             }
@@ -440,9 +560,12 @@ mod tests {
             Heading {
                 heading_level: crate::element::HeadingLevel::Four,
                 keyword: None,
+                done: false,
                 priority: None,
+                comment: false,
                 title: None,
                 tags: None,
+                planning: None,
                 properties: None,
                 children: None,
             }
@@ -450,12 +573,11 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn headline_too_many_stars() {
-        // panics because we'd unwrap on the case of no headings
+    fn headline_many_stars() {
         let input = "*********  \n";
 
         let head = get_head(input);
+        assert_eq!(head.heading_level, HeadingLevel::Deep(9));
     }
 
     #[test]
@@ -467,12 +589,15 @@ mod tests {
             Heading {
                 heading_level: HeadingLevel::One,
                 keyword: None,
+                done: false,
                 priority: None,
+                comment: false,
                 title: Some((
                     "title                                                \n",
                     vec![make_node_id(2)]
                 )),
                 tags: None,
+                planning: None,
                 properties: None,
                 children: None
             }
@@ -489,9 +614,12 @@ mod tests {
             Heading {
                 heading_level: crate::element::HeadingLevel::One,
                 keyword: Some("TODO"),
+                done: false,
                 priority: None,
+                comment: false,
                 title: None,
                 tags: None,
+                planning: None,
                 properties: None,
                 children: None,
             }
@@ -508,9 +636,12 @@ mod tests {
             Heading {
                 heading_level: crate::element::HeadingLevel::One,
                 keyword: None,
+                done: false,
                 priority: Some(crate::element::Priority::A),
+                comment: false,
                 title: None,
                 tags: None,
+                planning: None,
                 properties: None,
                 children: None,
             }
@@ -527,9 +658,12 @@ mod tests {
             Heading {
                 heading_level: crate::element::HeadingLevel::One,
                 keyword: None,
+                done: false,
                 priority: None,
+                comment: false,
                 title: Some(("cat", vec![make_node_id(2)])),
                 tags: Some(vec![Tag::Raw("tagone")]),
+                planning: None,
                 properties: None,
                 children: None,
             }
@@ -546,9 +680,12 @@ mod tests {
             Heading {
                 heading_level: crate::element::HeadingLevel::One,
                 keyword: None,
+                done: false,
                 priority: None,
+                comment: false,
                 title: Some(("test", vec![make_node_id(2)])),
                 tags: Some(vec![Tag::Raw("tagtwo"), Tag::Raw("tagone")]),
+                planning: None,
                 properties: None,
                 children: None,
             }
@@ -585,9 +722,12 @@ mod tests {
             Heading {
                 heading_level: crate::element::HeadingLevel::One,
                 keyword: Some("TODO"),
+                done: false,
                 priority: Some(crate::element::Priority::A),
+                comment: false,
                 title: None,
                 tags: None,
+                planning: None,
                 properties: None,
                 children: None,
             }
@@ -694,12 +834,118 @@ aaaa";
             &Heading {
                 heading_level: HeadingLevel::Three,
                 keyword: None,
+                done: false,
                 priority: None,
+                comment: false,
                 title: Some(("g", vec![make_node_id(2)])),
                 tags: None,
+                planning: None,
                 properties: None,
                 children: None
             }
         );
     }
+
+    #[test]
+    fn deep_headings() {
+        let input = r"****** six
+******* seven
+********** ten
+";
+        let p = parse_org(input);
+        let levels: Vec<HeadingLevel> = p
+            .pool
+            .iter()
+            .filter_map(|node| match &node.obj {
+                Expr::Heading(heading) => Some(heading.heading_level),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            levels,
+            [
+                HeadingLevel::Six,
+                HeadingLevel::Deep(7),
+                HeadingLevel::Deep(10)
+            ]
+        );
+        assert_eq!(u8::from(levels[2]), 10);
+        assert!(expr_in_pool!(p, Paragraph).is_none());
+
+        // deeper headings are children
+        let six = expr_in_pool!(p, Heading).unwrap();
+        assert_eq!(six.children.as_ref().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn headline_comment() {
+        let head = get_head("* TODO [#B] COMMENT private notes :tag:\n");
+        assert!(head.comment);
+        assert_eq!(head.keyword, Some("TODO"));
+        assert_eq!(head.title.as_ref().unwrap().0, "private notes");
+
+        let head = get_head("* COMMENT\n");
+        assert!(head.comment);
+        assert_eq!(head.title, None);
+
+        for input in [
+            "* COMMENTARY\n",
+            "* comment lowercase\n",
+            "* notes COMMENT\n",
+        ] {
+            assert!(!get_head(input).comment, "{input}");
+        }
+    }
+
+    #[test]
+    fn all_tags() {
+        let input = r"#+FILETAGS: :file:shared:
+* Project :project:shared:
+** Task :urgent:
+*** Subtask :file:
+*************** call :phone:
+* Other
+";
+        let p = parse_org(input);
+        let tags_of = |title: &str, no_inherit: &[&str]| {
+            let node_id = p
+                .pool
+                .iter()
+                .zip(0..)
+                .find_map(|(node, id)| match &node.obj {
+                    Expr::Heading(heading) if heading.title.as_ref().unwrap().0.trim() == title => {
+                        Some(make_node_id(id))
+                    }
+                    Expr::InlineTask(task) if task.title.as_ref().unwrap().0.trim() == title => {
+                        Some(make_node_id(id))
+                    }
+                    _ => None,
+                })
+                .unwrap();
+            p.all_tags_excluding(node_id, no_inherit)
+        };
+
+        assert_eq!(p.file_tags, ["file", "shared"]);
+        assert_eq!(tags_of("Other", &[]), ["file", "shared"]);
+        assert_eq!(
+            tags_of("Subtask", &[]),
+            ["file", "shared", "project", "urgent"]
+        );
+        assert_eq!(
+            tags_of("call", &[]),
+            ["file", "shared", "project", "urgent", "phone"]
+        );
+        // excluded tags are only kept where they are set
+        assert_eq!(tags_of("Task", &["project", "file"]), ["shared", "urgent"]);
+        assert_eq!(
+            tags_of("Subtask", &["file"]),
+            ["shared", "project", "urgent", "file"]
+        );
+        assert_eq!(
+            tags_of("Project", &["project"]),
+            ["file", "shared", "project"]
+        );
+        assert!(p.all_tags(p.pool.root_id()).is_empty());
+    }
 }
@@ -0,0 +1,223 @@
+use crate::constants::{NEWLINE, SPACE, STAR};
+use crate::node_pool::NodeID;
+use crate::parse::parse_element;
+use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result};
+use crate::utils::Match;
+
+use super::{Heading, HeadingLevel, Priority, Tag};
+
+/// A task nested in a section without starting a new one.
+///
+/// ```text
+/// * Heading
+/// *************** TODO inline task :tag:
+/// optional body
+/// *************** END
+/// still in Heading's section
+/// ```
+///
+/// Any heading with at least [`InlineTask::MIN_LEVEL`] stars is an inline task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineTask<'a> {
+    pub heading_level: HeadingLevel,
+    pub keyword: Option<&'a str>,
+    /// Whether `keyword` is a done state, see [`TodoKeywords`](super::TodoKeywords).
+    pub done: bool,
+    pub priority: Option<Priority>,
+    /// Whether the task is commented out with `COMMENT`.
+    pub comment: bool,
+    pub title: Option<(&'a str, Vec<NodeID>)>,
+    pub tags: Option<Vec<Tag<'a>>>,
+    /// The elements before the closing `END` line, `None` when there is no `END` line.
+    pub children: Option<Vec<NodeID>>,
+}
+
+impl InlineTask<'_> {
+    /// The number of stars from which a heading becomes an inline task.
+    pub const MIN_LEVEL: u8 = 15;
+}
+
+impl<'a> Parseable<'a> for InlineTask<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+
+        let stars = Heading::parse_stars(cursor)?;
+        let heading_level = stars.obj;
+        if u8::from(heading_level) < Self::MIN_LEVEL {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.move_to(stars.end);
+
+        let reserved_id = parser.pool.reserve_id();
+
+        let keyword = if let Ok(keyword_match) = Heading::parse_keyword(parser, cursor) {
+            cursor.move_to(keyword_match.end);
+            Some(keyword_match.obj)
+        } else {
+            None
+        };
+        let done = keyword.is_some_and(|keyword| parser.todo_keywords.is_done(keyword));
+
+        let priority = if let Ok(prio_match) = Heading::parse_priority(cursor) {
+            cursor.move_to(prio_match.end);
+            Some(prio_match.obj)
+        } else {
+            None
+        };
+
+        let comment = if let Ok(comment_match) = Heading::parse_comment(cursor) {
+            cursor.move_to(comment_match.end);
+            true
+        } else {
+            false
+        };
+
+        let tag_match = Heading::parse_tag(cursor);
+        let tags = tag_match.obj;
+
+        let (title, target) = Heading::parse_title(
+            parser,
+            cursor,
+            tag_match.start,
+            reserved_id,
+            ParseOpts::default(),
+        )
+        .unwrap_or((None, None));
+
+        cursor.move_to(tag_match.end.min(cursor.len()));
+
+        let children = if let Some(end_line) = find_end(cursor) {
+            let mut children: Vec<NodeID> = Vec::new();
+            let mut temp_cursor = cursor.cut_off(end_line.start);
+            while let Ok(element_id) =
+                parse_element(parser, temp_cursor, Some(reserved_id), ParseOpts::default())
+            {
+                children.push(element_id);
+                temp_cursor.index = parser.pool[element_id].end;
+            }
+            cursor.move_to(end_line.end);
+            Some(children)
+        } else {
+            None
+        };
+
+        let ret_id = parser.alloc_with_id(
+            Self {
+                heading_level,
+                keyword,
+                done,
+                priority,
+                comment,
+                title,
+                tags,
+                children,
+            },
+            start,
+            cursor.index,
+            parent,
+            reserved_id,
+        );
+        parser.pool[ret_id].id_target = target;
+        Ok(ret_id)
+    }
+}
+
+/// Finds the `END` line closing the task, which must come before any other
+/// heading or inline task.
+fn find_end(mut cursor: Cursor) -> Option<Match<()>> {
+    while cursor.curr_valid().is_ok() {
+        let line_start = cursor.index;
+        cursor.adv_till_byte(NEWLINE);
+        let line = cursor.clamp_backwards(line_start);
+        cursor.next();
+
+        let rest = line.trim_start_matches(STAR as char);
+        let num_stars = line.len() - rest.len();
+        if num_stars > 0 && rest.starts_with(SPACE as char) {
+            return (num_stars >= InlineTask::MIN_LEVEL.into() && rest.trim() == "END").then_some(
+                Match {
+                    start: line_start,
+                    end: cursor.index.min(cursor.len()),
+                    obj: (),
+                },
+            );
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::element::HeadingLevel;
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
+
+    #[test]
+    fn inline_task_with_body() {
+        let input = r"* Heading
+*************** TODO [#A] call back :phone:
+body text
+*************** END
+still in the section
+";
+        let parsed = parse_org(input);
+        let task = expr_in_pool!(parsed, InlineTask).unwrap();
+
+        assert_eq!(task.heading_level, HeadingLevel::Deep(15));
+        assert_eq!(task.keyword, Some("TODO"));
+        assert_eq!(task.title.as_ref().unwrap().0, "call back");
+        assert_eq!(task.children.as_ref().map(Vec::len), Some(1));
+
+        // the paragraph after the task belongs to the heading
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+        let section = heading.children.as_ref().unwrap();
+        assert_eq!(section.len(), 2);
+        assert!(matches!(parsed.pool[section[0]].obj, Expr::InlineTask(_)));
+        assert!(matches!(parsed.pool[section[1]].obj, Expr::Paragraph(_)));
+    }
+
+    #[test]
+    fn inline_task_without_end() {
+        let input = r"* Heading
+**************** DONE quick note
+text after
+*************** another
+** Child
+*************** END
+";
+        let parsed = parse_org(input);
+        let tasks: Vec<(&str, bool)> = parsed
+            .pool
+            .iter()
+            .filter_map(|node| match &node.obj {
+                Expr::InlineTask(task) => Some((
+                    task.title.as_ref().unwrap().0.trim(),
+                    task.children.is_some(),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        // END after a heading doesn't close the earlier tasks
+        assert_eq!(
+            tasks,
+            [("quick note", false), ("another", false), ("END", false)]
+        );
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+        assert_eq!(heading.children.as_ref().map(Vec::len), Some(4));
+    }
+
+    #[test]
+    fn fourteen_stars_is_a_heading() {
+        let parsed = parse_org("************** deep\n");
+        assert!(expr_in_pool!(parsed, InlineTask).is_none());
+        assert!(expr_in_pool!(parsed, Heading).is_some());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::constants::{DOLLAR, HYPHEN, NEWLINE, UNDERSCORE};
 use crate::node_pool::NodeID;
+use crate::object::add_property;
 use crate::parse::parse_element;
 use crate::types::{Cursor, Expr, MatchError, ParseOpts, Parseable, Parser, Result, process_attrs};
 use crate::utils::{Match, bytes_to_str};
@@ -21,6 +22,19 @@ pub enum Affiliated<'a> {
         backend: &'a str,
         val: &'a str,
     },
+    /// `#+RESULTS:` of a source block or babel call.
+    ///
+    /// `name` is set for results that belong to a named block
+    /// elsewhere in the document: `#+RESULTS: name`.
+    Results {
+        child_id: Option<NodeID>,
+        name: Option<&'a str>,
+    },
+    /// `#+HEADER:` arguments of the source block that follows.
+    Header {
+        child_id: Option<NodeID>,
+        val: &'a str,
+    },
 }
 
 impl<'a> Parseable<'a> for Keyword<'a> {
@@ -119,6 +133,7 @@ impl<'a> Parseable<'a> for Keyword<'a> {
                             cursor.index = node.end;
                         } else {
                             parser.pool[child_id].id_target = Some(parser.generate_target(val));
+                            parser.names.insert(val, child_id);
                             break Some(child_id);
                         }
                     } else {
@@ -129,6 +144,72 @@ impl<'a> Parseable<'a> for Keyword<'a> {
 
                 return Ok(ret_id);
             }
+            "header" | "headers" => {
+                let val = cursor.fn_until(|chr: u8| chr == NEWLINE)?;
+                cursor.index = val.end + 1;
+                let end_index = cursor.index;
+
+                let child_id = loop {
+                    if let Ok(child_id) = parse_element(parser, cursor, parent, parse_opts) {
+                        if let Expr::Affiliated(_) = &parser.pool[child_id].obj {
+                            // skip affiliated objects
+                            cursor.index = parser.pool[child_id].end;
+                        } else {
+                            break Some(child_id);
+                        }
+                    } else {
+                        break None;
+                    }
+                };
+                if let Some(child_id) = child_id {
+                    // the #+HEADER: lines below this one were parsed first
+                    parser
+                        .block_headers
+                        .entry(child_id)
+                        .or_default()
+                        .insert(0, val.obj.trim());
+                }
+
+                return Ok(parser.alloc(
+                    Affiliated::Header {
+                        child_id,
+                        val: val.obj.trim(),
+                    },
+                    start,
+                    end_index,
+                    parent,
+                ));
+            }
+            // #+RESULTS[hash]: name
+            key if key == "results" || key.starts_with("results[") => {
+                let val = cursor.fn_until(|chr: u8| chr == NEWLINE)?;
+                let name = val.obj.trim();
+                cursor.index = val.end + 1;
+                let end_index = cursor.index;
+
+                let child_id = loop {
+                    if let Ok(child_id) = parse_element(parser, cursor, parent, parse_opts) {
+                        match &parser.pool[child_id].obj {
+                            Expr::Affiliated(_) => cursor.index = parser.pool[child_id].end,
+                            // empty results
+                            Expr::BlankLine => break None,
+                            _ => break Some(child_id),
+                        }
+                    } else {
+                        break None;
+                    }
+                };
+
+                return Ok(parser.alloc(
+                    Affiliated::Results {
+                        child_id,
+                        name: (!name.is_empty()).then_some(name),
+                    },
+                    start,
+                    end_index,
+                    parent,
+                ));
+            }
             "caption" => {
                 let val = cursor.fn_until(|chr: u8| chr == b'\n')?;
                 let caption_id = parser.pool.reserve_id();
@@ -202,6 +283,22 @@ impl<'a> Parseable<'a> for Keyword<'a> {
         let val = cursor.fn_until(|chr: u8| chr == b'\n')?;
         let trimmed = val.obj.trim_ascii();
 
+        if key_word.obj.eq_ignore_ascii_case("tags") {
+            parser.tag_config.parse_line(trimmed);
+        } else if key_word.obj.eq_ignore_ascii_case("property") {
+            // #+PROPERTY: name value
+            let (name, val) = trimmed
+                .split_once(|chr: char| chr.is_ascii_whitespace())
+                .unwrap_or((trimmed, ""));
+            add_property(&mut parser.properties, name, val.trim());
+        } else if key_word.obj.eq_ignore_ascii_case("filetags") {
+            // :tag1:tag2:, also accepting tags separated by spaces
+            parser.file_tags.extend(
+                trimmed
+                    .split(|chr: char| chr == ':' || chr.is_ascii_whitespace())
+                    .filter(|tag| !tag.is_empty()),
+            );
+        }
         parser.keywords.insert(key_word.obj, trimmed);
         Ok(parser.alloc(
             Keyword {
@@ -462,4 +559,18 @@ mod tests {
             parse_org(x);
         });
     }
+
+    #[test]
+    fn property_keyword() {
+        let input = r"#+PROPERTY: header-args :results silent
+#+property: header-args+ :exports both
+#+PROPERTY: author
+";
+        let parsed = parse_org(input);
+        assert_eq!(
+            parsed.properties.get("header-args").unwrap(),
+            ":results silent :exports both"
+        );
+        assert_eq!(parsed.properties.get("author").unwrap(), "");
+    }
 }
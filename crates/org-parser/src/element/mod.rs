@@ -3,28 +3,51 @@
 //! Elements are typically larger and comprise broader sections of text than objects.
 //! They include structues such as: [`Heading`], [`PlainList`], etc...
 
+mod babel_call;
 mod block;
+mod clock;
 mod comment;
 mod drawer;
+mod dynamic_block;
+mod fixed_width;
 mod footnote_def;
+mod header_args;
 mod heading;
+mod inline_task;
 mod item;
 mod keyword;
 mod latex_env;
 mod paragraph;
 mod plain_list;
+mod planning;
 mod table;
+mod tag_config;
+mod todo_keywords;
 
-pub use block::Block;
+pub use babel_call::BabelCall;
+pub use babel_call::CallArgument;
+pub(crate) use babel_call::{
+    header_arg_pairs, link_results, parse_arguments, parse_call_name, parse_delimited,
+};
+pub use block::{Block, Coderef, Switches};
+pub use clock::Clock;
 pub use comment::Comment;
 pub use drawer::Drawer;
 pub use drawer::PropertyDrawer;
+pub use drawer::StateChange;
 pub(crate) use drawer::parse_property;
+pub use dynamic_block::DynamicBlock;
+pub use fixed_width::FixedWidth;
 pub use footnote_def::FootnoteDef;
+pub use header_args::{
+    Collection, Exports, Handling, HeaderArgs, Noweb, ResultsArgs, ResultsFormat, ResultsKind,
+    Tangle, language_defaults,
+};
 pub use heading::Heading;
 pub use heading::HeadingLevel;
 pub use heading::Priority;
 pub use heading::Tag;
+pub use inline_task::InlineTask;
 pub use item::BulletKind;
 pub use item::CheckBox;
 pub use item::CounterKind;
@@ -37,5 +60,10 @@ pub use latex_env::LatexEnv;
 pub use paragraph::Paragraph;
 pub use plain_list::ListKind;
 pub use plain_list::PlainList;
+pub use planning::Planning;
+pub(crate) use planning::parse_planning;
 pub use table::Table;
 pub use table::TableRow;
+pub use table::{Alignment, ColumnFormat, ColumnGroup};
+pub use tag_config::{TagConfig, TagConflict, TagDef, TagGroup};
+pub use todo_keywords::{TodoKeyword, TodoKeywords, TodoLog, TodoSequence, TodoSequenceKind};
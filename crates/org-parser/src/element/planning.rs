@@ -0,0 +1,182 @@
+use std::fmt::Display;
+
+use crate::constants::NEWLINE;
+use crate::object::{Timestamp, parse_timestamp};
+use crate::types::{Cursor, MatchError, Result};
+use crate::utils::Match;
+
+/// Planning information attached to a [`Heading`](super::Heading).
+///
+/// Must appear on the line directly after the headline:
+///
+/// ```text
+/// * TODO task
+/// SCHEDULED: <2024-03-01 Fri> DEADLINE: <2024-03-08 Fri -2d>
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Planning<'a> {
+    pub scheduled: Option<Timestamp<'a>>,
+    pub deadline: Option<Timestamp<'a>>,
+    pub closed: Option<Timestamp<'a>>,
+    /// The planning line as written, without surrounding whitespace.
+    pub raw: &'a str,
+}
+
+pub(crate) fn parse_planning(mut cursor: Cursor) -> Result<Match<Planning>> {
+    cursor.curr_valid()?;
+    let start = cursor.index;
+    let mut planning = Planning::default();
+
+    loop {
+        cursor.skip_ws();
+        match cursor.try_curr() {
+            Ok(NEWLINE) => {
+                cursor.next();
+                break;
+            }
+            // planning line at the end of the file
+            Err(_) => break,
+            Ok(_) => {}
+        }
+
+        let slot = if cursor.word("SCHEDULED:").is_ok() {
+            &mut planning.scheduled
+        } else if cursor.word("DEADLINE:").is_ok() {
+            &mut planning.deadline
+        } else if cursor.word("CLOSED:").is_ok() {
+            &mut planning.closed
+        } else {
+            return Err(MatchError::InvalidLogic);
+        };
+
+        cursor.skip_ws();
+        let ts_match = parse_timestamp(cursor)?;
+        cursor.index = ts_match.end;
+        *slot = Some(ts_match.obj);
+    }
+
+    if planning == Planning::default() {
+        return Err(MatchError::InvalidLogic);
+    }
+    planning.raw = cursor.clamp_backwards(start).trim();
+
+    Ok(Match {
+        start,
+        end: cursor.index,
+        obj: planning,
+    })
+}
+
+impl Display for Planning<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut sep = "";
+        for (name, ts) in [
+            ("SCHEDULED", &self.scheduled),
+            ("DEADLINE", &self.deadline),
+            ("CLOSED", &self.closed),
+        ] {
+            if let Some(ts) = ts {
+                write!(f, "{sep}{name}: {ts}")?;
+                sep = " ";
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::expr_in_pool;
+    use crate::object::{Date, DateTime};
+    use crate::parse_org;
+    use crate::types::Expr;
+
+    #[test]
+    fn basic_planning() {
+        let input = r"* TODO task
+SCHEDULED: <2024-03-01 Fri> DEADLINE: <2024-03-08 Fri -2d>
+";
+        let parsed = parse_org(input);
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+        let planning = heading.planning.as_ref().unwrap();
+
+        assert_eq!(
+            planning.scheduled.as_ref().unwrap().start,
+            DateTime {
+                date: Date {
+                    year: 2024,
+                    month: 3,
+                    day: 1,
+                    dayname: Some("Fri"),
+                },
+                time: None,
+            }
+        );
+        assert_eq!(planning.deadline.as_ref().unwrap().start.date.day, 8);
+        assert!(planning.closed.is_none());
+        assert!(heading.children.is_none());
+    }
+
+    #[test]
+    fn planning_before_properties() {
+        let input = r"* DONE task
+  CLOSED: [2024-03-02 Sat 10:12]
+:properties:
+:id: abc
+:end:
+text
+";
+        let parsed = parse_org(input);
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+
+        assert!(heading.planning.as_ref().unwrap().closed.is_some());
+        assert_eq!(heading.properties.as_ref().unwrap()["id"], "abc");
+        assert!(heading.children.is_some());
+    }
+
+    #[test]
+    fn planning_at_eof() {
+        let input = "* task\nDEADLINE: <2024-03-08 Fri>";
+        let parsed = parse_org(input);
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+
+        assert!(heading.planning.as_ref().unwrap().deadline.is_some());
+    }
+
+    #[test]
+    fn not_planning() {
+        // trailing text disqualifies the line
+        let input = r"* task
+SCHEDULED: <2024-03-01 Fri> and more
+";
+        let parsed = parse_org(input);
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+        assert!(heading.planning.is_none());
+        assert!(heading.children.is_some());
+
+        // must directly follow the headline
+        let input = r"* task
+
+SCHEDULED: <2024-03-01 Fri>
+";
+        let parsed = parse_org(input);
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+        assert!(heading.planning.is_none());
+    }
+
+    #[test]
+    fn planning_display() {
+        let input = r"* task
+CLOSED: [2024-03-02 Sat] SCHEDULED: <2024-03-01 Fri .+1d>
+";
+        let parsed = parse_org(input);
+        let heading = expr_in_pool!(parsed, Heading).unwrap();
+
+        assert_eq!(
+            heading.planning.as_ref().unwrap().to_string(),
+            "SCHEDULED: <2024-03-01 Fri .+1d> CLOSED: [2024-03-02 Sat]"
+        );
+    }
+}
@@ -1,18 +1,31 @@
-use crate::constants::{HYPHEN, NEWLINE};
-use crate::node_pool::NodeID;
+use std::fmt;
+
+use crate::constants::{COLON, HYPHEN, NEWLINE};
+use crate::node_pool::{NodeID, NodePool};
 use crate::object::TableCell;
-use crate::types::{Cursor, Expr, MarkupKind, ParseOpts, Parseable, Parser, Result};
+use crate::types::{Cursor, Expr, MarkupKind, MatchError, ParseOpts, Parseable, Parser, Result};
+use crate::utils::Match;
 
 /// A table consisting of a collection of [`TableRow`]s
 ///
 /// | one | two |
 /// | three | four |
 #[derive(Debug, Clone)]
-pub struct Table {
+pub struct Table<'a> {
     pub rows: usize,
     pub cols: usize,
     pub children: Vec<NodeID>,
     pub caption: Option<NodeID>, // will be filled by parent caption if exists
+    /// The `#+TBLFM:` lines directly below the table.
+    ///
+    /// Only the first line is used when recalculating the table.
+    pub formulas: Vec<&'a str>,
+    /// The alignment and width of every column, taken from the first cookie
+    /// found in it.
+    pub columns: Vec<ColumnFormat>,
+    /// The column group of every column, empty when the table has no
+    /// column group row.
+    pub column_groups: Vec<Option<ColumnGroup>>,
 }
 
 /// A row of a [`Table`] consisting of [`TableCell`]s or a [`TableRow::Rule`].
@@ -32,13 +45,51 @@ pub struct Table {
 /// TableRow::Rule
 /// TableRow::Standard(TableCell, TableCell)
 /// ```
+///
+/// Rows made up only of alignment cookies or column groups are special rows,
+/// they describe the table instead of holding data:
+///
+/// ```text
+/// |     | <r10> | <c> |
+/// | /   | <     | >   |
+/// ```
 #[derive(Debug, Clone)]
 pub enum TableRow {
     Rule, // hrule
     Standard(Vec<NodeID>),
+    /// `| <l> | <r10> |`, one entry per field.
+    Cookies(Vec<ColumnFormat>),
+    /// `| / | < | <> | > |`, one entry per field, the first field being the `/`.
+    ColumnGroups(Vec<Option<ColumnGroup>>),
+}
+
+/// An alignment cookie: `<l>`, `<r10>`, `<10>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnFormat {
+    pub align: Option<Alignment>,
+    /// The width the column is shrunk to.
+    pub width: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
 }
 
-impl<'a> Parseable<'a> for Table {
+/// Where a column sits in its column group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnGroup {
+    /// `<`, starts a group.
+    Start,
+    /// `>`, ends a group.
+    End,
+    /// `<>`, a group by itself.
+    Single,
+}
+
+impl<'a> Parseable<'a> for Table<'a> {
     fn parse(
         parser: &mut Parser<'a>,
         mut cursor: Cursor<'a>,
@@ -53,24 +104,65 @@ impl<'a> Parseable<'a> for Table {
         let mut children: Vec<NodeID> = Vec::new();
         let mut rows = 0;
         let mut cols = 0;
+        let mut columns: Vec<ColumnFormat> = Vec::new();
+        let mut column_groups: Vec<Option<ColumnGroup>> = Vec::new();
         while let Ok(row_id) = TableRow::parse(parser, cursor, Some(reserve_id), parse_opts) {
             let obj = &parser.pool[row_id];
 
             children.push(row_id);
             rows += 1;
-            if let Expr::TableRow(TableRow::Standard(node_ids)) = &obj.obj {
-                cols = cols.max(node_ids.len());
+            match &obj.obj {
+                Expr::TableRow(TableRow::Standard(node_ids)) => {
+                    cols = cols.max(node_ids.len());
+                }
+                Expr::TableRow(TableRow::Cookies(cookies)) => {
+                    cols = cols.max(cookies.len());
+                    if columns.len() < cookies.len() {
+                        columns.resize(cookies.len(), ColumnFormat::default());
+                    }
+                    for (column, cookie) in columns.iter_mut().zip(cookies) {
+                        if *column == ColumnFormat::default() {
+                            *column = *cookie;
+                        }
+                    }
+                }
+                Expr::TableRow(TableRow::ColumnGroups(groups)) => {
+                    cols = cols.max(groups.len());
+                    if column_groups.len() < groups.len() {
+                        column_groups.resize(groups.len(), None);
+                    }
+                    for (column, group) in column_groups.iter_mut().zip(groups) {
+                        if column.is_none() {
+                            *column = *group;
+                        }
+                    }
+                }
+                _ => {}
             }
 
             cursor.index = parser.pool[row_id].end;
         }
 
+        let mut formulas: Vec<&str> = Vec::new();
+        while let Ok(formula) = parse_tblfm(cursor) {
+            formulas.push(formula.obj);
+            cursor.index = formula.end;
+        }
+
+        columns.resize(cols, ColumnFormat::default());
+        if !column_groups.is_empty() {
+            column_groups.resize(cols, None);
+        }
+
         Ok(parser.alloc_with_id(
             Self {
                 rows,
                 cols,
                 children,
                 caption: None,
+                formulas,
+                columns,
+                column_groups,
             },
             start,
             cursor.index,
@@ -114,6 +206,18 @@ impl<'a> Parseable<'a> for TableRow {
                 .alloc(Self::Rule, start, cursor.index + 1, parent));
         }
 
+        let line_start = cursor.index;
+        let mut line_end = cursor;
+        line_end.adv_till_byte(NEWLINE);
+        if let Some(row) = Self::parse_special(line_end.clamp_backwards(line_start)) {
+            return Ok(parser.pool.alloc(
+                row,
+                start,
+                (line_end.index + 1).min(cursor.len()),
+                parent,
+            ));
+        }
+
         let mut children: Vec<NodeID> = Vec::new();
         while let Ok(table_cell_id) = TableCell::parse(parser, cursor, parent, parse_opts) {
             let node_item = &parser.pool[table_cell_id];
@@ -136,8 +240,184 @@ impl<'a> Parseable<'a> for TableRow {
     }
 }
 
+impl Table<'_> {
+    /// The cells of every row holding data, leaving out horizontal rules and
+    /// rows of alignment cookies or column groups.
+    ///
+    /// Every row is [`Table::cols`] long, with `None` for missing cells.
+    pub fn grid(&self, pool: &NodePool) -> Vec<Vec<Option<NodeID>>> {
+        self.children
+            .iter()
+            .filter_map(|id| match &pool[*id].obj {
+                Expr::TableRow(TableRow::Standard(cells)) => {
+                    let mut row: Vec<Option<NodeID>> = cells.iter().copied().map(Some).collect();
+                    row.resize(self.cols, None);
+                    Some(row)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The leading rows of [`Table::grid`] that make up the header.
+    ///
+    /// These are the rows above the first horizontal rule, provided there are
+    /// more rows below it. Rules at the top of the table are ignored:
+    ///
+    /// ```text
+    /// | name | age |
+    /// |------+-----|
+    /// | bob  | 30  |
+    /// ```
+    pub fn header_rows(&self, pool: &NodePool) -> Vec<Vec<Option<NodeID>>> {
+        let mut grid = self.grid(pool);
+        let mut header_len = 0;
+        let mut found_rule = false;
+        for id in &self.children {
+            match &pool[*id].obj {
+                Expr::TableRow(TableRow::Standard(_)) if found_rule => {
+                    grid.truncate(header_len);
+                    return grid;
+                }
+                Expr::TableRow(TableRow::Standard(_)) => header_len += 1,
+                // a rule above every row doesn't end the header
+                Expr::TableRow(TableRow::Rule) => found_rule = header_len > 0,
+                _ => {}
+            }
+        }
+        Vec::new()
+    }
+}
+
+impl TableRow {
+    /// Recognises rows of alignment cookies and column groups.
+    ///
+    /// `line` is the text following the opening `|`.
+    fn parse_special(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        let fields: Vec<&str> = line
+            .strip_suffix('|')
+            .unwrap_or(line)
+            .split('|')
+            .map(str::trim)
+            .collect();
+
+        if fields.first() == Some(&"/") {
+            let mut groups = vec![None];
+            for field in &fields[1..] {
+                groups.push(match *field {
+                    "" => None,
+                    "<" => Some(ColumnGroup::Start),
+                    ">" => Some(ColumnGroup::End),
+                    "<>" => Some(ColumnGroup::Single),
+                    _ => return None,
+                });
+            }
+            return Some(Self::ColumnGroups(groups));
+        }
+
+        let cookies = fields
+            .iter()
+            .map(|field| {
+                if field.is_empty() {
+                    Some(ColumnFormat::default())
+                } else {
+                    ColumnFormat::parse(field)
+                }
+            })
+            .collect::<Option<Vec<ColumnFormat>>>()?;
+        if cookies
+            .iter()
+            .all(|cookie| *cookie == ColumnFormat::default())
+        {
+            None
+        } else {
+            Some(Self::Cookies(cookies))
+        }
+    }
+}
+
+impl ColumnFormat {
+    // <l>, <r10>, <10>
+    fn parse(field: &str) -> Option<Self> {
+        let inner = field.strip_prefix('<')?.strip_suffix('>')?;
+        let (align, width) = match inner.as_bytes().first() {
+            Some(b'l') => (Some(Alignment::Left), &inner[1..]),
+            Some(b'r') => (Some(Alignment::Right), &inner[1..]),
+            Some(b'c') => (Some(Alignment::Center), &inner[1..]),
+            _ => (None, inner),
+        };
+        let width = if width.is_empty() {
+            None
+        } else if width.bytes().all(|byte| byte.is_ascii_digit()) {
+            Some(width.parse().ok()?)
+        } else {
+            return None;
+        };
+
+        if align.is_none() && width.is_none() {
+            None
+        } else {
+            Some(Self { align, width })
+        }
+    }
+}
+
+impl fmt::Display for ColumnFormat {
+    /// Writes the cookie back out, nothing for the default format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == Self::default() {
+            return Ok(());
+        }
+        f.write_str("<")?;
+        match self.align {
+            Some(Alignment::Left) => f.write_str("l")?,
+            Some(Alignment::Right) => f.write_str("r")?,
+            Some(Alignment::Center) => f.write_str("c")?,
+            None => {}
+        }
+        if let Some(width) = self.width {
+            write!(f, "{width}")?;
+        }
+        f.write_str(">")
+    }
+}
+
+impl fmt::Display for ColumnGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Start => "<",
+            Self::End => ">",
+            Self::Single => "<>",
+        })
+    }
+}
+
+// #+TBLFM: $3=$1*$2::@>$3=vsum(@I..@II)
+fn parse_tblfm(mut cursor: Cursor<'_>) -> Result<Match<&str>> {
+    cursor.curr_valid()?;
+    cursor.skip_ws();
+    cursor.word("#+")?;
+    let key = cursor.fn_until(|chr: u8| chr == COLON || chr.is_ascii_whitespace())?;
+    if !key.obj.eq_ignore_ascii_case("tblfm") {
+        return Err(MatchError::InvalidLogic);
+    }
+    cursor.index = key.end;
+    cursor.word(":")?;
+
+    let val_start = cursor.index;
+    cursor.adv_till_byte(NEWLINE);
+    Ok(Match {
+        start: val_start,
+        end: (cursor.index + 1).min(cursor.len()),
+        obj: cursor.clamp_backwards(val_start).trim(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{Alignment, ColumnFormat, ColumnGroup, TableRow};
+    use crate::node_pool::NodeID;
     use crate::{Expr, element::Affiliated, expr_in_pool, parse_org};
 
     #[test]
@@ -304,4 +584,160 @@ word
             panic!()
         };
     }
+
+    #[test]
+    fn table_formulas() {
+        let input = r"| 1 | 2 |   |
+|---+---+---|
+| 3 | 4 |   |
+  #+TBLFM: $3=$1+$2::@1$3=0
+#+tblfm: $3=$1*$2
+#+TBLFM: $3=$1-$2
+";
+
+        let parser = parse_org(input);
+        let table = expr_in_pool!(parser, Table).unwrap();
+        assert_eq!(table.rows, 3);
+        assert_eq!(table.formulas, ["$3=$1+$2::@1$3=0", "$3=$1*$2", "$3=$1-$2"]);
+        assert!(expr_in_pool!(parser, Keyword).is_none());
+        assert_eq!(
+            parser.pool[parser.pool.root_id()]
+                .obj
+                .children()
+                .map(Vec::len),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn table_cookies() {
+        let input = r"| <l> | <r10> |  |
+|-----+-------+--|
+| a   | b     |  |
+|     | <c>   |  | <5> |
+| / | < | <> |
+| /   | >     |  |
+";
+
+        let parser = parse_org(input);
+        let table = expr_in_pool!(parser, Table).unwrap();
+        assert_eq!(table.rows, 6);
+        assert_eq!(table.cols, 4);
+        assert_eq!(
+            table.columns,
+            [
+                ColumnFormat {
+                    align: Some(Alignment::Left),
+                    width: None
+                },
+                ColumnFormat {
+                    align: Some(Alignment::Right),
+                    width: Some(10)
+                },
+                ColumnFormat::default(),
+                ColumnFormat {
+                    align: None,
+                    width: Some(5)
+                },
+            ]
+        );
+        assert_eq!(
+            table.column_groups,
+            [
+                None,
+                Some(ColumnGroup::Start),
+                Some(ColumnGroup::Single),
+                None
+            ]
+        );
+
+        let rows: Vec<&Expr> = table
+            .children
+            .iter()
+            .map(|id| &parser.pool[*id].obj)
+            .collect();
+        assert!(
+            matches!(rows[0], Expr::TableRow(TableRow::Cookies(cookies)) if cookies.len() == 3)
+        );
+        assert!(matches!(rows[2], Expr::TableRow(TableRow::Standard(_))));
+        assert!(matches!(
+            rows[5],
+            Expr::TableRow(TableRow::ColumnGroups(groups)) if groups[..] == [None, Some(ColumnGroup::End), None]
+        ));
+    }
+
+    #[test]
+    fn table_not_cookies() {
+        let input = r"| <l> | x |
+|  |  |
+| / | a |
+| <> |
+";
+
+        let parser = parse_org(input);
+        let table = expr_in_pool!(parser, Table).unwrap();
+        assert!(
+            table
+                .children
+                .iter()
+                .all(|id| matches!(parser.pool[*id].obj, Expr::TableRow(TableRow::Standard(_))))
+        );
+        assert!(table.column_groups.is_empty());
+        assert_eq!(table.columns, [ColumnFormat::default(); 2]);
+    }
+
+    #[test]
+    fn table_header() {
+        let input = r"|-
+|a|b|
+|c|
+|--
+|-
+|1|2|
+|3|4|
+|-
+";
+
+        let parser = parse_org(input);
+        let table = expr_in_pool!(parser, Table).unwrap();
+        let cell = |id: Option<NodeID>| {
+            id.map(|id| {
+                let node = &parser.pool[id];
+                parser.source[node.start..node.end].trim_end_matches(['|', '\n'])
+            })
+        };
+
+        let grid: Vec<Vec<Option<&str>>> = table
+            .grid(&parser.pool)
+            .into_iter()
+            .map(|row| row.into_iter().map(cell).collect())
+            .collect();
+        assert_eq!(
+            grid,
+            [
+                [Some("a"), Some("b")],
+                [Some("c"), None],
+                [Some("1"), Some("2")],
+                [Some("3"), Some("4")],
+            ]
+        );
+        assert_eq!(
+            table.header_rows(&parser.pool),
+            table.grid(&parser.pool)[..2]
+        );
+
+        for input in ["|a|\n|b|\n", "|a|\n|-\n", "|-\n|a|\n"] {
+            let parser = parse_org(input);
+            let table = expr_in_pool!(parser, Table).unwrap();
+            assert!(table.header_rows(&parser.pool).is_empty(), "{input}");
+        }
+    }
+
+    #[test]
+    fn table_formula_after_gap() {
+        let parser = parse_org("|1|\n\n#+TBLFM: $1=2\n");
+        let table = expr_in_pool!(parser, Table).unwrap();
+        assert!(table.formulas.is_empty());
+        assert!(expr_in_pool!(parser, Keyword).is_some());
+    }
 }
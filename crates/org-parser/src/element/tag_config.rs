@@ -0,0 +1,307 @@
+use crate::node_pool::NodeID;
+use crate::types::{Expr, Parser};
+
+use super::Tag;
+
+/// The tags declared with `#+TAGS:` lines.
+///
+/// ```text
+/// #+TAGS: laptop(l) { @home(h) @work(w) }
+/// #+TAGS: [ project : alpha beta ]
+/// ```
+///
+/// Tags in braces are mutually exclusive, tags in brackets are not. A tag
+/// followed by a colon inside either becomes a group tag standing for the
+/// tags after it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagConfig<'a> {
+    /// Every declared tag, group tags included, in order of appearance.
+    pub tags: Vec<TagDef<'a>>,
+    pub groups: Vec<TagGroup<'a>>,
+}
+
+/// A declared tag with its optional fast access key: `@work(w)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagDef<'a> {
+    pub name: &'a str,
+    /// The key used to select the tag.
+    pub fast_key: Option<char>,
+}
+
+/// Tags declared together in braces or brackets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagGroup<'a> {
+    /// The group tag, `None` for `{ @home @work }`.
+    pub name: Option<&'a str>,
+    pub members: Vec<&'a str>,
+    /// Whether at most one of the members may be used on a heading.
+    pub exclusive: bool,
+}
+
+/// A heading carrying more than one tag from an exclusive set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagConflict<'a> {
+    pub heading: NodeID,
+    /// The conflicting tags, in the order they were declared.
+    pub tags: Vec<&'a str>,
+}
+
+impl<'a> TagConfig<'a> {
+    /// Adds the declarations of a single `#+TAGS:` line.
+    pub(crate) fn parse_line(&mut self, val: &'a str) {
+        // the group being declared, along with whether we've hit its colon yet
+        let mut group: Option<(TagGroup<'a>, bool)> = None;
+        for word in val.split_ascii_whitespace() {
+            match word {
+                "{" | "[" => {
+                    group = Some((
+                        TagGroup {
+                            name: None,
+                            members: Vec::new(),
+                            exclusive: word == "{",
+                        },
+                        false,
+                    ));
+                }
+                "}" | "]" => {
+                    if let Some((group, _)) = group.take()
+                        && (group.name.is_some() || group.exclusive)
+                    {
+                        self.groups.push(group);
+                    }
+                }
+                ":" => {
+                    if let Some((group, seen_colon)) = &mut group
+                        && !*seen_colon
+                    {
+                        *seen_colon = true;
+                        group.name = group.members.pop();
+                    }
+                }
+                // line break in the tag selection menu
+                "\\n" => {}
+                _ => {
+                    let tag = TagDef::parse(word);
+                    if let Some((group, _)) = &mut group {
+                        group.members.push(tag.name);
+                    }
+                    self.tags.push(tag);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TagDef<'a>> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    /// Every tag a group tag stands for, following nested groups.
+    ///
+    /// Empty when `name` isn't a group tag.
+    pub fn members(&self, name: &str) -> Vec<&'a str> {
+        let mut ret: Vec<&'a str> = Vec::new();
+        let mut pending = vec![name];
+        while let Some(curr) = pending.pop() {
+            for group in &self.groups {
+                if group.name == Some(curr) {
+                    for member in &group.members {
+                        // guards against cycles
+                        if *member != name && !ret.contains(member) {
+                            ret.push(member);
+                            pending.push(member);
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Whether `tag` is `query` or one of the members of `query`.
+    pub fn matches(&self, query: &str, tag: &str) -> bool {
+        tag == query || self.members(query).contains(&tag)
+    }
+}
+
+impl<'a> TagDef<'a> {
+    // NAME or NAME(k)
+    fn parse(word: &'a str) -> Self {
+        if let Some((name, key)) = word.split_once('(')
+            && let Some(key) = key.strip_suffix(')')
+            && let [key] = key.chars().collect::<Vec<char>>()[..]
+        {
+            Self {
+                name,
+                fast_key: Some(key),
+            }
+        } else {
+            Self {
+                name: word,
+                fast_key: None,
+            }
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Whether a heading or inline task has `query` among its tags, see [`Parser::all_tags`].
+    ///
+    /// A group tag matches any of its members.
+    pub fn has_tag(&self, node_id: NodeID, query: &str) -> bool {
+        self.all_tags(node_id)
+            .iter()
+            .any(|tag| self.tag_config.matches(query, tag))
+    }
+
+    /// Finds the headings and inline tasks with more than one tag from an exclusive set.
+    ///
+    /// Only the tags set on the heading itself are checked.
+    pub fn lint_tags(&self) -> Vec<TagConflict<'a>> {
+        let mut ret = Vec::new();
+        self.lint_tags_rec(self.pool.root_id(), &mut ret);
+        ret
+    }
+
+    fn lint_tags_rec(&self, node_id: NodeID, ret: &mut Vec<TagConflict<'a>>) {
+        let obj = &self.pool[node_id].obj;
+        let tags = match obj {
+            Expr::Heading(inner) => inner.tags.as_ref(),
+            Expr::InlineTask(inner) => inner.tags.as_ref(),
+            _ => None,
+        };
+
+        if let Some(tags) = tags {
+            for group in self
+                .tag_config
+                .groups
+                .iter()
+                .filter(|group| group.exclusive)
+            {
+                let used: Vec<&'a str> = group
+                    .members
+                    .iter()
+                    .filter(|member| tags.contains(&Tag::Raw(member)))
+                    .copied()
+                    .collect();
+                if used.len() > 1 {
+                    ret.push(TagConflict {
+                        heading: node_id,
+                        tags: used,
+                    });
+                }
+            }
+        }
+
+        for id in obj.children().into_iter().flatten() {
+            self.lint_tags_rec(*id, ret);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parse_org;
+
+    #[test]
+    fn tag_declarations() {
+        let mut config = TagConfig::default();
+        config.parse_line("laptop(l) { @home(h) @work(w) } \\n [ project : alpha beta ]");
+        config.parse_line("{ context : @car @phone } [ alpha : a1 ] [ plain tags ]");
+
+        assert_eq!(
+            config.get("@home"),
+            Some(&TagDef {
+                name: "@home",
+                fast_key: Some('h')
+            })
+        );
+        assert_eq!(config.get("project").unwrap().fast_key, None);
+        assert_eq!(config.tags.len(), 13);
+        assert_eq!(
+            config.groups,
+            [
+                TagGroup {
+                    name: None,
+                    members: vec!["@home", "@work"],
+                    exclusive: true,
+                },
+                TagGroup {
+                    name: Some("project"),
+                    members: vec!["alpha", "beta"],
+                    exclusive: false,
+                },
+                TagGroup {
+                    name: Some("context"),
+                    members: vec!["@car", "@phone"],
+                    exclusive: true,
+                },
+                TagGroup {
+                    name: Some("alpha"),
+                    members: vec!["a1"],
+                    exclusive: false,
+                },
+            ]
+        );
+
+        assert_eq!(config.members("project"), ["alpha", "beta", "a1"]);
+        assert!(config.members("laptop").is_empty());
+        assert!(config.matches("project", "a1"));
+        assert!(config.matches("beta", "beta"));
+        assert!(!config.matches("alpha", "beta"));
+    }
+
+    #[test]
+    fn group_cycles() {
+        let mut config = TagConfig::default();
+        config.parse_line("[ a : b ] [ b : a c ]");
+        assert_eq!(config.members("a"), ["b", "c"]);
+    }
+
+    #[test]
+    fn group_tag_matching() {
+        let input = r"#+TAGS: [ project : alpha beta ]
+* Alpha :alpha:
+** Task
+* Other :gamma:
+";
+        let parsed = parse_org(input);
+        let root = parsed.pool[parsed.pool.root_id()].obj.children().unwrap();
+        let alpha = root[1];
+        let task = parsed.pool[alpha].obj.children().unwrap()[0];
+
+        assert!(parsed.has_tag(alpha, "project"));
+        assert!(parsed.has_tag(task, "project"));
+        assert!(parsed.has_tag(task, "alpha"));
+        assert!(!parsed.has_tag(root[2], "project"));
+    }
+
+    #[test]
+    fn exclusive_conflicts() {
+        let input = r"#+TAGS: { @home @work @car } [ project : alpha ]
+* Both :@home:@work:
+** Inherited :@car:
+* Fine :@home:alpha:project:
+*************** task :@car:@work:@home:
+";
+        let parsed = parse_org(input);
+        let root = parsed.pool[parsed.pool.root_id()].obj.children().unwrap();
+        let fine = parsed.pool[root[2]].obj.children().unwrap();
+
+        assert_eq!(
+            parsed.lint_tags(),
+            [
+                TagConflict {
+                    heading: root[1],
+                    tags: vec!["@home", "@work"],
+                },
+                TagConflict {
+                    heading: fine[0],
+                    tags: vec!["@home", "@work", "@car"],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use crate::constants::{AT, LPAREN, RPAREN, SLASH};
+
+/// The TODO keywords of a document, collected from its
+/// `#+TODO:`, `#+SEQ_TODO:` and `#+TYP_TODO:` lines.
+///
+/// ```text
+/// #+TODO: TODO(t) NEXT(n) WAITING(w@/!) | DONE(d!) CANCELLED(c@)
+/// ```
+///
+/// Without any such lines, the only keywords are `TODO` and `DONE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoKeywords<'a> {
+    pub sequences: Vec<TodoSequence<'a>>,
+}
+
+/// The keywords of a single `#+TODO:` line.
+///
+/// Keywords after the `|` are done states. Without a `|`, the last keyword
+/// is the only done state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoSequence<'a> {
+    pub kind: TodoSequenceKind,
+    pub todo: Vec<TodoKeyword<'a>>,
+    pub done: Vec<TodoKeyword<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TodoSequenceKind {
+    /// `#+TODO:` or `#+SEQ_TODO:`, states that follow one another.
+    Sequence,
+    /// `#+TYP_TODO:`, states that each stand for a kind of task.
+    Type,
+}
+
+/// A single keyword with its optional fast access settings: `WAITING(w@/!)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TodoKeyword<'a> {
+    pub name: &'a str,
+    /// The key used to select the keyword.
+    pub fast_key: Option<char>,
+    /// What to log when entering the state.
+    pub on_enter: Option<TodoLog>,
+    /// What to log when leaving the state.
+    pub on_leave: Option<TodoLog>,
+}
+
+/// What is recorded when a TODO state changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TodoLog {
+    /// `!`
+    Timestamp,
+    /// `@`
+    Note,
+}
+
+impl Default for TodoKeywords<'_> {
+    fn default() -> Self {
+        let keyword = |name| TodoKeyword {
+            name,
+            fast_key: None,
+            on_enter: None,
+            on_leave: None,
+        };
+        Self {
+            sequences: vec![TodoSequence {
+                kind: TodoSequenceKind::Sequence,
+                todo: vec![keyword("TODO")],
+                done: vec![keyword("DONE")],
+            }],
+        }
+    }
+}
+
+impl<'a> TodoKeywords<'a> {
+    /// Collects the keywords from every TODO line in the source, skipping the
+    /// contents of blocks.
+    ///
+    /// The lines apply to the whole document, including headings before them.
+    pub(crate) fn collect(source: &'a str) -> Self {
+        // the name of the block being skipped: `#+begin_src` until `#+end_src`
+        let mut block: Option<String> = None;
+        let sequences: Vec<TodoSequence> = source
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_start();
+                let starts_with = |prefix: &str| {
+                    line.get(..prefix.len())
+                        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+                };
+                if let Some(name) = &block {
+                    if starts_with(&format!("#+end_{name}")) {
+                        block = None;
+                    }
+                    return None;
+                }
+                if starts_with("#+begin_") {
+                    block = line["#+begin_".len()..]
+                        .split_ascii_whitespace()
+                        .next()
+                        .map(str::to_ascii_lowercase);
+                    return None;
+                }
+
+                let (key, val) = line.strip_prefix("#+")?.split_once(':')?;
+                let kind = match key.to_ascii_lowercase().as_str() {
+                    "todo" | "seq_todo" => TodoSequenceKind::Sequence,
+                    "typ_todo" => TodoSequenceKind::Type,
+                    _ => return None,
+                };
+                TodoSequence::parse(kind, val)
+            })
+            .collect();
+
+        if sequences.is_empty() {
+            Self::default()
+        } else {
+            Self { sequences }
+        }
+    }
+
+    /// Looks up a keyword, returning it along with whether it's a done state.
+    pub fn get(&self, name: &str) -> Option<(&TodoKeyword<'a>, bool)> {
+        self.sequences.iter().find_map(|seq| {
+            seq.todo
+                .iter()
+                .find(|keyword| keyword.name == name)
+                .map(|keyword| (keyword, false))
+                .or_else(|| {
+                    seq.done
+                        .iter()
+                        .find(|keyword| keyword.name == name)
+                        .map(|keyword| (keyword, true))
+                })
+        })
+    }
+
+    pub fn is_done(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|(_, done)| done)
+    }
+}
+
+impl<'a> TodoSequence<'a> {
+    fn parse(kind: TodoSequenceKind, val: &'a str) -> Option<Self> {
+        let mut todo = Vec::new();
+        let mut done = Vec::new();
+        let mut seen_bar = false;
+        for word in val.split_ascii_whitespace() {
+            if word == "|" {
+                seen_bar = true;
+            } else if seen_bar {
+                done.push(TodoKeyword::parse(word));
+            } else {
+                todo.push(TodoKeyword::parse(word));
+            }
+        }
+
+        if !seen_bar {
+            done.extend(todo.pop());
+        }
+        if todo.is_empty() && done.is_empty() {
+            None
+        } else {
+            Some(Self { kind, todo, done })
+        }
+    }
+}
+
+impl<'a> TodoKeyword<'a> {
+    // NAME or NAME(k@/!)
+    fn parse(word: &'a str) -> Self {
+        let mut ret = Self {
+            name: word,
+            fast_key: None,
+            on_enter: None,
+            on_leave: None,
+        };
+        let Some((name, spec)) = word.split_once(LPAREN as char) else {
+            return ret;
+        };
+        let Some(spec) = spec.strip_suffix(RPAREN as char) else {
+            return ret;
+        };
+        ret.name = name;
+
+        let log = |chr: Option<char>| match chr.map(|chr| chr as u8) {
+            Some(AT) => Some(TodoLog::Note),
+            Some(b'!') => Some(TodoLog::Timestamp),
+            _ => None,
+        };
+        let (enter, leave) = spec.split_once(SLASH as char).unwrap_or((spec, ""));
+        let mut enter = enter.chars().peekable();
+        if enter.peek().is_some_and(|chr| log(Some(*chr)).is_none()) {
+            ret.fast_key = enter.next();
+        }
+        ret.on_enter = log(enter.next());
+        ret.on_leave = log(leave.chars().next());
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parse_org;
+    use crate::types::Expr;
+
+    #[test]
+    fn default_keywords() {
+        let keywords = TodoKeywords::collect("* TODO task\n");
+        assert_eq!(keywords, TodoKeywords::default());
+        assert!(keywords.is_done("DONE"));
+        assert!(!keywords.is_done("TODO"));
+        assert!(keywords.get("WAITING").is_none());
+    }
+
+    #[test]
+    fn sequences() {
+        let input = r"#+TODO: TODO(t) WAITING(w@/!) | DONE(d!) CANCELLED(c@)
+#+typ_todo: BUG FEATURE FIXED
+";
+        let keywords = TodoKeywords::collect(input);
+
+        assert_eq!(keywords.sequences.len(), 2);
+        assert_eq!(
+            keywords.get("WAITING"),
+            Some((
+                &TodoKeyword {
+                    name: "WAITING",
+                    fast_key: Some('w'),
+                    on_enter: Some(TodoLog::Note),
+                    on_leave: Some(TodoLog::Timestamp),
+                },
+                false
+            ))
+        );
+        assert_eq!(
+            keywords.get("DONE").unwrap().0.on_enter,
+            Some(TodoLog::Timestamp)
+        );
+        assert!(keywords.is_done("CANCELLED"));
+        // the last keyword is done without a `|`
+        assert_eq!(keywords.sequences[1].kind, TodoSequenceKind::Type);
+        assert!(keywords.is_done("FIXED"));
+        assert!(!keywords.is_done("BUG"));
+        // the defaults no longer apply
+        assert!(keywords.get("TODO").is_some());
+        assert!(keywords.get("Done").is_none());
+    }
+
+    #[test]
+    fn keywords_in_blocks() {
+        let input = r"#+begin_example
+#+TODO: EXAMPLE | DONE
+#+end_example
+#+BEGIN_SRC org
+#+todo: SRC | DONE
+#+END_SRC
+#+begin_src
+#+end_src
+#+todo: NEXT | DONE
+";
+        let keywords = TodoKeywords::collect(input);
+
+        assert_eq!(keywords.sequences.len(), 1);
+        assert!(keywords.get("NEXT").is_some());
+        assert!(keywords.get("EXAMPLE").is_none());
+        assert!(keywords.get("SRC").is_none());
+    }
+
+    #[test]
+    fn heading_keywords() {
+        let input = r"* WAITING reply
+* CANCELLED meeting
+* DONE not a keyword here
+#+seq_todo: TODO WAITING | CANCELLED
+";
+        let parsed = parse_org(input);
+        let headings: Vec<(Option<&str>, bool)> = parsed
+            .pool
+            .iter()
+            .filter_map(|node| match &node.obj {
+                Expr::Heading(heading) => Some((heading.keyword, heading.done)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            headings,
+            [
+                (Some("WAITING"), false),
+                (Some("CANCELLED"), true),
+                (None, false)
+            ]
+        );
+    }
+}
@@ -16,6 +16,8 @@ pub use utils::Match;
 
 use std::collections::HashMap;
 
+use element::{TagConfig, TodoKeywords, link_results};
+use object::link_radio_targets;
 use parse::{parse_element, parse_object};
 use types::{Cursor, NodeCache, ParseOpts};
 
@@ -46,6 +48,8 @@ pub(crate) mod constants {
     pub const NEWLINE     : u8 = b'\n';
     pub const LPAREN      : u8 = b'(';
     pub const RPAREN      : u8 = b')';
+    pub const AT          : u8 = b'@';
+    pub const SEMICOLON   : u8 = b';';
 }
 
 /// The main entry point to the parser.
@@ -63,10 +67,19 @@ pub fn parse_org(input: &str) -> Parser<'_> {
         pool,
         cache,
         targets: HashMap::new(),
+        radio_targets: Vec::new(),
         macros: HashMap::new(),
         keywords: HashMap::new(),
         target_occurences: HashMap::new(),
         footnotes: HashMap::new(),
+        names: HashMap::new(),
+        block_results: HashMap::new(),
+        block_headers: HashMap::new(),
+        coderefs: HashMap::new(),
+        todo_keywords: TodoKeywords::collect(input),
+        file_tags: Vec::new(),
+        tag_config: TagConfig::default(),
+        properties: HashMap::new(),
         source: input,
     };
     // main loop
@@ -75,6 +88,8 @@ pub fn parse_org(input: &str) -> Parser<'_> {
         cursor.move_to(parser.pool[id].end);
     }
     parser.alloc_with_id(Expr::Root(content_vec), 0, cursor.index, None, parent);
+    link_radio_targets(&mut parser);
+    link_results(&mut parser);
 
     parser
 }
@@ -94,10 +109,19 @@ pub fn parse_macro_call<'a>(input: &'a str) -> Parser<'a> {
         pool,
         cache: NodeCache::new(),
         targets: HashMap::new(),
+        radio_targets: Vec::new(),
         macros: HashMap::new(),
         keywords: HashMap::new(),
         target_occurences: HashMap::new(),
         footnotes: HashMap::new(),
+        names: HashMap::new(),
+        block_results: HashMap::new(),
+        block_headers: HashMap::new(),
+        coderefs: HashMap::new(),
+        todo_keywords: TodoKeywords::default(),
+        file_tags: Vec::new(),
+        tag_config: TagConfig::default(),
+        properties: HashMap::new(),
         source: input,
     };
     while let Ok(id) = parse_object(&mut parser, cursor, Some(parent), parse_opts) {
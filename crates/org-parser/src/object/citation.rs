@@ -0,0 +1,260 @@
+use crate::constants::{AT, COLON, LBRACK, RBRACK, SEMICOLON};
+use crate::node_pool::NodeID;
+use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result};
+
+/// An org-cite citation.
+///
+/// ```text
+/// [cite/style:common prefix; pre @key1 post; @key2; common suffix]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation<'a> {
+    /// The `style` or `style/variant` following `cite/`.
+    pub style: Option<&'a str>,
+    /// Text preceding every reference.
+    pub prefix: Option<&'a str>,
+    /// Text following every reference.
+    pub suffix: Option<&'a str>,
+    /// NodeIDs of the [`CitationReference`]s in this citation.
+    pub references: Vec<NodeID>,
+}
+
+/// A single `pre @key post` entry in a [`Citation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CitationReference<'a> {
+    pub key: &'a str,
+    pub prefix: Option<&'a str>,
+    pub suffix: Option<&'a str>,
+}
+
+impl<'a> Parseable<'a> for Citation<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        cursor.word("[cite")?;
+
+        let style = if cursor.try_curr()? == b'/' {
+            cursor.next();
+            let style_match = cursor.fn_while(|chr: u8| {
+                chr.is_ascii_alphanumeric() || matches!(chr, b'_' | b'-' | b'/')
+            })?;
+            cursor.index = style_match.end;
+            Some(style_match.obj)
+        } else {
+            None
+        };
+
+        if cursor.try_curr()? != COLON {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.next();
+
+        let contents_match = cursor.fn_until(|chr: u8| chr == RBRACK || chr == LBRACK)?;
+        cursor.index = contents_match.end;
+        if cursor.curr() != RBRACK || contents_match.obj.contains("\n\n") {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.next();
+
+        let parts: Vec<&str> = contents_match.obj.split(SEMICOLON as char).collect();
+        let is_ref = |part: &&str| find_key(part).is_some();
+        let first_ref = parts
+            .iter()
+            .position(is_ref)
+            .ok_or(MatchError::InvalidLogic)?;
+        let last_ref = parts.iter().rposition(is_ref).unwrap();
+        // at most one global prefix and suffix
+        if first_ref > 1 || parts.len() - last_ref > 2 {
+            return Err(MatchError::InvalidLogic);
+        }
+
+        let mut references = Vec::new();
+        let mut part_start = contents_match.start;
+        for (i, part) in parts.iter().enumerate() {
+            if (first_ref..=last_ref).contains(&i) {
+                let (key_start, key) = find_key(part).ok_or(MatchError::InvalidLogic)?;
+                references.push((
+                    CitationReference {
+                        key,
+                        prefix: non_empty(&part[..key_start - 1]),
+                        suffix: non_empty(&part[key_start + key.len()..]),
+                    },
+                    part_start,
+                    part_start + part.len(),
+                ));
+            }
+            // skip the semicolon
+            part_start += part.len() + 1;
+        }
+
+        let reserved_id = parser.pool.reserve_id();
+        let references = references
+            .into_iter()
+            .map(|(reference, ref_start, ref_end)| {
+                parser
+                    .pool
+                    .alloc(reference, ref_start, ref_end, Some(reserved_id))
+            })
+            .collect();
+
+        Ok(parser.alloc_with_id(
+            Self {
+                style,
+                prefix: parts[..first_ref].first().copied().and_then(non_empty),
+                suffix: parts[last_ref + 1..].first().copied().and_then(non_empty),
+                references,
+            },
+            start,
+            cursor.index,
+            parent,
+            reserved_id,
+        ))
+    }
+}
+
+/// Locates `@key` in a citation part, returning the index the key starts at
+/// (after the `@`) and the key itself.
+fn find_key(part: &str) -> Option<(usize, &str)> {
+    let at = part.find(AT as char)?;
+    let key_start = at + 1;
+    let key_len = part[key_start..]
+        .find(|chr: char| !is_key_char(chr))
+        .unwrap_or(part.len() - key_start);
+
+    if key_len == 0 {
+        None
+    } else {
+        Some((key_start, &part[key_start..key_start + key_len]))
+    }
+}
+
+// word constituents, plus: -.:?!`'/*@+|(){}<>&_^$#%~
+fn is_key_char(chr: char) -> bool {
+    chr.is_alphanumeric() || "-.:?!`'/*@+|(){}<>&_^$#%~".contains(chr)
+}
+
+fn non_empty(s: &str) -> Option<&str> {
+    if s.trim().is_empty() { None } else { Some(s) }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::object::{Citation, CitationReference};
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
+
+    fn references<'a>(
+        parsed: &'a crate::Parser,
+        citation: &Citation,
+    ) -> Vec<&'a CitationReference<'a>> {
+        citation
+            .references
+            .iter()
+            .map(|id| {
+                if let Expr::CitationReference(reference) = &parsed.pool[*id].obj {
+                    reference
+                } else {
+                    unreachable!()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn basic_citation() {
+        let input = "[cite:@key]";
+        let parsed = parse_org(input);
+        let citation = expr_in_pool!(parsed, Citation).unwrap();
+
+        assert_eq!(citation.style, None);
+        assert_eq!(citation.prefix, None);
+        assert_eq!(
+            references(&parsed, citation),
+            [&CitationReference {
+                key: "key",
+                prefix: None,
+                suffix: None
+            }]
+        );
+    }
+
+    #[test]
+    fn full_citation() {
+        let input = "[cite/t/c:common prefix; pre @key1 post; @key2; common suffix]";
+        let parsed = parse_org(input);
+        let citation = expr_in_pool!(parsed, Citation).unwrap();
+
+        assert_eq!(citation.style, Some("t/c"));
+        assert_eq!(citation.prefix, Some("common prefix"));
+        assert_eq!(citation.suffix, Some(" common suffix"));
+        assert_eq!(
+            references(&parsed, citation),
+            [
+                &CitationReference {
+                    key: "key1",
+                    prefix: Some(" pre "),
+                    suffix: Some(" post")
+                },
+                &CitationReference {
+                    key: "key2",
+                    prefix: None,
+                    suffix: None
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn citation_in_paragraph() {
+        let input = "As shown [cite:see @doe2020 p. 4], it works.\n";
+        let parsed = parse_org(input);
+        let citation = expr_in_pool!(parsed, Citation).unwrap();
+        let refs = references(&parsed, citation);
+
+        assert_eq!(refs[0].key, "doe2020");
+        assert_eq!(refs[0].prefix, Some("see "));
+        assert_eq!(refs[0].suffix, Some(" p. 4"));
+    }
+
+    #[test]
+    fn reference_spans() {
+        let input = "[cite:prefix;pre @one;@two]";
+        let parsed = parse_org(input);
+        let citation = expr_in_pool!(parsed, Citation).unwrap();
+
+        let spans: Vec<&str> = citation
+            .references
+            .iter()
+            .map(|id| &input[parsed.pool[*id].start..parsed.pool[*id].end])
+            .collect();
+        assert_eq!(spans, ["pre @one", "@two"]);
+    }
+
+    #[test]
+    fn invalid_citations() {
+        for input in [
+            "[cite:no key]",
+            "[cite:@]",
+            "[cite @key]",
+            "[cite:one; two; @key]",
+            "[cite:@key; one; two]",
+            "[cite:@key\n\n]",
+        ] {
+            let parsed = parse_org(input);
+            assert!(expr_in_pool!(parsed, Citation).is_none(), "{input}");
+        }
+    }
+
+    #[test]
+    fn no_citation_in_link() {
+        let input = "[[https://example.com][[cite:@key]]]";
+        let parsed = parse_org(input);
+        assert!(expr_in_pool!(parsed, Citation).is_none());
+    }
+}
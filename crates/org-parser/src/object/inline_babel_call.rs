@@ -0,0 +1,126 @@
+use crate::constants::{LBRACK, LPAREN, RBRACK, RPAREN};
+use crate::element::{
+    CallArgument, header_arg_pairs, parse_arguments, parse_call_name, parse_delimited,
+};
+use crate::node_pool::NodeID;
+use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result};
+
+/// An inline call to a named source block.
+///
+/// ```text
+/// call_name[:inside header](arg=value)[:end header]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineBabelCall<'a> {
+    pub name: &'a str,
+    /// Header arguments applied to the called block.
+    pub inside_header: Vec<(&'a str, &'a str)>,
+    pub arguments: Vec<CallArgument<'a>>,
+    /// Header arguments applied to the results of the call.
+    pub end_header: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Parseable<'a> for InlineBabelCall<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        // don't match in the middle of a word: recall_x()
+        if cursor
+            .peek_rev(1)
+            .is_ok_and(|chr| chr.is_ascii_alphanumeric())
+        {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.word("call_")?;
+
+        let name = parse_call_name(cursor)?;
+        cursor.index = name.end;
+
+        let inside_header = if cursor.try_curr()? == LBRACK {
+            let header = parse_delimited(cursor, LBRACK, RBRACK)?;
+            cursor.index = header.end;
+            header_arg_pairs(header.obj)
+        } else {
+            Vec::new()
+        };
+
+        if cursor.try_curr()? != LPAREN {
+            return Err(MatchError::InvalidLogic);
+        }
+        let args = parse_delimited(cursor, LPAREN, RPAREN)?;
+        cursor.index = args.end;
+
+        let end_header = if cursor.try_curr().is_ok_and(|chr| chr == LBRACK) {
+            let header = parse_delimited(cursor, LBRACK, RBRACK)?;
+            cursor.index = header.end;
+            header_arg_pairs(header.obj)
+        } else {
+            Vec::new()
+        };
+
+        Ok(parser.alloc(
+            Self {
+                name: name.obj,
+                inside_header,
+                arguments: parse_arguments(args.obj),
+                end_header,
+            },
+            start,
+            cursor.index,
+            parent,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::element::CallArgument;
+    use crate::object::InlineBabelCall;
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
+
+    #[test]
+    fn basic_inline_call() {
+        let input = "the answer is call_square(x=4) units";
+        let parsed = parse_org(input);
+        let call = expr_in_pool!(parsed, InlineBabelCall).unwrap();
+
+        assert_eq!(
+            call,
+            &InlineBabelCall {
+                name: "square",
+                inside_header: Vec::new(),
+                arguments: vec![CallArgument {
+                    name: Some("x"),
+                    value: "4"
+                }],
+                end_header: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn inline_call_headers() {
+        let input = "call_name[:session](a=1, b=2)[:results raw]";
+        let parsed = parse_org(input);
+        let call = expr_in_pool!(parsed, InlineBabelCall).unwrap();
+
+        assert_eq!(call.inside_header, [("session", "")]);
+        assert_eq!(call.arguments.len(), 2);
+        assert_eq!(call.end_header, [("results", "raw")]);
+    }
+
+    #[test]
+    fn invalid_inline_calls() {
+        for input in ["call_name", "call_(x)", "recall_name(x)", "call_name[:a]"] {
+            let parsed = parse_org(input);
+            assert!(expr_in_pool!(parsed, InlineBabelCall).is_none(), "{input}");
+        }
+    }
+}
@@ -36,7 +36,7 @@ impl<'a> Parseable<'a> for InlineSrc<'a> {
                     },
                     start,
                     body.end,
-                    None,
+                    parent,
                 ))
             }
             LBRACK => {
@@ -106,9 +106,9 @@ impl<'a> PathReg<'a> {
                 return PathReg::CustomId(cursor.clamp(cursor.index + 1, cursor.len()));
             }
             LPAREN => {
-                // FIXME: breaks on ()
-                if cursor[cursor.len() - 1] == RPAREN {
-                    return PathReg::Coderef(cursor.clamp(cursor.index + 1, cursor.len()));
+                // (label), the label can't be empty
+                if cursor[cursor.len() - 1] == RPAREN && cursor.len() - cursor.index > 2 {
+                    return PathReg::Coderef(cursor.clamp(cursor.index + 1, cursor.len() - 1));
                 }
             }
             chr => {
@@ -422,7 +422,7 @@ mod tests {
 
     use crate::element::Affiliated;
     use crate::expr_in_pool;
-    use crate::object::{PlainLink, RegularLink};
+    use crate::object::{PathReg, PlainLink, RegularLink};
     use crate::parse_org;
     use crate::types::Expr;
 
@@ -605,4 +605,15 @@ I'll be skipping over the instrumentals unless there's reason to.
             panic!()
         };
     }
+
+    #[test]
+    fn coderef_link() {
+        let parser = parse_org("[[(label)]]");
+        let link = expr_in_pool!(parser, RegularLink).unwrap();
+        assert!(matches!(link.path.obj, PathReg::Coderef("label")));
+
+        let parser = parse_org("[[()]]");
+        let link = expr_in_pool!(parser, RegularLink).unwrap();
+        assert!(matches!(link.path.obj, PathReg::Unspecified(_)));
+    }
 }
@@ -1,24 +1,32 @@
 //! Module containing object structures
 //!
+mod citation;
 mod emoji;
 mod entity;
 mod export_snippet;
 mod footnote_ref;
+mod inline_babel_call;
 mod inline_src;
 mod latex_frag;
 mod link;
 mod markup;
 mod node_property;
 mod org_macro;
+mod radio_target;
+mod statistics_cookie;
 mod sup_sub;
 mod table_cell;
 mod target;
+mod timestamp;
 
+pub use citation::Citation;
+pub use citation::CitationReference;
 pub use emoji::Emoji;
 pub use entity::Entity;
 pub(crate) use entity::parse_entity;
 pub use export_snippet::ExportSnippet;
 pub use footnote_ref::FootnoteRef;
+pub use inline_babel_call::InlineBabelCall;
 pub use inline_src::InlineSrc;
 pub use latex_frag::LatexFragment;
 pub use link::PathReg;
@@ -28,10 +36,25 @@ pub(crate) use link::parse_angle_link;
 pub(crate) use link::parse_plain_link;
 pub use markup::*;
 pub use node_property::NodeProperty;
-pub(crate) use node_property::parse_node_property;
+pub(crate) use node_property::{add_property, parse_node_property};
 pub use org_macro::MacroCall;
+pub use radio_target::RadioLink;
+pub use radio_target::RadioTarget;
+pub(crate) use radio_target::link_radio_targets;
+pub use statistics_cookie::StatisticsCookie;
 pub use sup_sub::PlainOrRec;
 pub use sup_sub::Subscript;
 pub use sup_sub::Superscript;
 pub use table_cell::TableCell;
 pub use target::Target;
+pub use timestamp::Date;
+pub use timestamp::DateTime;
+pub use timestamp::Repeater;
+pub use timestamp::RepeaterKind;
+pub use timestamp::Time;
+pub use timestamp::TimeUnit;
+pub use timestamp::Timestamp;
+pub use timestamp::TimestampEnd;
+pub use timestamp::Warning;
+pub use timestamp::WarningKind;
+pub(crate) use timestamp::parse_timestamp;
@@ -27,7 +27,13 @@ pub(crate) fn parse_node_property<'a>(
     cursor.word(":")?;
 
     let val_match = cursor.fn_until(|chr: u8| chr == b'\n')?;
-    let val = val_match.obj.trim();
+    add_property(properties, name, val_match.obj.trim());
+
+    Ok(val_match.end + 1)
+}
+
+/// Sets a property, appending to it instead when the name ends with `+`.
+pub(crate) fn add_property<'a>(properties: &mut PropertyDrawer<'a>, name: &'a str, val: &'a str) {
     if name.ends_with('+') {
         let new_name = name.trim_end_matches('+');
         properties
@@ -39,6 +45,4 @@ pub(crate) fn parse_node_property<'a>(
     } else {
         properties.insert(name, Cow::from(val));
     }
-
-    Ok(val_match.end + 1)
 }
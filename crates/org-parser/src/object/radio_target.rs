@@ -0,0 +1,274 @@
+use crate::constants::{LANGLE, NEWLINE, RANGLE};
+use crate::node_pool::NodeID;
+use crate::types::{Cursor, Expr, MatchError, ParseOpts, Parseable, Parser, Result};
+
+/// A radio target, turning every occurrence of its contents into a [`RadioLink`].
+///
+/// ```text
+/// <<<term>>>
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct RadioTarget<'a>(pub &'a str);
+
+/// Text that matched a [`RadioTarget`].
+///
+/// These are not produced while parsing, but by a second pass
+/// over the [`Expr::Plain`] nodes of the document.
+#[derive(Debug, Copy, Clone)]
+pub struct RadioLink<'a> {
+    /// The contents of the [`RadioTarget`] being linked to.
+    ///
+    /// Use as a key into [`Parser::targets`].
+    pub target: &'a str,
+    /// The matched text, which might differ in case from the target.
+    pub contents: &'a str,
+}
+
+impl<'a> Parseable<'a> for RadioTarget<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        cursor.word("<<<")?;
+        if cursor.try_curr()?.is_ascii_whitespace() {
+            return Err(MatchError::InvalidLogic);
+        }
+
+        let inner_match =
+            cursor.fn_until(|chr: u8| chr == NEWLINE || chr == RANGLE || chr == LANGLE)?;
+        if inner_match.obj.ends_with(|c: char| c.is_ascii_whitespace()) {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.index = inner_match.end;
+        cursor.word(">>>")?;
+
+        let ret_id = parser.alloc(Self(inner_match.obj), start, cursor.index, parent);
+
+        parser.pool[ret_id].id_target = Some(parser.generate_target(inner_match.obj));
+        parser.radio_targets.push(inner_match.obj);
+        Ok(ret_id)
+    }
+}
+
+/// Splits every [`Expr::Plain`] node that mentions a radio target into
+/// [`RadioLink`]s and the text surrounding them.
+///
+/// Matching is case-insensitive, and only whole words are linked.
+pub(crate) fn link_radio_targets(parser: &mut Parser) {
+    if parser.radio_targets.is_empty() {
+        return;
+    }
+    // prefer the longest match when targets overlap
+    let mut terms = parser.radio_targets.clone();
+    terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+
+    let mut plain_ids = Vec::new();
+    collect_plain(parser, parser.pool.root_id(), &mut plain_ids);
+
+    for plain_id in plain_ids {
+        let node = &parser.pool[plain_id];
+        let Expr::Plain(text) = node.obj else {
+            unreachable!()
+        };
+        let (start, end, parent) = (node.start, node.end, node.parent);
+
+        let source = parser.source.as_bytes();
+        let is_boundary =
+            |loc: Option<&u8>| loc.is_none_or(|chr: &u8| !chr.is_ascii_alphanumeric());
+
+        let mut pieces: Vec<Expr> = Vec::new();
+        let mut spans = Vec::new();
+        let mut prev = 0;
+        let mut i = 0;
+        while i < text.len() {
+            let found = terms.iter().find(|term| {
+                let term_end = i + term.len();
+                text.get(i..term_end)
+                    .is_some_and(|slice| slice.eq_ignore_ascii_case(term))
+                    && is_boundary((start + i).checked_sub(1).and_then(|loc| source.get(loc)))
+                    && is_boundary(source.get(start + term_end))
+            });
+
+            if let Some(term) = found {
+                if prev != i {
+                    pieces.push(Expr::Plain(&text[prev..i]));
+                    spans.push((start + prev, start + i));
+                }
+                pieces.push(Expr::RadioLink(RadioLink {
+                    target: term,
+                    contents: &text[i..i + term.len()],
+                }));
+                spans.push((start + i, start + i + term.len()));
+                i += term.len();
+                prev = i;
+            } else {
+                i += 1;
+            }
+        }
+
+        if pieces.is_empty() {
+            continue;
+        }
+        if prev != text.len() {
+            pieces.push(Expr::Plain(&text[prev..]));
+            spans.push((start + prev, end));
+        }
+
+        // reuse the original node for the first piece, so anything holding
+        // its id stays valid
+        let mut new_ids = vec![plain_id];
+        let mut pieces = pieces.into_iter().zip(spans);
+        let (first, (first_start, first_end)) = pieces.next().unwrap();
+        parser
+            .pool
+            .alloc_with_id(first, first_start, first_end, parent, plain_id);
+        for (piece, (piece_start, piece_end)) in pieces {
+            new_ids.push(parser.pool.alloc(piece, piece_start, piece_end, parent));
+        }
+
+        let Some(parent) = parent else { continue };
+        let siblings = match &mut parser.pool[parent].obj {
+            Expr::Heading(heading)
+                if heading
+                    .title
+                    .as_ref()
+                    .is_some_and(|title| title.1.contains(&plain_id)) =>
+            {
+                heading.title.as_mut().map(|title| &mut title.1)
+            }
+            obj => obj.children_mut(),
+        };
+        if let Some(siblings) = siblings
+            && let Some(pos) = siblings.iter().position(|id| *id == plain_id)
+        {
+            siblings.splice(pos..=pos, new_ids);
+        }
+    }
+}
+
+fn collect_plain(parser: &Parser, id: NodeID, out: &mut Vec<NodeID>) {
+    let obj = &parser.pool[id].obj;
+    match obj {
+        Expr::Plain(_) => out.push(id),
+        // links can't nest
+        Expr::RegularLink(_) => return,
+        Expr::Heading(heading) => {
+            if let Some(title) = &heading.title {
+                for child in &title.1 {
+                    collect_plain(parser, *child, out);
+                }
+            }
+        }
+        _ => {}
+    }
+    if let Some(children) = obj.children() {
+        for child in children {
+            collect_plain(parser, *child, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::RadioLink;
+    use crate::parse_org;
+    use crate::types::Expr;
+
+    fn radio_links<'a>(parsed: &'a crate::Parser) -> Vec<RadioLink<'a>> {
+        parsed
+            .pool
+            .iter()
+            .filter_map(|node| {
+                if let Expr::RadioLink(link) = node.obj {
+                    Some(link)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn basic_radio_target() {
+        let input = r"<<<Org Mode>>> is a mode.
+
+Using org mode is fun, org moder isn't a thing.
+";
+        let parsed = parse_org(input);
+        let links = radio_links(&parsed);
+
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, "Org Mode");
+        assert_eq!(links[0].contents, "org mode");
+        assert_eq!(&*parsed.targets["Org Mode"], "org-mode");
+    }
+
+    #[test]
+    fn radio_split_keeps_order() {
+        let input = r"<<<cat>>>
+
+a cat and a cat
+";
+        let parsed = parse_org(input);
+        let root = parsed.pool[parsed.pool.root_id()].obj.children().unwrap();
+        let para = parsed.pool[*root.last().unwrap()].obj.children().unwrap();
+
+        let text: Vec<String> = para
+            .iter()
+            .map(|id| match parsed.pool[*id].obj {
+                Expr::Plain(plain) => plain.to_owned(),
+                Expr::RadioLink(link) => format!("[{}]", link.contents),
+                _ => String::new(),
+            })
+            .collect();
+        assert_eq!(text.concat(), "a [cat] and a [cat]");
+
+        for id in para {
+            let node = &parsed.pool[*id];
+            if let Expr::Plain(plain) = node.obj {
+                assert_eq!(&input[node.start..node.end], plain);
+            }
+        }
+    }
+
+    #[test]
+    fn radio_in_heading_title() {
+        let input = r"* all about cats
+<<<cats>>>
+";
+        let parsed = parse_org(input);
+        let Expr::Heading(heading) = &parsed.pool[parsed.pool.root_id()]
+            .obj
+            .children()
+            .map(|c| &parsed.pool[c[0]].obj)
+            .unwrap()
+        else {
+            panic!()
+        };
+        let title = &heading.title.as_ref().unwrap().1;
+        assert!(matches!(
+            parsed.pool[*title.last().unwrap()].obj,
+            Expr::RadioLink(_)
+        ));
+    }
+
+    #[test]
+    fn no_radio_in_links() {
+        let input = r"<<<cat>>>
+
+[[https://example.com][a cat]]
+";
+        let parsed = parse_org(input);
+        assert!(radio_links(&parsed).is_empty());
+    }
+
+    #[test]
+    fn invalid_radio_target() {
+        let input = "<<< cat>>>\n";
+        let parsed = parse_org(input);
+        assert!(parsed.radio_targets.is_empty());
+    }
+}
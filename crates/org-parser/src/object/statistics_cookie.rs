@@ -0,0 +1,293 @@
+use std::fmt::Display;
+
+use crate::constants::{LBRACK, RBRACK, SLASH};
+use crate::element::CheckBox;
+use crate::node_pool::NodeID;
+use crate::types::{Cursor, Expr, MatchError, ParseOpts, Parseable, Parser, Result};
+
+/// A progress indicator for the checkboxes or TODO items below it.
+///
+/// ```text
+/// * Tasks [1/3]
+/// - [-] Shopping [50%]
+///   - [X] Milk
+///   - [ ] Eggs
+/// ```
+///
+/// Empty cookies (`[/]`, `[%]`) are placeholders that can be filled in
+/// with [`Parser::update_statistics_cookies`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatisticsCookie {
+    /// `[done/total]`
+    Fraction {
+        done: Option<u32>,
+        total: Option<u32>,
+    },
+    /// `[percent%]`
+    Percent(Option<u32>),
+}
+
+impl<'a> Parseable<'a> for StatisticsCookie {
+    fn parse(
+        parser: &mut Parser<'a>,
+        mut cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let start = cursor.index;
+        cursor.word("[")?;
+
+        let first = number(&mut cursor)?;
+        let cookie = match cursor.try_curr()? {
+            SLASH => {
+                cursor.next();
+                let total = number(&mut cursor)?;
+                StatisticsCookie::Fraction { done: first, total }
+            }
+            b'%' => {
+                cursor.next();
+                StatisticsCookie::Percent(first)
+            }
+            _ => return Err(MatchError::InvalidLogic),
+        };
+
+        if cursor.try_curr()? != RBRACK {
+            return Err(MatchError::InvalidLogic);
+        }
+        cursor.next();
+
+        Ok(parser.alloc(cookie, start, cursor.index, parent))
+    }
+}
+
+fn number(cursor: &mut Cursor) -> Result<Option<u32>> {
+    let num_match = cursor.fn_while(|chr: u8| chr.is_ascii_digit())?;
+    cursor.index = num_match.end;
+    if num_match.obj.is_empty() {
+        Ok(None)
+    } else {
+        num_match
+            .obj
+            .parse()
+            .map(Some)
+            .map_err(|_| MatchError::InvalidLogic)
+    }
+}
+
+impl StatisticsCookie {
+    fn with_counts(self, done: u32, total: u32) -> Self {
+        match self {
+            StatisticsCookie::Fraction { .. } => StatisticsCookie::Fraction {
+                done: Some(done),
+                total: Some(total),
+            },
+            StatisticsCookie::Percent(_) => {
+                StatisticsCookie::Percent(Some((done * 100).checked_div(total).unwrap_or(0)))
+            }
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Recomputes every [`StatisticsCookie`] in the document.
+    ///
+    /// A cookie in a heading counts its direct child headings that have a
+    /// TODO keyword. If there are none, it counts the checkboxes in the
+    /// heading's section instead.
+    ///
+    /// A cookie in a list item counts the checkboxes of its direct child items.
+    pub fn update_statistics_cookies(&mut self) {
+        let mut cookies = Vec::new();
+        collect_cookies(self, self.pool.root_id(), &mut cookies);
+
+        for cookie_id in cookies {
+            let Some((done, total)) = self.cookie_owner(cookie_id).map(|id| self.progress(id))
+            else {
+                continue;
+            };
+            if let Expr::StatisticsCookie(cookie) = &mut self.pool[cookie_id].obj {
+                *cookie = cookie.with_counts(done, total);
+            }
+        }
+    }
+
+    /// The closest heading or item containing the node.
+    fn cookie_owner(&self, node_id: NodeID) -> Option<NodeID> {
+        let mut curr = self.pool[node_id].parent;
+        while let Some(id) = curr {
+            if matches!(self.pool[id].obj, Expr::Heading(_) | Expr::Item(_)) {
+                return Some(id);
+            }
+            curr = self.pool[id].parent;
+        }
+        None
+    }
+
+    /// (done, total) for the children of a heading or item.
+    fn progress(&self, owner: NodeID) -> (u32, u32) {
+        let children: &[NodeID] = self.pool[owner]
+            .obj
+            .children()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        if matches!(self.pool[owner].obj, Expr::Heading(_)) {
+            let tasks = children.iter().filter_map(|id| match &self.pool[*id].obj {
+                Expr::Heading(heading) if heading.keyword.is_some() => Some(heading.done),
+                _ => None,
+            });
+            let (mut done, mut total) = (0, 0);
+            for is_done in tasks {
+                total += 1;
+                if is_done {
+                    done += 1;
+                }
+            }
+            if total > 0 {
+                return (done, total);
+            }
+        }
+
+        let (mut done, mut total) = (0, 0);
+        for id in children {
+            let Expr::PlainList(list) = &self.pool[*id].obj else {
+                continue;
+            };
+            for item_id in &list.children {
+                if let Expr::Item(item) = &self.pool[*item_id].obj
+                    && let Some(check_box) = &item.check_box
+                {
+                    total += 1;
+                    if matches!(check_box, CheckBox::On) {
+                        done += 1;
+                    }
+                }
+            }
+        }
+        (done, total)
+    }
+}
+
+fn collect_cookies(parser: &Parser, id: NodeID, out: &mut Vec<NodeID>) {
+    let obj = &parser.pool[id].obj;
+    match obj {
+        Expr::StatisticsCookie(_) => out.push(id),
+        Expr::Heading(heading) => {
+            if let Some(title) = &heading.title {
+                for child in &title.1 {
+                    collect_cookies(parser, *child, out);
+                }
+            }
+        }
+        _ => {}
+    }
+    if let Some(children) = obj.children() {
+        for child in children {
+            collect_cookies(parser, *child, out);
+        }
+    }
+}
+
+impl Display for StatisticsCookie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", LBRACK as char)?;
+        match self {
+            StatisticsCookie::Fraction { done, total } => {
+                if let Some(done) = done {
+                    write!(f, "{done}")?;
+                }
+                write!(f, "/")?;
+                if let Some(total) = total {
+                    write!(f, "{total}")?;
+                }
+            }
+            StatisticsCookie::Percent(percent) => {
+                if let Some(percent) = percent {
+                    write!(f, "{percent}")?;
+                }
+                write!(f, "%")?;
+            }
+        }
+        write!(f, "{}", RBRACK as char)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{expr_in_pool, parse_org};
+
+    fn cookies(parsed: &Parser) -> Vec<String> {
+        parsed
+            .pool
+            .iter()
+            .filter_map(|node| {
+                if let Expr::StatisticsCookie(cookie) = node.obj {
+                    Some(cookie.to_string())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn basic_cookies() {
+        let parsed = parse_org("[2/5] [40%] [/] [%]");
+        assert_eq!(cookies(&parsed), ["[2/5]", "[40%]", "[/]", "[%]"]);
+
+        let cookie = expr_in_pool!(parsed, StatisticsCookie).unwrap();
+        assert_eq!(
+            cookie,
+            &StatisticsCookie::Fraction {
+                done: Some(2),
+                total: Some(5)
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_cookies() {
+        for input in ["[2/5", "[ 2/5]", "[2/5%]", "[a/b]", "[40]", "[4%0]"] {
+            let parsed = parse_org(input);
+            assert!(cookies(&parsed).is_empty(), "{input}");
+        }
+    }
+
+    #[test]
+    fn update_checkbox_cookies() {
+        let input = r"- [-] groceries [/]
+  - [X] milk
+  - [ ] eggs
+  - [-] bread
+- [X] chores [%]
+  - [X] dishes
+  - laundry
+- no cookie
+  - [X] ignored
+";
+        let mut parsed = parse_org(input);
+        parsed.update_statistics_cookies();
+        assert_eq!(cookies(&parsed), ["[1/3]", "[100%]"]);
+    }
+
+    #[test]
+    fn update_heading_cookies() {
+        let input = r"* Project [%]
+** DONE one
+** TODO two
+** not a task
+*** DONE nested
+* Checklist [1/1]
+- [X] a
+- [ ] b
+- no box
+** child without keyword
+";
+        let mut parsed = parse_org(input);
+        parsed.update_statistics_cookies();
+        assert_eq!(cookies(&parsed), ["[50%]", "[1/2]"]);
+    }
+}
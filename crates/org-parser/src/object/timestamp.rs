@@ -0,0 +1,651 @@
+use std::fmt::Display;
+
+use crate::constants::{COLON, HYPHEN, LANGLE, LBRACK, NEWLINE, PERIOD, PLUS, RANGLE, RBRACK};
+use crate::node_pool::NodeID;
+use crate::types::{Cursor, MatchError, ParseOpts, Parseable, Parser, Result};
+use crate::utils::Match;
+
+/// A point (or range) in time.
+///
+/// ```text
+/// <2024-03-01 Fri 10:00 +1w -2d>
+/// [2024-03-01 Fri 10:00-12:00]
+/// <2024-03-01 Fri>--<2024-03-04 Mon>
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp<'a> {
+    /// Active timestamps are enclosed in `<>`, inactive ones in `[]`.
+    pub active: bool,
+    pub start: DateTime<'a>,
+    pub repeater: Option<Repeater>,
+    pub warning: Option<Warning>,
+    pub end: Option<TimestampEnd<'a>>,
+}
+
+/// The end of a ranged [`Timestamp`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimestampEnd<'a> {
+    /// A range within a single day:
+    ///
+    /// ```text
+    /// <2024-03-01 Fri 10:00-12:00>
+    /// ```
+    Time(Time),
+    /// A range spanning two timestamps:
+    ///
+    /// ```text
+    /// <2024-03-01 Fri>--<2024-03-04 Mon>
+    /// ```
+    Date {
+        end: DateTime<'a>,
+        repeater: Option<Repeater>,
+        warning: Option<Warning>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime<'a> {
+    pub date: Date<'a>,
+    pub time: Option<Time>,
+}
+
+/// YYYY-MM-DD DAYNAME
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date<'a> {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub dayname: Option<&'a str>,
+}
+
+/// H:MM or HH:MM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+/// Describes how a timestamp recurs, e.g.: `+1w`, `.+1d`, `++1m`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeater {
+    pub kind: RepeaterKind,
+    pub value: u32,
+    pub unit: TimeUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeaterKind {
+    /// `+`
+    Cumulate,
+    /// `++`
+    CatchUp,
+    /// `.+`
+    Restart,
+}
+
+/// Describes how early to warn about a timestamp, e.g.: `-2d`, `--2d`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub value: u32,
+    pub unit: TimeUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// `-`
+    All,
+    /// `--`
+    First,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl<'a> Parseable<'a> for Timestamp<'a> {
+    fn parse(
+        parser: &mut Parser<'a>,
+        cursor: Cursor<'a>,
+        parent: Option<NodeID>,
+        parse_opts: ParseOpts,
+    ) -> Result<NodeID> {
+        let ts_match = parse_timestamp(cursor)?;
+        Ok(parser.alloc(ts_match.obj, ts_match.start, ts_match.end, parent))
+    }
+}
+
+/// Parses a timestamp without allocating it.
+///
+/// Used by elements that store timestamps directly.
+pub(crate) fn parse_timestamp(mut cursor: Cursor<'_>) -> Result<Match<Timestamp<'_>>> {
+    let start = cursor.index;
+    let first = parse_bracketed(cursor)?;
+    cursor.index = first.end;
+    let first_end = first.end;
+    let first = first.obj;
+
+    // <a>--<b>
+    if first.end_time.is_none()
+        && cursor.word("--").is_ok()
+        && let Ok(second) = parse_bracketed(cursor)
+        && second.obj.active == first.active
+        && second.obj.end_time.is_none()
+    {
+        return Ok(Match {
+            start,
+            end: second.end,
+            obj: Timestamp {
+                active: first.active,
+                start: first.datetime,
+                repeater: first.repeater,
+                warning: first.warning,
+                end: Some(TimestampEnd::Date {
+                    end: second.obj.datetime,
+                    repeater: second.obj.repeater,
+                    warning: second.obj.warning,
+                }),
+            },
+        });
+    }
+
+    Ok(Match {
+        start,
+        end: first_end,
+        obj: Timestamp {
+            active: first.active,
+            start: first.datetime,
+            repeater: first.repeater,
+            warning: first.warning,
+            end: first.end_time.map(TimestampEnd::Time),
+        },
+    })
+}
+
+/// The contents of a single pair of `<>` or `[]`.
+struct Bracketed<'a> {
+    active: bool,
+    datetime: DateTime<'a>,
+    end_time: Option<Time>,
+    repeater: Option<Repeater>,
+    warning: Option<Warning>,
+}
+
+fn parse_bracketed(mut cursor: Cursor<'_>) -> Result<Match<Bracketed<'_>>> {
+    let start = cursor.index;
+    let (active, closer) = match cursor.try_curr()? {
+        LANGLE => (true, RANGLE),
+        LBRACK => (false, RBRACK),
+        _ => return Err(MatchError::InvalidLogic),
+    };
+    cursor.next();
+
+    let date_match = parse_date(cursor)?;
+    cursor.index = date_match.end;
+
+    let mut ret = Bracketed {
+        active,
+        datetime: DateTime {
+            date: date_match.obj,
+            time: None,
+        },
+        end_time: None,
+        repeater: None,
+        warning: None,
+    };
+
+    loop {
+        // every component is separated from the previous one by whitespace
+        let prev = cursor.index;
+        cursor.skip_ws();
+        match cursor.try_curr()? {
+            chr if chr == closer => break,
+            _ if cursor.index == prev => return Err(MatchError::InvalidLogic),
+            NEWLINE => return Err(MatchError::InvalidLogic),
+            chr if chr.is_ascii_digit() => {
+                if ret.datetime.time.is_some() || ret.repeater.is_some() || ret.warning.is_some() {
+                    return Err(MatchError::InvalidLogic);
+                }
+                let time_match = parse_time(cursor)?;
+                cursor.index = time_match.end;
+                ret.datetime.time = Some(time_match.obj);
+
+                // 10:00-12:00
+                if cursor.try_curr()? == HYPHEN && cursor.peek(1)?.is_ascii_digit() {
+                    cursor.next();
+                    let end_match = parse_time(cursor)?;
+                    cursor.index = end_match.end;
+                    ret.end_time = Some(end_match.obj);
+                }
+            }
+            PLUS | PERIOD => {
+                if ret.repeater.is_some() {
+                    return Err(MatchError::InvalidLogic);
+                }
+                let kind = if cursor.word("++").is_ok() {
+                    RepeaterKind::CatchUp
+                } else if cursor.word(".+").is_ok() {
+                    RepeaterKind::Restart
+                } else if cursor.word("+").is_ok() {
+                    RepeaterKind::Cumulate
+                } else {
+                    return Err(MatchError::InvalidLogic);
+                };
+                let (value, unit) = parse_interval(&mut cursor)?;
+                ret.repeater = Some(Repeater { kind, value, unit });
+            }
+            HYPHEN => {
+                if ret.warning.is_some() {
+                    return Err(MatchError::InvalidLogic);
+                }
+                let kind = if cursor.word("--").is_ok() {
+                    WarningKind::First
+                } else {
+                    cursor.next();
+                    WarningKind::All
+                };
+                let (value, unit) = parse_interval(&mut cursor)?;
+                ret.warning = Some(Warning { kind, value, unit });
+            }
+            _ => {
+                // DAYNAME: a string consisting of non-whitespace characters
+                // other than +, -, ], >, a digit, or \n
+                if ret.datetime.date.dayname.is_some()
+                    || ret.datetime.time.is_some()
+                    || ret.repeater.is_some()
+                    || ret.warning.is_some()
+                {
+                    return Err(MatchError::InvalidLogic);
+                }
+                let dayname_match = cursor.fn_while(|chr: u8| {
+                    !(chr.is_ascii_whitespace()
+                        || chr.is_ascii_digit()
+                        || matches!(chr, PLUS | HYPHEN | RBRACK | RANGLE))
+                })?;
+                cursor.index = dayname_match.end;
+                ret.datetime.date.dayname = Some(dayname_match.obj);
+            }
+        }
+    }
+
+    Ok(Match {
+        start,
+        end: cursor.index + 1,
+        obj: ret,
+    })
+}
+
+// YYYY-MM-DD
+fn parse_date(mut cursor: Cursor<'_>) -> Result<Match<Date<'_>>> {
+    let start = cursor.index;
+    let year = parse_digits(&mut cursor, 4, 4)?;
+    cursor.word("-")?;
+    let month = parse_digits(&mut cursor, 2, 2)?;
+    cursor.word("-")?;
+    let day = parse_digits(&mut cursor, 2, 2)?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(MatchError::InvalidLogic);
+    }
+
+    Ok(Match {
+        start,
+        end: cursor.index,
+        obj: Date {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            dayname: None,
+        },
+    })
+}
+
+// H:MM or HH:MM
+fn parse_time(mut cursor: Cursor<'_>) -> Result<Match<Time>> {
+    let start = cursor.index;
+    let hour = parse_digits(&mut cursor, 1, 2)?;
+    if cursor.try_curr()? != COLON {
+        return Err(MatchError::InvalidLogic);
+    }
+    cursor.next();
+    let minute = parse_digits(&mut cursor, 2, 2)?;
+
+    if hour > 24 || minute > 59 {
+        return Err(MatchError::InvalidLogic);
+    }
+
+    Ok(Match {
+        start,
+        end: cursor.index,
+        obj: Time {
+            hour: hour as u8,
+            minute: minute as u8,
+        },
+    })
+}
+
+// the 1w in +1w
+fn parse_interval(cursor: &mut Cursor<'_>) -> Result<(u32, TimeUnit)> {
+    let value = parse_digits(cursor, 1, 9)?;
+    let unit = match cursor.try_curr()? {
+        b'h' => TimeUnit::Hour,
+        b'd' => TimeUnit::Day,
+        b'w' => TimeUnit::Week,
+        b'm' => TimeUnit::Month,
+        b'y' => TimeUnit::Year,
+        _ => return Err(MatchError::InvalidLogic),
+    };
+    cursor.next();
+    Ok((value, unit))
+}
+
+/// Reads between `min` and `max` ascii digits, advancing the cursor past them.
+fn parse_digits(cursor: &mut Cursor<'_>, min: usize, max: usize) -> Result<u32> {
+    let num_match = cursor.fn_while(|chr: u8| chr.is_ascii_digit())?;
+    if num_match.len() < min || num_match.len() > max {
+        return Err(MatchError::InvalidLogic);
+    }
+    cursor.index = num_match.end;
+    num_match.obj.parse().or(Err(MatchError::InvalidLogic))
+}
+
+impl Date<'_> {
+    /// Number of days since 1970-01-01 in the proleptic Gregorian calendar.
+    pub fn days_since_epoch(&self) -> i64 {
+        // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let doy =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+}
+
+impl DateTime<'_> {
+    /// Number of minutes since 1970-01-01 00:00, ignoring timezones.
+    ///
+    /// A missing time is treated as midnight.
+    pub fn minutes_since_epoch(&self) -> i64 {
+        let (hour, minute) = self
+            .time
+            .map_or((0, 0), |t| (i64::from(t.hour), i64::from(t.minute)));
+        self.date.days_since_epoch() * 24 * 60 + hour * 60 + minute
+    }
+}
+
+impl Display for Timestamp<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (open, close) = if self.active { ('<', '>') } else { ('[', ']') };
+        write!(f, "{open}{}", self.start)?;
+        if let Some(TimestampEnd::Time(end_time)) = &self.end {
+            write!(f, "-{end_time}")?;
+        }
+        if let Some(repeater) = &self.repeater {
+            write!(f, " {repeater}")?;
+        }
+        if let Some(warning) = &self.warning {
+            write!(f, " {warning}")?;
+        }
+        write!(f, "{close}")?;
+
+        if let Some(TimestampEnd::Date {
+            end,
+            repeater,
+            warning,
+        }) = &self.end
+        {
+            write!(f, "--{open}{end}")?;
+            if let Some(repeater) = repeater {
+                write!(f, " {repeater}")?;
+            }
+            if let Some(warning) = warning {
+                write!(f, " {warning}")?;
+            }
+            write!(f, "{close}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DateTime<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.date)?;
+        if let Some(time) = &self.time {
+            write!(f, " {time}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Date<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)?;
+        if let Some(dayname) = self.dayname {
+            write!(f, " {dayname}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl Display for Repeater {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            RepeaterKind::Cumulate => "+",
+            RepeaterKind::CatchUp => "++",
+            RepeaterKind::Restart => ".+",
+        };
+        write!(f, "{kind}{}{}", self.value, self.unit)
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            WarningKind::All => "-",
+            WarningKind::First => "--",
+        };
+        write!(f, "{kind}{}{}", self.value, self.unit)
+    }
+}
+
+impl Display for TimeUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TimeUnit::Hour => "h",
+            TimeUnit::Day => "d",
+            TimeUnit::Week => "w",
+            TimeUnit::Month => "m",
+            TimeUnit::Year => "y",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object::{
+        Date, DateTime, Repeater, RepeaterKind, Time, TimeUnit, Timestamp, TimestampEnd, Warning,
+        WarningKind,
+    };
+    use crate::types::Expr;
+    use crate::{expr_in_pool, parse_org};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn basic_timestamp() {
+        let input = "<2024-03-01 Fri 10:00>";
+        let parsed = parse_org(input);
+        let ts = expr_in_pool!(parsed, Timestamp).unwrap();
+
+        assert_eq!(
+            ts,
+            &Timestamp {
+                active: true,
+                start: DateTime {
+                    date: Date {
+                        year: 2024,
+                        month: 3,
+                        day: 1,
+                        dayname: Some("Fri"),
+                    },
+                    time: Some(Time {
+                        hour: 10,
+                        minute: 0
+                    }),
+                },
+                repeater: None,
+                warning: None,
+                end: None,
+            }
+        );
+    }
+
+    #[test]
+    fn inactive_no_dayname() {
+        let input = "text [2024-03-01] more text";
+        let parsed = parse_org(input);
+        let ts = expr_in_pool!(parsed, Timestamp).unwrap();
+
+        assert!(!ts.active);
+        assert_eq!(ts.start.date.dayname, None);
+        assert_eq!(ts.start.time, None);
+    }
+
+    #[test]
+    fn timestamp_cookies() {
+        let input = "<2024-03-01 Fri .+1w --2d>";
+        let parsed = parse_org(input);
+        let ts = expr_in_pool!(parsed, Timestamp).unwrap();
+
+        assert_eq!(
+            ts.repeater,
+            Some(Repeater {
+                kind: RepeaterKind::Restart,
+                value: 1,
+                unit: TimeUnit::Week
+            })
+        );
+        assert_eq!(
+            ts.warning,
+            Some(Warning {
+                kind: WarningKind::First,
+                value: 2,
+                unit: TimeUnit::Day
+            })
+        );
+    }
+
+    #[test]
+    fn timestamp_time_range() {
+        let input = "<2024-03-01 Fri 10:00-12:30 ++1m>";
+        let parsed = parse_org(input);
+        let ts = expr_in_pool!(parsed, Timestamp).unwrap();
+
+        assert_eq!(
+            ts.end,
+            Some(TimestampEnd::Time(Time {
+                hour: 12,
+                minute: 30
+            }))
+        );
+        assert_eq!(ts.repeater.unwrap().kind, RepeaterKind::CatchUp);
+    }
+
+    #[test]
+    fn timestamp_date_range() {
+        let input = "[2024-03-01 Fri]--[2024-03-04 Mon 9:15]";
+        let parsed = parse_org(input);
+        let ts = expr_in_pool!(parsed, Timestamp).unwrap();
+
+        let Some(TimestampEnd::Date { end, .. }) = &ts.end else {
+            panic!()
+        };
+        assert_eq!(end.date.day, 4);
+        assert_eq!(
+            end.time,
+            Some(Time {
+                hour: 9,
+                minute: 15
+            })
+        );
+    }
+
+    #[test]
+    fn mismatched_range() {
+        // the second half isn't the same kind of timestamp, so no range
+        let input = "<2024-03-01 Fri>--[2024-03-04 Mon]";
+        let parsed = parse_org(input);
+        let ts = expr_in_pool!(parsed, Timestamp).unwrap();
+
+        assert_eq!(ts.end, None);
+    }
+
+    #[test]
+    fn invalid_timestamps() {
+        for input in [
+            "<2024-3-01>",
+            "<2024-13-01>",
+            "<2024-03-01 Fri 25:00>",
+            "<2024-03-01 +1x>",
+            "<2024-03-01Fri>",
+            "<2024-03-01 Fri",
+            "<2024-03-01 Fri\n>",
+        ] {
+            let parsed = parse_org(input);
+            assert!(expr_in_pool!(parsed, Timestamp).is_none(), "{input}");
+        }
+    }
+
+    #[test]
+    fn date_arithmetic() {
+        let date = |year, month, day| Date {
+            year,
+            month,
+            day,
+            dayname: None,
+        };
+        assert_eq!(date(1970, 1, 1).days_since_epoch(), 0);
+        assert_eq!(date(2000, 3, 1).days_since_epoch(), 11017);
+        assert_eq!(
+            date(2024, 3, 1).days_since_epoch() - date(2024, 2, 28).days_since_epoch(),
+            2
+        );
+
+        let dt = DateTime {
+            date: date(1970, 1, 2),
+            time: Some(Time {
+                hour: 1,
+                minute: 30,
+            }),
+        };
+        assert_eq!(dt.minutes_since_epoch(), 24 * 60 + 90);
+    }
+
+    #[test]
+    fn timestamp_display() {
+        for input in [
+            "<2024-03-01 Fri 10:00>",
+            "[2024-03-01]",
+            "<2024-03-01 Fri 10:00-12:00 +1w -2d>",
+            "<2024-03-01 Fri>--<2024-03-04 Mon>",
+            "<2024-03-01 Fri .+1d>--<2024-03-04 Mon .+1d>",
+        ] {
+            let parsed = parse_org(input);
+            let ts = expr_in_pool!(parsed, Timestamp).unwrap();
+            assert_eq!(ts.to_string(), input);
+        }
+    }
+}
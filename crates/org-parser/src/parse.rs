@@ -5,13 +5,13 @@ use crate::constants::{
 use crate::node_pool::NodeID;
 
 use crate::element::{
-    Block, Comment, Drawer, FootnoteDef, Heading, Item, Keyword, LatexEnv, Paragraph, PlainList,
-    Table,
+    BabelCall, Block, Clock, Comment, Drawer, DynamicBlock, FixedWidth, FootnoteDef, Heading,
+    InlineTask, Item, Keyword, LatexEnv, Paragraph, PlainList, Table,
 };
 use crate::object::{
-    Bold, Code, Emoji, ExportSnippet, FootnoteRef, InlineSrc, Italic, LatexFragment, MacroCall,
-    RegularLink, StrikeThrough, Subscript, Superscript, Target, Underline, Verbatim,
-    parse_angle_link, parse_plain_link,
+    Bold, Citation, Code, Emoji, ExportSnippet, FootnoteRef, InlineBabelCall, InlineSrc, Italic,
+    LatexFragment, MacroCall, RadioTarget, RegularLink, StatisticsCookie, StrikeThrough, Subscript,
+    Superscript, Target, Timestamp, Underline, Verbatim, parse_angle_link, parse_plain_link,
 };
 use crate::types::{Cursor, Expr, MarkupKind, MatchError, ParseOpts, Parseable, Parser, Result};
 use crate::utils::verify_markup;
@@ -95,6 +95,10 @@ pub(crate) fn parse_element<'a>(
                 if let ret @ Ok(_) = PlainList::parse(parser, cursor, parent, new_opts) {
                     return ret;
                 }
+            } else if let ret @ Ok(_) =
+                InlineTask::parse(parser, cursor, parent, ParseOpts::default())
+            {
+                return ret;
             } else if let ret @ Ok(_) = Heading::parse(parser, cursor, parent, ParseOpts::default())
             {
                 return ret;
@@ -134,12 +138,20 @@ pub(crate) fn parse_element<'a>(
             }
         }
         chr if chr.is_ascii_alphanumeric() => {
-            if let ret @ Ok(_) = PlainList::parse(parser, cursor, parent, new_opts) {
+            if chr == b'C'
+                && let ret @ Ok(_) = Clock::parse(parser, cursor, parent, no_para_opts)
+            {
+                return ret;
+            } else if let ret @ Ok(_) = PlainList::parse(parser, cursor, parent, new_opts) {
                 return ret;
             }
         }
         POUND => {
-            if let ret @ Ok(_) = Keyword::parse(parser, cursor, parent, no_para_opts) {
+            if let ret @ Ok(_) = BabelCall::parse(parser, cursor, parent, no_para_opts) {
+                return ret;
+            } else if let ret @ Ok(_) = DynamicBlock::parse(parser, cursor, parent, no_para_opts) {
+                return ret;
+            } else if let ret @ Ok(_) = Keyword::parse(parser, cursor, parent, no_para_opts) {
                 return ret;
             } else if let ret @ Ok(_) = Block::parse(parser, cursor, parent, no_para_opts) {
                 return ret;
@@ -160,6 +172,8 @@ pub(crate) fn parse_element<'a>(
         COLON => {
             if let ret @ Ok(_) = Drawer::parse(parser, cursor, parent, no_para_opts) {
                 return ret;
+            } else if let ret @ Ok(_) = FixedWidth::parse(parser, cursor, parent, no_para_opts) {
+                return ret;
             }
         }
         LBRACK => {
@@ -235,6 +249,16 @@ pub(crate) fn parse_object<'a>(
                 return ret;
             } else if let ret @ Ok(_) = FootnoteRef::parse(parser, cursor, parent, parse_opts) {
                 return ret;
+            } else if let ret @ Ok(_) = Timestamp::parse(parser, cursor, parent, parse_opts) {
+                return ret;
+            } else if let ret @ Ok(_) = StatisticsCookie::parse(parser, cursor, parent, parse_opts)
+            {
+                return ret;
+            } else if !parse_opts.markup.contains(MarkupKind::Link)
+                && let ret @ Ok(_) = Citation::parse(parser, cursor, parent, parse_opts)
+            {
+                // citations aren't allowed in link descriptions
+                return ret;
             }
         }
         RBRACK => {
@@ -299,8 +323,12 @@ pub(crate) fn parse_object<'a>(
         LANGLE => {
             if let ret @ Ok(_) = parse_angle_link(parser, cursor, parent, parse_opts) {
                 return ret;
+            } else if let ret @ Ok(_) = RadioTarget::parse(parser, cursor, parent, parse_opts) {
+                return ret;
             } else if let ret @ Ok(_) = Target::parse(parser, cursor, parent, parse_opts) {
                 return ret;
+            } else if let ret @ Ok(_) = Timestamp::parse(parser, cursor, parent, parse_opts) {
+                return ret;
             }
         }
         VBAR => {
@@ -328,6 +356,11 @@ pub(crate) fn parse_object<'a>(
                 return ret;
             }
         }
+        b'c' => {
+            if let ret @ Ok(_) = InlineBabelCall::parse(parser, cursor, parent, parse_opts) {
+                return ret;
+            }
+        }
         LBRACE => {
             if let ret @ Ok(_) = MacroCall::parse(parser, cursor, parent, parse_opts) {
                 return ret;
@@ -102,6 +102,9 @@ pub struct Parser<'a> {
     /// A map of target names to their unique uuids.
     pub targets: HashMap<&'a str, Rc<str>>,
 
+    /// The contents of every [`RadioTarget`] in the document, in order of appearance.
+    pub radio_targets: Vec<&'a str>,
+
     /// uuids to number of times they occur
     /// used to help ensure no duplicates are being inserted
     pub(crate) target_occurences: HashMap<Rc<str>, usize>,
@@ -118,6 +121,31 @@ pub struct Parser<'a> {
     /// A map of footnote labels to [`FootnoteDef`]s.
     pub footnotes: HashMap<&'a str, NodeID>,
 
+    /// A map of `#+name:` values to the elements they name.
+    pub names: HashMap<&'a str, NodeID>,
+
+    /// A map of source blocks and babel calls to their `#+RESULTS:` keywords, and of
+    /// inline source blocks to the `{{{results(...)}}}` macros following them.
+    pub block_results: HashMap<NodeID, NodeID>,
+
+    /// A map of elements to the `#+HEADER:` lines above them, in order.
+    pub block_headers: HashMap<NodeID, Vec<&'a str>>,
+
+    /// A map of coderef labels in src and example blocks to where they're defined.
+    pub coderefs: HashMap<&'a str, Coderef>,
+
+    /// The TODO keywords recognized in headings.
+    pub todo_keywords: TodoKeywords<'a>,
+
+    /// Tags from `#+FILETAGS:`, inherited by every heading.
+    pub file_tags: Vec<&'a str>,
+
+    /// The tags declared with `#+TAGS:`.
+    pub tag_config: TagConfig<'a>,
+
+    /// Properties set with `#+PROPERTY:`, inherited by every heading.
+    pub properties: PropertyDrawer<'a>,
+
     /// The original source text
     ///
     /// Useful for referencing the captured area of a [`Node`], since it stores spans.
@@ -443,13 +471,15 @@ pub enum Expr<'a> {
     Underline(Underline),
     PlainList(PlainList),
     Item(Item<'a>),
-    Table(Table),
+    Table(Table<'a>),
     TableRow(TableRow),
     TableCell(TableCell),
     PlainLink(PlainLink<'a>),
     Superscript(Superscript<'a>),
     Subscript(Subscript<'a>),
     Drawer(Drawer<'a>),
+    DynamicBlock(DynamicBlock<'a>),
+    InlineTask(InlineTask<'a>),
     Affiliated(Affiliated<'a>),
     FootnoteDef(FootnoteDef<'a>),
     FootnoteRef(FootnoteRef<'a>),
@@ -474,6 +504,16 @@ pub enum Expr<'a> {
     Macro(MacroCall<'a>),
     ExportSnippet(ExportSnippet<'a>),
     MacroDef(MacroDef<'a>),
+    Timestamp(Timestamp<'a>),
+    Clock(Clock<'a>),
+    RadioTarget(RadioTarget<'a>),
+    RadioLink(RadioLink<'a>),
+    Citation(Citation<'a>),
+    CitationReference(CitationReference<'a>),
+    StatisticsCookie(StatisticsCookie),
+    BabelCall(BabelCall<'a>),
+    InlineBabelCall(InlineBabelCall<'a>),
+    FixedWidth(FixedWidth<'a>),
 }
 
 // TODO: maybe make all fields bitflags for space optimization
@@ -585,8 +625,8 @@ impl<'a> Expr<'a> {
             Expr::Item(item) => Some(&mut item.children),
             Expr::Table(inner) => Some(&mut inner.children),
             Expr::TableRow(inner) => match inner {
-                TableRow::Rule => None,
                 TableRow::Standard(stan) => Some(stan),
+                TableRow::Rule | TableRow::Cookies(_) | TableRow::ColumnGroups(_) => None,
             },
             Expr::TableCell(inner) => Some(&mut inner.0),
             Expr::Superscript(inner) => match &mut inner.0 {
@@ -598,8 +638,11 @@ impl<'a> Expr<'a> {
                 PlainOrRec::Rec(rec) => Some(rec),
             },
             Expr::Drawer(inner) => Some(&mut inner.children),
+            Expr::DynamicBlock(inner) => Some(&mut inner.children),
+            Expr::InlineTask(inner) => inner.children.as_mut(),
             Expr::FootnoteDef(inner) => Some(&mut inner.children),
             Expr::FootnoteRef(inner) => inner.children.as_mut(),
+            Expr::Citation(inner) => Some(&mut inner.references),
             _ => None,
         }
     }
@@ -625,8 +668,8 @@ impl<'a> Expr<'a> {
             Expr::Item(item) => Some(&item.children),
             Expr::Table(inner) => Some(&inner.children),
             Expr::TableRow(inner) => match &inner {
-                TableRow::Rule => None,
                 TableRow::Standard(stan) => Some(stan),
+                TableRow::Rule | TableRow::Cookies(_) | TableRow::ColumnGroups(_) => None,
             },
             Expr::TableCell(inner) => Some(&inner.0),
             Expr::Superscript(inner) => match &inner.0 {
@@ -638,8 +681,11 @@ impl<'a> Expr<'a> {
                 PlainOrRec::Rec(rec) => Some(rec),
             },
             Expr::Drawer(inner) => Some(&inner.children),
+            Expr::DynamicBlock(inner) => Some(&inner.children),
+            Expr::InlineTask(inner) => inner.children.as_ref(),
             Expr::FootnoteDef(inner) => Some(&inner.children),
             Expr::FootnoteRef(inner) => inner.children.as_ref(),
+            Expr::Citation(inner) => Some(&inner.references),
             _ => None,
         }
     }
@@ -660,8 +706,11 @@ impl<'a> Expr<'a> {
                 println!("Heading {{");
                 println!("heading_level: {:#?}", inner.heading_level);
                 println!("keyword: {:#?}", inner.keyword);
+                println!("done: {:#?}", inner.done);
                 println!("priority: {:#?}", inner.priority);
+                println!("comment: {:#?}", inner.comment);
                 println!("tags: {:#?}", inner.tags);
+                println!("planning: {:#?}", inner.planning);
                 println!("properties: {:#?}", inner.properties);
                 print!("title: ");
                 if let Some(title) = &inner.title {
@@ -789,13 +838,15 @@ impl<'a> Expr<'a> {
             }
 
             Expr::TableRow(inner) => {
-                if let TableRow::Standard(stans) = inner {
-                    print!("|");
-                    for id in stans {
-                        pool[*id].obj.print_tree(pool);
+                match inner {
+                    TableRow::Standard(stans) => {
+                        print!("|");
+                        for id in stans {
+                            pool[*id].obj.print_tree(pool);
+                        }
                     }
-                } else {
-                    print!("h-rule");
+                    TableRow::Rule => print!("h-rule"),
+                    TableRow::Cookies(_) | TableRow::ColumnGroups(_) => print!("{inner:?}"),
                 }
 
                 println!();
@@ -812,6 +863,15 @@ impl<'a> Expr<'a> {
             Expr::Target(inner) => print!("{inner:#?}"),
             Expr::Macro(inner) => print!("{inner:#?}"),
             Expr::Drawer(inner) => print!("{inner:#?}"),
+            Expr::InlineTask(inner) => print!("{inner:#?}"),
+            Expr::DynamicBlock(inner) => {
+                print!("DynamicBlock{{");
+                for id in &inner.children {
+                    pool[*id].obj.print_tree(pool);
+                    print!(", ");
+                }
+                print!("}}");
+            }
             Expr::ExportSnippet(inner) => print!("{inner:#?}"),
             Expr::Affiliated(inner) => match inner {
                 Affiliated::Name(_) => print!("{inner:#?}"),
@@ -824,10 +884,28 @@ impl<'a> Expr<'a> {
                     backend,
                     val,
                 } => print!("{inner:#?}"),
+                Affiliated::Results { .. } | Affiliated::Header { .. } => print!("{inner:#?}"),
             },
             Expr::MacroDef(inner) => print!("{inner:#?}"),
             Expr::FootnoteDef(inner) => print!("{inner:#?}"),
             Expr::FootnoteRef(inner) => print!("{inner:#?}"),
+            Expr::Timestamp(inner) => print!("{inner:#?}"),
+            Expr::Clock(inner) => print!("{inner:#?}"),
+            Expr::RadioTarget(inner) => print!("{inner:#?}"),
+            Expr::RadioLink(inner) => print!("{inner:#?}"),
+            Expr::Citation(inner) => {
+                print!("Citation{{");
+                for id in &inner.references {
+                    pool[*id].obj.print_tree(pool);
+                    print!(", ");
+                }
+                print!("}}");
+            }
+            Expr::CitationReference(inner) => print!("{inner:#?}"),
+            Expr::StatisticsCookie(inner) => print!("{inner:#?}"),
+            Expr::BabelCall(inner) => print!("{inner:#?}"),
+            Expr::InlineBabelCall(inner) => print!("{inner:#?}"),
+            Expr::FixedWidth(inner) => print!("{inner:#?}"),
         }
     }
 }
@@ -878,11 +956,23 @@ impl<'a> std::fmt::Debug for Expr<'a> {
                 Expr::Target(inner) => f.write_fmt(format_args!("{inner:#?}")),
                 Expr::Macro(inner) => f.write_fmt(format_args!("{inner:#?}")),
                 Expr::Drawer(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::DynamicBlock(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::InlineTask(inner) => f.write_fmt(format_args!("{inner:#?}")),
                 Expr::ExportSnippet(inner) => f.write_fmt(format_args!("{inner:#?}")),
                 Expr::Affiliated(inner) => f.write_fmt(format_args!("{inner:#?}")),
                 Expr::MacroDef(inner) => f.write_fmt(format_args!("{inner:#?}")),
                 Expr::FootnoteDef(inner) => f.write_fmt(format_args!("{inner:#?}")),
                 Expr::FootnoteRef(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::Timestamp(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::Clock(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::RadioTarget(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::RadioLink(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::Citation(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::CitationReference(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::StatisticsCookie(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::BabelCall(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::InlineBabelCall(inner) => f.write_fmt(format_args!("{inner:#?}")),
+                Expr::FixedWidth(inner) => f.write_fmt(format_args!("{inner:#?}")),
             }
         } else {
             match self {
@@ -923,9 +1013,21 @@ impl<'a> std::fmt::Debug for Expr<'a> {
                 Expr::Target(inner) => f.write_fmt(format_args!("{inner:?}")),
                 Expr::Macro(inner) => f.write_fmt(format_args!("{inner:?}")),
                 Expr::Drawer(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::DynamicBlock(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::InlineTask(inner) => f.write_fmt(format_args!("{inner:?}")),
                 Expr::ExportSnippet(inner) => f.write_fmt(format_args!("{inner:?}")),
                 Expr::Affiliated(inner) => f.write_fmt(format_args!("{inner:?}")),
                 Expr::MacroDef(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::Timestamp(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::Clock(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::RadioTarget(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::RadioLink(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::Citation(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::CitationReference(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::StatisticsCookie(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::BabelCall(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::InlineBabelCall(inner) => f.write_fmt(format_args!("{inner:?}")),
+                Expr::FixedWidth(inner) => f.write_fmt(format_args!("{inner:?}")),
             }
         }
     }
@@ -957,6 +1059,8 @@ mod object {
             const TimeStamp         = 1 << 15;
             const Markup            = 1 << 16;
             const Plain             = 1 << 17;
+            const RadioTarget       = 1 << 18;
+            const RadioLink         = 1 << 19;
         }
     }
     const ALL: Object = Object::all();
@@ -977,6 +1081,7 @@ mod object {
             | Object::FootnoteReference.bits()
             | Object::Link.bits()
             | Object::Macro.bits()
+            | Object::RadioTarget.bits()
             | Object::Target.bits()
             | Object::TimeStamp.bits(),
     ));